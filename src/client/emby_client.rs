@@ -10,6 +10,10 @@ use anyhow::{
     anyhow,
     Result,
 };
+use chrono::{
+    DateTime,
+    Utc,
+};
 use once_cell::sync::Lazy;
 use reqwest::{
     header::HeaderValue,
@@ -50,13 +54,19 @@ use super::{
         List,
         LiveMedia,
         LoginResponse,
+        LyricsResponse,
         Media,
+        MediaSource,
         MissingEpisodesList,
         PublicServerInfo,
         RemoteSearchInfo,
+        RemoteSubtitleInfo,
         ScheduledTask,
         ServerInfo,
+        SessionInfo,
         SimpleListItem,
+        SyncPlayGroup,
+        UtcTime,
     },
     Account,
     ReqClient,
@@ -89,6 +99,11 @@ pub static DEVICE_ID: Lazy<String> = Lazy::new(|| {
 });
 
 const PROFILE: &str = include_str!("stream_profile.json");
+/// What the universal audio endpoint may send as is, `container|codec`.
+const UNIVERSAL_AUDIO_CONTAINERS: &str =
+    "opus,mp3|mp3,mp2,mp3|mp2,m4a|aac,mp4|aac,flac,webma,webm,wav|PCM_S16LE,wav|PCM_S24LE,ogg";
+/// Songs asked for per instant mix, a radio asks again as it runs out.
+const INSTANT_MIX_LIMIT: &str = "100";
 const CLIENT_ID: &str = "Tsukimi";
 
 static DEVICE_NAME: Lazy<String> = Lazy::new(|| {
@@ -116,6 +131,7 @@ pub struct EmbyClient {
     pub user_access_token: Mutex<String>,
     pub server_name: Mutex<String>,
     pub server_name_hash: Mutex<String>,
+    pub server_type: Mutex<Option<String>>,
 }
 
 fn generate_emby_authorization(
@@ -159,6 +175,7 @@ impl EmbyClient {
             user_access_token: Mutex::new(String::new()),
             server_name: Mutex::new(String::new()),
             server_name_hash: Mutex::new(String::new()),
+            server_type: Mutex::new(None),
         }
     }
 
@@ -170,6 +187,7 @@ impl EmbyClient {
         self.set_user_password(&account.password)?;
         self.set_user_access_token(&account.access_token)?;
         self.set_server_name(&account.servername)?;
+        self.set_server_type(account.server_type.as_deref())?;
         crate::ui::provider::set_admin(false);
         spawn_tokio_without_await(async move {
             match EMBY_CLIENT.authenticate_admin().await {
@@ -276,6 +294,19 @@ impl EmbyClient {
         Ok(())
     }
 
+    pub fn set_server_type(&self, server_type: Option<&str>) -> Result<()> {
+        let mut server_type_lock = self
+            .server_type
+            .lock()
+            .map_err(|_| anyhow!("Failed to acquire lock on server_type"))?;
+        *server_type_lock = server_type.map(|s| s.to_string());
+        Ok(())
+    }
+
+    pub fn is_jellyfin(&self) -> bool {
+        self.server_type.lock().unwrap().as_deref() == Some("Jellyfin")
+    }
+
     pub fn get_url_and_headers(&self) -> Result<(Url, reqwest::header::HeaderMap)> {
         let url = self
             .url
@@ -486,6 +517,15 @@ impl EmbyClient {
         self.request(&path, &params).await
     }
 
+    /// Parents of an item up to the root, its library is the
+    /// `CollectionFolder` among them.
+    pub async fn get_ancestors(&self, id: &str) -> Result<Vec<SimpleListItem>> {
+        let path = format!("Items/{}/Ancestors", id);
+        let user_id = self.user_id();
+        let params = [("UserId", user_id.as_str())];
+        self.request(&path, &params).await
+    }
+
     pub async fn get_edit_info(&self, id: &str) -> Result<Value> {
         let path = format!("Users/{}/Items/{}", self.user_id(), id);
         let params = [("Fields", "ChannelMappingInfo")];
@@ -645,23 +685,87 @@ impl EmbyClient {
         path.to_string_lossy().to_string()
     }
 
-    pub async fn get_artist_albums(&self, id: &str, artist_id: &str) -> Result<List> {
+    /// Albums of `artist_id`, newest first. `limit` of `None` returns the
+    /// whole discography.
+    pub async fn get_artist_albums(
+        &self, exclude_id: Option<&str>, artist_id: &str, limit: Option<u32>,
+    ) -> Result<List> {
         let path = format!("Users/{}/Items", self.user_id());
-        let params = [
+        let limit = limit.map(|limit| limit.to_string());
+        let mut params = vec![
             ("IncludeItemTypes", "MusicAlbum"),
             ("Recursive", "true"),
             ("ImageTypeLimit", "1"),
-            ("Limit", "12"),
             ("SortBy", "ProductionYear,SortName"),
             ("EnableImageTypes", "Primary,Backdrop,Thumb,Banner"),
             ("SortOrder", "Descending"),
             (
                 "Fields",
-                "BasicSyncInfo,CanDelete,PrimaryImageAspectRatio,ProductionYear",
+                "BasicSyncInfo,CanDelete,PrimaryImageAspectRatio,ProductionYear,ChildCount",
             ),
             ("AlbumArtistIds", artist_id),
-            ("ExcludeItemIds", id),
         ];
+        if let Some(limit) = &limit {
+            params.push(("Limit", limit));
+        }
+        if let Some(exclude_id) = exclude_id {
+            params.push(("ExcludeItemIds", exclude_id));
+        }
+        self.request(&path, &params).await
+    }
+
+    /// Albums `artist_id` plays on, including those of other album artists.
+    pub async fn get_artist_appearances(&self, artist_id: &str) -> Result<List> {
+        let path = format!("Users/{}/Items", self.user_id());
+        let artist_filter = if self.is_jellyfin() {
+            "ContributingArtistIds"
+        } else {
+            "ArtistIds"
+        };
+        let params = [
+            ("IncludeItemTypes", "MusicAlbum"),
+            ("Recursive", "true"),
+            ("ImageTypeLimit", "1"),
+            ("SortBy", "ProductionYear,SortName"),
+            ("EnableImageTypes", "Primary,Backdrop,Thumb,Banner"),
+            ("SortOrder", "Descending"),
+            (
+                "Fields",
+                "BasicSyncInfo,CanDelete,PrimaryImageAspectRatio,ProductionYear,ChildCount",
+            ),
+            (artist_filter, artist_id),
+        ];
+        self.request(&path, &params).await
+    }
+
+    /// Songs of the library `parent_id` for the songs table.
+    pub async fn get_library_songs(
+        &self, parent_id: &str, start: u32, sort_by: &str, sort_order: &str, search: &str,
+        favorites: bool,
+    ) -> Result<List> {
+        let path = format!("Users/{}/Items", self.user_id());
+        let start = start.to_string();
+        let mut params = vec![
+            ("IncludeItemTypes", "Audio"),
+            ("Recursive", "true"),
+            ("ParentId", parent_id),
+            ("StartIndex", &start),
+            ("Limit", "100"),
+            ("SortBy", sort_by),
+            ("SortOrder", sort_order),
+            ("ImageTypeLimit", "1"),
+            ("EnableImageTypes", "Primary"),
+            (
+                "Fields",
+                "BasicSyncInfo,CanDelete,PrimaryImageAspectRatio,CommunityRating",
+            ),
+        ];
+        if !search.is_empty() {
+            params.push(("SearchTerm", search));
+        }
+        if favorites {
+            params.push(("Filters", "IsFavorite"));
+        }
         self.request(&path, &params).await
     }
 
@@ -733,6 +837,45 @@ impl EmbyClient {
         self.post(&path, &params, json! {value}).await
     }
 
+    pub async fn search_remote_subtitles(
+        &self, id: &str, media_source_id: &str, language: &str,
+    ) -> Result<Vec<RemoteSubtitleInfo>> {
+        let path = format!("Items/{}/RemoteSearch/Subtitles/{}", id, language);
+        let params = [
+            ("MediaSourceId", media_source_id),
+            ("IsPerfectMatch", "false"),
+        ];
+        self.request(&path, &params).await
+    }
+
+    pub async fn download_remote_subtitles(
+        &self, id: &str, media_source_id: &str, subtitle_id: &str,
+    ) -> Result<()> {
+        let path = format!("Items/{}/RemoteSearch/Subtitles/{}", id, subtitle_id);
+        let params = [("MediaSourceId", media_source_id)];
+        self.post(&path, &params, json!({}))
+            .await?
+            .error_for_status()?;
+        Ok(())
+    }
+
+    /// `data` is the base64 encoded subtitle file.
+    pub async fn upload_subtitle(
+        &self, id: &str, language: &str, format: &str, is_forced: bool, is_hearing_impaired: bool,
+        data: &str,
+    ) -> Result<()> {
+        let path = format!("Videos/{}/Subtitles", id);
+        let body = json!({
+            "Language": language,
+            "Format": format,
+            "IsForced": is_forced,
+            "IsHearingImpaired": is_hearing_impaired,
+            "Data": data,
+        });
+        self.post(&path, &[], body).await?.error_for_status()?;
+        Ok(())
+    }
+
     pub async fn get_user_avatar(&self) -> Result<String> {
         let path = format!("Users/{}/Images/Primary", self.user_id());
         let params = [("maxHeight", "50"), ("maxWidth", "50")];
@@ -817,7 +960,10 @@ impl EmbyClient {
         let path = match list_type {
             ListType::All => format!("Users/{}/Items", user_id),
             ListType::Resume => format!("Users/{}/Items/Resume", user_id),
+            ListType::Genres if include_item_types == "MusicAlbum" => "MusicGenres".to_string(),
             ListType::Genres => "Genres".to_string(),
+            ListType::AlbumArtists => "Artists/AlbumArtists".to_string(),
+            ListType::Artists => "Artists".to_string(),
             _ => format!("Users/{}/Items", user_id),
         };
         let include_item_type = match list_type {
@@ -875,6 +1021,20 @@ impl EmbyClient {
                 ("Recursive", "true"),
                 ("ParentId", id),
             ],
+            ListType::AlbumArtists | ListType::Artists => vec![
+                (
+                    "Fields",
+                    "BasicSyncInfo,CanDelete,PrimaryImageAspectRatio,SortName",
+                ),
+                ("StartIndex", &start_string),
+                ("ImageTypeLimit", "1"),
+                ("EnableImageTypes", "Primary,Backdrop,Thumb,Banner"),
+                ("Limit", "50"),
+                ("SortBy", "SortName"),
+                ("SortOrder", sort_order),
+                ("UserId", user_id),
+                ("ParentId", id),
+            ],
             _ => vec![],
         };
         let kv = filters_list.to_kv();
@@ -904,7 +1064,7 @@ impl EmbyClient {
             ("SortBy", sortby),
             ("SortOrder", sort_order),
             ("EnableImageTypes", "Primary,Backdrop,Thumb,Banner"),
-            if listtype == "Genres" || listtype == "Genre" {
+            if listtype == "Genres" || listtype == "Genre" || listtype == "MusicGenre" {
                 ("GenreIds", parentid)
             } else if listtype == "Studios" {
                 ("StudioIds", parentid)
@@ -968,7 +1128,7 @@ impl EmbyClient {
             BackType::Back => "Sessions/Playing/Progress".to_string(),
         };
         let params = [("reqformat", "json")];
-        let body = json!({"VolumeLevel":100,"NowPlayingQueue":[],"IsMuted":false,"IsPaused":false,"MaxStreamingBitrate":2147483647,"RepeatMode":"RepeatNone","PlaybackStartTimeTicks":back.start_tick,"SubtitleOffset":0,"PlaybackRate":1,"PositionTicks":back.tick,"PlayMethod":"DirectStream","PlaySessionId":back.playsessionid,"MediaSourceId":back.mediasourceid,"PlaylistIndex":0,"PlaylistLength":1,"CanSeek":true,"ItemId":back.id,"Shuffle":false});
+        let body = json!({"VolumeLevel":100,"NowPlayingQueue":[],"IsMuted":false,"IsPaused":back.paused,"MaxStreamingBitrate":2147483647,"RepeatMode":"RepeatNone","PlaybackStartTimeTicks":back.start_tick,"SubtitleOffset":0,"PlaybackRate":1,"PositionTicks":back.tick,"PlayMethod":back.play_method.as_deref().unwrap_or("DirectStream"),"PlaySessionId":back.playsessionid,"MediaSourceId":back.mediasourceid,"PlaylistIndex":0,"PlaylistLength":1,"CanSeek":true,"ItemId":back.id,"Shuffle":false});
         self.post(&path, &params, body).await?;
         Ok(())
     }
@@ -1220,11 +1380,82 @@ impl EmbyClient {
         self.request(&path, &params).await
     }
 
-    pub fn get_song_streaming_uri(&self, id: &str) -> String {
+    /// Songs similar to the item `id`, which may be a song, an album, an
+    /// artist or a genre.
+    pub async fn get_instant_mix(&self, id: &str, item_type: &str) -> Result<List> {
+        let user_id = self.user_id();
+        let mut params = vec![
+            ("UserId", user_id.as_str()),
+            ("Limit", INSTANT_MIX_LIMIT),
+            ("Fields", "PrimaryImageAspectRatio"),
+            ("ImageTypeLimit", "1"),
+            ("EnableTotalRecordCount", "false"),
+        ];
+        let path = if item_type == "MusicArtist" {
+            params.push(("Id", id));
+            "Artists/InstantMix".to_string()
+        } else {
+            format!("Items/{}/InstantMix", id)
+        };
+        self.request(&path, &params).await
+    }
+
+    /// Files of an audiobook in reading order with their chapters, those in
+    /// the folder `folder_id` or just `id` for single file books.
+    pub async fn get_audiobook_files(&self, id: &str, folder_id: Option<&str>) -> Result<List> {
+        let path = format!("Users/{}/Items", &self.user_id());
+        let mut params = vec![
+            ("Fields", "Chapters,PrimaryImageAspectRatio"),
+            ("IncludeItemTypes", "AudioBook,Audio"),
+            ("SortBy", "ParentIndexNumber,IndexNumber,SortName"),
+            ("SortOrder", "Ascending"),
+            ("ImageTypeLimit", "1"),
+            ("EnableTotalRecordCount", "false"),
+        ];
+        match folder_id {
+            Some(folder_id) => {
+                params.push(("ParentId", folder_id));
+                params.push(("Recursive", "true"));
+            }
+            None => params.push(("Ids", id)),
+        }
+        self.request(&path, &params).await
+    }
+
+    pub fn get_song_streaming_uri(&self, id: &str, play_session_id: &str) -> String {
         let url = self.url.lock().unwrap().as_ref().unwrap().clone();
 
-        url.join(&format!("Audio/{}/universal?UserId={}&DeviceId={}&MaxStreamingBitrate=4000000&Container=opus,mp3|mp3,mp2,mp3|mp2,m4a|aac,mp4|aac,flac,webma,webm,wav|PCM_S16LE,wav|PCM_S24LE,ogg&TranscodingContainer=aac&TranscodingProtocol=hls&AudioCodec=aac&api_key={}&PlaySessionId=1715006733496&StartTimeTicks=0&EnableRedirection=true&EnableRemoteMedia=false",
-        id, &self.user_id(), &DEVICE_ID.to_string(), self.user_access_token.lock().unwrap(), )).unwrap().to_string()
+        url.join(&format!("Audio/{}/universal?UserId={}&DeviceId={}&MaxStreamingBitrate=4000000&Container={}&TranscodingContainer=aac&TranscodingProtocol=hls&AudioCodec=aac&api_key={}&PlaySessionId={}&StartTimeTicks=0&EnableRedirection=true&EnableRemoteMedia=false",
+        id, &self.user_id(), &DEVICE_ID.to_string(), UNIVERSAL_AUDIO_CONTAINERS, self.user_access_token.lock().unwrap(), play_session_id)).unwrap().to_string()
+    }
+
+    /// How the universal audio endpoint delivers `source`, for playback
+    /// reports.
+    pub fn audio_play_method(source: &MediaSource) -> &'static str {
+        let codec = source
+            .media_streams
+            .iter()
+            .find(|stream| stream.stream_type == "Audio")
+            .and_then(|stream| stream.codec.as_deref());
+        let containers = source.container.as_deref().unwrap_or_default();
+
+        let direct = UNIVERSAL_AUDIO_CONTAINERS.split(',').any(|profile| {
+            let (container, profile_codec) = match profile.split_once('|') {
+                Some((container, codec)) => (container, Some(codec)),
+                None => (profile, None),
+            };
+            containers
+                .split(',')
+                .any(|c| c.eq_ignore_ascii_case(container))
+                && profile_codec.map_or(true, |profile_codec| {
+                    codec.is_some_and(|codec| codec.eq_ignore_ascii_case(profile_codec))
+                })
+        });
+        if direct {
+            "DirectPlay"
+        } else {
+            "Transcode"
+        }
     }
 
     fn user_id(&self) -> String {
@@ -1389,6 +1620,165 @@ impl EmbyClient {
         ];
         self.request(type_, &params).await
     }
+
+    pub fn websocket_url(&self) -> Result<Url> {
+        let (url, _) = self.get_url_and_headers()?;
+        let path = if self.is_jellyfin() {
+            "../socket"
+        } else {
+            "embywebsocket"
+        };
+        let mut url = url.join(path)?;
+        let scheme = if url.scheme() == "https" { "wss" } else { "ws" };
+        url.set_scheme(scheme)
+            .map_err(|_| anyhow!("Failed to set websocket scheme"))?;
+        url.query_pairs_mut()
+            .append_pair("api_key", &self.user_access_token.lock().unwrap())
+            .append_pair("deviceId", &DEVICE_ID);
+        Ok(url)
+    }
+
+    // jellyfin
+    pub async fn get_lyrics(&self, id: &str) -> Result<LyricsResponse> {
+        let path = format!("Audio/{}/Lyrics", id);
+        self.request(&path, &[]).await
+    }
+
+    pub fn get_subtitle_stream_path(
+        &self, id: &str, source_id: &str, index: u64, codec: &str,
+    ) -> String {
+        format!(
+            "Videos/{}/{}/Subtitles/{}/Stream.{}",
+            id, source_id, index, codec
+        )
+    }
+
+    /// Path to stream a subtitle from without the auth headers, for streams
+    /// the server gives no delivery url.
+    pub fn get_subtitle_delivery_path(
+        &self, id: &str, source_id: &str, index: u64, codec: &str,
+    ) -> String {
+        format!(
+            "{}?api_key={}",
+            self.get_subtitle_stream_path(id, source_id, index, codec),
+            self.user_access_token.lock().unwrap()
+        )
+    }
+
+    /// Raw subtitle stream, Emby keeps the lyrics of songs as one.
+    pub async fn get_subtitle_text(
+        &self, id: &str, source_id: &str, index: u64, codec: &str,
+    ) -> Result<String> {
+        let path = self.get_subtitle_stream_path(id, source_id, index, codec);
+        let request = self.prepare_request(Method::GET, &path, &[])?;
+        let res = self.send_request(request).await?.error_for_status()?;
+        Ok(res.text().await?)
+    }
+
+    pub async fn get_utc_time(&self) -> Result<UtcTime> {
+        self.request("GetUtcTime", &[]).await
+    }
+
+    pub async fn syncplay_list(&self) -> Result<Vec<SyncPlayGroup>> {
+        self.request("SyncPlay/List", &[]).await
+    }
+
+    pub async fn syncplay_new(&self, group_name: &str) -> Result<()> {
+        let body = json!({ "GroupName": group_name });
+        self.post("SyncPlay/New", &[], body)
+            .await?
+            .error_for_status()?;
+        Ok(())
+    }
+
+    pub async fn syncplay_join(&self, group_id: &str) -> Result<()> {
+        let body = json!({ "GroupId": group_id });
+        self.post("SyncPlay/Join", &[], body)
+            .await?
+            .error_for_status()?;
+        Ok(())
+    }
+
+    pub async fn syncplay_leave(&self) -> Result<()> {
+        self.post("SyncPlay/Leave", &[], json!({})).await?;
+        Ok(())
+    }
+
+    pub async fn syncplay_pause(&self) -> Result<()> {
+        self.post("SyncPlay/Pause", &[], json!({})).await?;
+        Ok(())
+    }
+
+    pub async fn syncplay_unpause(&self) -> Result<()> {
+        self.post("SyncPlay/Unpause", &[], json!({})).await?;
+        Ok(())
+    }
+
+    pub async fn syncplay_seek(&self, position_ticks: i64) -> Result<()> {
+        let body = json!({ "PositionTicks": position_ticks });
+        self.post("SyncPlay/Seek", &[], body).await?;
+        Ok(())
+    }
+
+    /// Tells the group we are ready (or not, while buffering) at the position.
+    pub async fn syncplay_ready(
+        &self, ready: bool, when: DateTime<Utc>, position_ticks: i64, is_playing: bool,
+        playlist_item_id: &str,
+    ) -> Result<()> {
+        let body = json!({
+            "When": when,
+            "PositionTicks": position_ticks,
+            "IsPlaying": is_playing,
+            "PlaylistItemId": playlist_item_id,
+        });
+        let path = if ready {
+            "SyncPlay/Ready"
+        } else {
+            "SyncPlay/Buffering"
+        };
+        self.post(path, &[], body).await?;
+        Ok(())
+    }
+
+    pub async fn syncplay_set_new_queue(&self, item_id: &str, position_ticks: i64) -> Result<()> {
+        let body = json!({
+            "PlayingQueue": [item_id],
+            "PlayingItemPosition": 0,
+            "StartPositionTicks": position_ticks,
+        });
+        self.post("SyncPlay/SetNewQueue", &[], body).await?;
+        Ok(())
+    }
+
+    pub async fn get_sessions(&self) -> Result<Vec<SessionInfo>> {
+        let params = [("ActiveWithinSeconds", "960")];
+        self.request("Sessions", &params).await
+    }
+
+    /// Lets other sessions send us playstate commands, Emby only forwards
+    /// them to sessions that support media control.
+    pub async fn post_capabilities(&self) -> Result<()> {
+        let body = json!({
+            "PlayableMediaTypes": ["Audio", "Video"],
+            "SupportedCommands": [],
+            "SupportsMediaControl": true,
+        });
+        self.post("Sessions/Capabilities/Full", &[], body).await?;
+        Ok(())
+    }
+
+    pub async fn send_playstate_command(
+        &self, session_id: &str, command: &str, position_ticks: Option<i64>,
+    ) -> Result<()> {
+        let path = format!("Sessions/{}/Playing/{}", session_id, command);
+        let ticks = position_ticks.map(|ticks| ticks.to_string());
+        let params: Vec<(&str, &str)> = ticks
+            .as_deref()
+            .map(|ticks| vec![("SeekPositionTicks", ticks)])
+            .unwrap_or_default();
+        self.post(&path, &params, json!({})).await?;
+        Ok(())
+    }
 }
 
 #[cfg(test)]
@@ -1436,6 +1826,35 @@ mod tests {
         assert_eq!(url, "http://127.0.0.1");
     }
 
+    #[test]
+    fn audio_play_method() {
+        let source = |container: &str, codec: &str| -> MediaSource {
+            serde_json::from_value(json!({
+                "Id": "1",
+                "Name": "song",
+                "Container": container,
+                "MediaStreams": [{"Type": "Audio", "Codec": codec, "Index": 0, "IsExternal": false}],
+            }))
+            .unwrap()
+        };
+        assert_eq!(
+            EmbyClient::audio_play_method(&source("flac", "flac")),
+            "DirectPlay"
+        );
+        assert_eq!(
+            EmbyClient::audio_play_method(&source("mov,mp4,m4a", "aac")),
+            "DirectPlay"
+        );
+        assert_eq!(
+            EmbyClient::audio_play_method(&source("m4a", "alac")),
+            "Transcode"
+        );
+        assert_eq!(
+            EmbyClient::audio_play_method(&source("wma", "wmav2")),
+            "Transcode"
+        );
+    }
+
     #[tokio::test]
     async fn test_upload_image() {
         let _ = EMBY_CLIENT.header_change_url("http://127.0.0.1", "8096");
@@ -4,6 +4,8 @@ pub mod error;
 pub mod proxy;
 pub mod runtime;
 pub mod structs;
+pub mod syncplay;
+pub mod websocket;
 #[cfg(target_os = "windows")]
 pub mod windows_compat;
 
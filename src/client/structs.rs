@@ -8,6 +8,13 @@ use serde::{
     Serialize,
 };
 
+/// Emby counts time in ticks of 100 nanoseconds.
+pub const TICKS_PER_SECOND: f64 = 10_000_000.0;
+
+pub fn ticks(seconds: f64) -> i64 {
+    (seconds * TICKS_PER_SECOND) as i64
+}
+
 #[derive(Serialize, Deserialize, Clone)]
 pub struct AuthenticateResponse {
     #[serde(rename = "Policy")]
@@ -35,6 +42,12 @@ pub struct MediaStream {
     pub title: Option<String>,
     #[serde(rename = "DisplayLanguage")]
     pub display_language: Option<String>,
+    #[serde(rename = "Language")]
+    pub language: Option<String>,
+    #[serde(rename = "IsForced")]
+    pub is_forced: Option<bool>,
+    #[serde(rename = "IsHearingImpaired")]
+    pub is_hearing_impaired: Option<bool>,
     #[serde(rename = "Codec")]
     pub codec: Option<String>,
     #[serde(rename = "BitRate")]
@@ -229,6 +242,8 @@ pub struct UserData {
     pub unplayed_item_count: Option<u32>,
     #[serde(rename = "IsFavorite")]
     pub is_favorite: Option<bool>,
+    #[serde(rename = "PlayCount")]
+    pub play_count: Option<u32>,
 }
 
 #[derive(Serialize, Deserialize, Clone)]
@@ -335,6 +350,14 @@ pub struct SimpleListItem {
     pub lock_data: Option<bool>,
     #[serde(rename = "PartCount")]
     pub part_count: Option<u32>,
+    /// Track gain in dB computed by the server, Jellyfin only.
+    #[serde(rename = "NormalizationGain")]
+    pub normalization_gain: Option<f64>,
+    /// Tracks of an album.
+    #[serde(rename = "ChildCount")]
+    pub child_count: Option<u32>,
+    #[serde(rename = "Chapters")]
+    pub chapters: Option<Vec<ChapterInfo>>,
 }
 
 #[derive(Serialize, Deserialize, Clone, Default)]
@@ -496,6 +519,9 @@ pub struct Back {
     pub mediasourceid: String,
     pub tick: u64,
     pub start_tick: u64,
+    /// `None` reports `DirectStream`.
+    pub play_method: Option<String>,
+    pub paused: bool,
 }
 
 #[derive(Deserialize)]
@@ -546,6 +572,36 @@ pub struct ImageSearchResultItem {
     pub rating_type: String,
 }
 
+#[derive(Serialize, Deserialize, Clone)]
+pub struct RemoteSubtitleInfo {
+    #[serde(rename = "Id")]
+    pub id: String,
+    #[serde(rename = "Name")]
+    pub name: Option<String>,
+    #[serde(rename = "ProviderName")]
+    pub provider_name: Option<String>,
+    #[serde(rename = "Format")]
+    pub format: Option<String>,
+    #[serde(rename = "Author")]
+    pub author: Option<String>,
+    #[serde(rename = "Comment")]
+    pub comment: Option<String>,
+    #[serde(rename = "DateCreated")]
+    pub date_created: Option<String>,
+    #[serde(rename = "CommunityRating")]
+    pub community_rating: Option<f32>,
+    #[serde(rename = "DownloadCount")]
+    pub download_count: Option<u64>,
+    #[serde(rename = "IsHashMatch")]
+    pub is_hash_match: Option<bool>,
+    #[serde(rename = "IsForced")]
+    pub is_forced: Option<bool>,
+    #[serde(rename = "Language")]
+    pub language: Option<String>,
+    #[serde(rename = "ThreeLetterISOLanguageName")]
+    pub three_letter_iso_language_name: Option<String>,
+}
+
 #[derive(Serialize, Deserialize, Clone)]
 pub struct DeleteInfo {
     #[serde(rename = "Paths")]
@@ -590,6 +646,65 @@ pub struct FilterItem {
     pub id: Option<String>,
 }
 
+#[derive(Serialize, Deserialize, Clone, Default)]
+pub struct SyncPlayGroup {
+    #[serde(rename = "GroupId")]
+    pub group_id: String,
+    #[serde(rename = "GroupName")]
+    pub group_name: String,
+    #[serde(rename = "State", default)]
+    pub state: String,
+    #[serde(rename = "Participants", default)]
+    pub participants: Vec<String>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct LyricLine {
+    #[serde(rename = "Text", default)]
+    pub text: String,
+    /// Ticks, missing for unsynced lyrics.
+    #[serde(rename = "Start")]
+    pub start: Option<u64>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct ChapterInfo {
+    #[serde(rename = "Name")]
+    pub name: Option<String>,
+    #[serde(rename = "StartPositionTicks", default)]
+    pub start_position_ticks: u64,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct LyricsResponse {
+    #[serde(rename = "Lyrics", default)]
+    pub lyrics: Vec<LyricLine>,
+}
+
+#[derive(Serialize, Deserialize, Clone)]
+pub struct UtcTime {
+    #[serde(rename = "RequestReceptionTime")]
+    pub request_reception_time: DateTime<Utc>,
+    #[serde(rename = "ResponseTransmissionTime")]
+    pub response_transmission_time: DateTime<Utc>,
+}
+
+#[derive(Serialize, Deserialize, Clone)]
+pub struct SessionInfo {
+    #[serde(rename = "Id")]
+    pub id: String,
+    #[serde(rename = "DeviceId")]
+    pub device_id: Option<String>,
+    #[serde(rename = "DeviceName")]
+    pub device_name: Option<String>,
+    #[serde(rename = "UserName")]
+    pub user_name: Option<String>,
+    #[serde(rename = "SupportsRemoteControl", default)]
+    pub supports_remote_control: bool,
+    #[serde(rename = "NowPlayingItem")]
+    pub now_playing_item: Option<SimpleListItem>,
+}
+
 use adw::prelude::*;
 use gtk::glib;
 
@@ -0,0 +1,191 @@
+use chrono::{
+    DateTime,
+    TimeDelta,
+    Utc,
+};
+use serde::Deserialize;
+use serde_json::Value;
+
+use super::structs::{
+    UtcTime,
+    TICKS_PER_SECOND,
+};
+
+/// Drift in seconds that is small enough to leave alone.
+const MIN_DRIFT: f64 = 0.1;
+/// Drift in seconds up to which playback speed is nudged, past it we seek.
+const MAX_SPEED_DRIFT: f64 = 2.0;
+/// Seconds a speed correction takes to close the gap, at most 2s of drift
+/// this keeps the speed within 0.8x-1.2x.
+const CORRECTION_TIME: f64 = 10.0;
+
+/// `SyncPlayGroupUpdate` socket message.
+#[derive(Deserialize, Debug, Clone)]
+pub struct GroupUpdate {
+    #[serde(rename = "GroupId")]
+    pub group_id: String,
+    #[serde(rename = "Type")]
+    pub update_type: String,
+    #[serde(rename = "Data", default)]
+    pub data: Value,
+}
+
+#[derive(Deserialize, Debug, Clone, Copy, PartialEq)]
+pub enum CommandType {
+    Unpause,
+    Pause,
+    Stop,
+    Seek,
+}
+
+/// `SyncPlayCommand` socket message, `when` is server time.
+#[derive(Deserialize, Debug, Clone, PartialEq)]
+pub struct SendCommand {
+    #[serde(rename = "GroupId")]
+    pub group_id: String,
+    #[serde(rename = "PlaylistItemId", default)]
+    pub playlist_item_id: String,
+    #[serde(rename = "When")]
+    pub when: DateTime<Utc>,
+    #[serde(rename = "PositionTicks")]
+    pub position_ticks: Option<i64>,
+    #[serde(rename = "Command")]
+    pub command: CommandType,
+}
+
+#[derive(Deserialize, Debug, Clone, Default)]
+pub struct PlayQueueUpdate {
+    #[serde(rename = "Playlist", default)]
+    pub playlist: Vec<QueueItem>,
+    #[serde(rename = "PlayingItemIndex", default)]
+    pub playing_item_index: i32,
+    #[serde(rename = "StartPositionTicks", default)]
+    pub start_position_ticks: i64,
+    #[serde(rename = "IsPlaying", default)]
+    pub is_playing: bool,
+}
+
+#[derive(Deserialize, Debug, Clone)]
+pub struct QueueItem {
+    #[serde(rename = "ItemId")]
+    pub item_id: String,
+    #[serde(rename = "PlaylistItemId")]
+    pub playlist_item_id: String,
+}
+
+impl PlayQueueUpdate {
+    pub fn playing_item(&self) -> Option<&QueueItem> {
+        usize::try_from(self.playing_item_index)
+            .ok()
+            .and_then(|index| self.playlist.get(index))
+    }
+}
+
+/// `Playstate` socket message, how Emby sessions are remote controlled.
+#[derive(Deserialize, Debug, Clone)]
+pub struct PlaystateRequest {
+    #[serde(rename = "Command")]
+    pub command: String,
+    #[serde(rename = "SeekPositionTicks")]
+    pub seek_position_ticks: Option<i64>,
+}
+
+/// Offset between the server clock and ours, measured like NTP does.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct TimeSync {
+    offset: TimeDelta,
+}
+
+impl TimeSync {
+    /// `sent` and `received` are local times around the `GetUtcTime` request.
+    pub fn new(sent: DateTime<Utc>, received: DateTime<Utc>, time: &UtcTime) -> Self {
+        let offset = ((time.request_reception_time - sent)
+            + (time.response_transmission_time - received))
+            / 2;
+        Self { offset }
+    }
+
+    pub fn server_now(&self) -> DateTime<Utc> {
+        self.local_to_server(Utc::now())
+    }
+
+    pub fn local_to_server(&self, local: DateTime<Utc>) -> DateTime<Utc> {
+        local + self.offset
+    }
+
+    pub fn server_to_local(&self, server: DateTime<Utc>) -> DateTime<Utc> {
+        server - self.offset
+    }
+}
+
+/// Where the group is at `now` (server time), in seconds.
+pub fn expected_position(command: &SendCommand, now: DateTime<Utc>) -> f64 {
+    let position = command.position_ticks.unwrap_or_default() as f64 / TICKS_PER_SECOND;
+    if command.command != CommandType::Unpause {
+        return position;
+    }
+    let elapsed = (now - command.when).num_milliseconds() as f64 / 1000.0;
+    position + elapsed.max(0.0)
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Correction {
+    None,
+    /// Play at this speed until caught up.
+    Speed(f64),
+    /// Too far off, seek to this position.
+    Seek(f64),
+}
+
+/// How to catch up with the group, `position` is ours and `expected` theirs.
+pub fn correction(position: f64, expected: f64) -> Correction {
+    let drift = position - expected;
+    if drift.abs() < MIN_DRIFT {
+        Correction::None
+    } else if drift.abs() > MAX_SPEED_DRIFT {
+        Correction::Seek(expected)
+    } else {
+        Correction::Speed(1.0 - drift / CORRECTION_TIME)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_time_sync() {
+        let sent = "2024-01-01T00:00:00Z".parse().unwrap();
+        let received = "2024-01-01T00:00:00.200Z".parse().unwrap();
+        // Server is 1s ahead, 100ms each way
+        let time = UtcTime {
+            request_reception_time: "2024-01-01T00:00:01.100Z".parse().unwrap(),
+            response_transmission_time: "2024-01-01T00:00:01.100Z".parse().unwrap(),
+        };
+        let sync = TimeSync::new(sent, received, &time);
+        assert_eq!(
+            sync.local_to_server(sent),
+            time.request_reception_time - TimeDelta::milliseconds(100)
+        );
+        assert_eq!(
+            sync.server_to_local(sync.local_to_server(received)),
+            received
+        );
+    }
+
+    #[test]
+    fn test_correction() {
+        let command: SendCommand = serde_json::from_str(
+            r#"{"GroupId":"g","PlaylistItemId":"p","When":"2024-01-01T00:00:00Z",
+                "PositionTicks":600000000,"Command":"Unpause","EmittedAt":"2024-01-01T00:00:00Z"}"#,
+        )
+        .unwrap();
+        let now = "2024-01-01T00:00:05Z".parse().unwrap();
+        assert_eq!(expected_position(&command, now), 65.0);
+
+        assert_eq!(correction(65.05, 65.0), Correction::None);
+        assert_eq!(correction(66.0, 65.0), Correction::Speed(0.9));
+        assert_eq!(correction(64.0, 65.0), Correction::Speed(1.1));
+        assert_eq!(correction(70.0, 65.0), Correction::Seek(65.0));
+    }
+}
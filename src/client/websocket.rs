@@ -0,0 +1,85 @@
+use std::time::Duration;
+
+use anyhow::Result;
+use futures_util::{
+    SinkExt,
+    StreamExt,
+};
+use serde::Deserialize;
+use serde_json::{
+    json,
+    Value,
+};
+use tokio_tungstenite::{
+    connect_async,
+    tungstenite::Message,
+};
+use tracing::{
+    debug,
+    warn,
+};
+use url::Url;
+
+use crate::utils::spawn_tokio_without_await;
+
+const KEEP_ALIVE: Duration = Duration::from_secs(30);
+
+#[derive(Deserialize, Debug, Clone)]
+pub struct ServerMessage {
+    #[serde(rename = "MessageType")]
+    pub message_type: String,
+    #[serde(rename = "Data", default)]
+    pub data: Value,
+}
+
+/// Opens the server socket. Messages arrive on the returned channel until
+/// the connection drops, dropping the receiver closes the socket.
+pub async fn connect(url: Url) -> Result<flume::Receiver<ServerMessage>> {
+    let (stream, _) = connect_async(url.as_str()).await?;
+    let (mut write, mut read) = stream.split();
+    let (sender, receiver) = flume::unbounded();
+
+    spawn_tokio_without_await(async move {
+        let mut keep_alive = tokio::time::interval(KEEP_ALIVE);
+        loop {
+            tokio::select! {
+                _ = keep_alive.tick() => {
+                    if sender.is_disconnected() {
+                        break;
+                    }
+                    let message = json!({ "MessageType": "KeepAlive" }).to_string();
+                    if write.send(Message::Text(message)).await.is_err() {
+                        break;
+                    }
+                }
+                message = read.next() => {
+                    let text = match message {
+                        Some(Ok(Message::Text(text))) => text,
+                        Some(Ok(Message::Close(_))) | Some(Err(_)) | None => break,
+                        Some(Ok(_)) => continue,
+                    };
+                    let message = match serde_json::from_str::<ServerMessage>(&text) {
+                        Ok(message) => message,
+                        Err(e) => {
+                            warn!("Failed to parse socket message: {}", e);
+                            continue;
+                        }
+                    };
+                    // The server drops clients that stay quiet for longer than this
+                    if message.message_type == "ForceKeepAlive" {
+                        let timeout = message.data.as_u64().unwrap_or(60).max(2);
+                        keep_alive = tokio::time::interval(Duration::from_secs(timeout / 2));
+                        continue;
+                    }
+                    debug!("Socket message: {}", message.message_type);
+                    if sender.send(message).is_err() {
+                        break;
+                    }
+                }
+            }
+        }
+        let _ = write.send(Message::Close(None)).await;
+    });
+
+    Ok(receiver)
+}
@@ -0,0 +1,241 @@
+use std::f64::consts::FRAC_PI_2;
+
+use gst::prelude::*;
+
+use crate::ui::models::{
+    equalizer::Gains,
+    SETTINGS,
+};
+
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub enum ReplayGainMode {
+    #[default]
+    Off = 0,
+    Track = 1,
+    Album = 2,
+}
+
+impl From<i32> for ReplayGainMode {
+    fn from(value: i32) -> Self {
+        match value {
+            1 => Self::Track,
+            2 => Self::Album,
+            _ => Self::Off,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct ReplayGain {
+    pub mode: ReplayGainMode,
+    /// dB added on top of the gain.
+    pub preamp: f64,
+    pub prevent_clipping: bool,
+}
+
+impl ReplayGain {
+    pub fn load() -> Self {
+        Self {
+            mode: ReplayGainMode::from(SETTINGS.music_replaygain()),
+            preamp: SETTINGS.music_replaygain_preamp(),
+            prevent_clipping: SETTINGS.music_replaygain_prevent_clipping(),
+        }
+    }
+}
+
+/// The `audio-filter` of playbin with the elements adjusted while playing.
+pub struct AudioFilter {
+    pub bin: gst::Element,
+    pub rgvolume: Option<gst::Element>,
+    pub equalizer: Option<gst::Element>,
+}
+
+impl AudioFilter {
+    /// ReplayGain, unless it is off, followed by the equalizer. Elements
+    /// whose plugin is missing are left out, `None` when nothing is left.
+    /// `fallback_gain` is the ReplayGain of songs without tags, with
+    /// `pitch_correction` playback at other rates keeps its pitch.
+    pub fn new(
+        replaygain: &ReplayGain, fallback_gain: f64, gains: &Gains, pitch_correction: bool,
+    ) -> Option<Self> {
+        match Self::build(replaygain, fallback_gain, gains, pitch_correction) {
+            Ok(filter) => filter,
+            Err(e) => {
+                tracing::warn!("Failed to build the audio filter: {}", e);
+                None
+            }
+        }
+    }
+
+    fn build(
+        replaygain: &ReplayGain, fallback_gain: f64, gains: &Gains, pitch_correction: bool,
+    ) -> anyhow::Result<Option<Self>> {
+        let mut elements = Vec::new();
+
+        if pitch_correction {
+            match gst::ElementFactory::make("scaletempo").build() {
+                Ok(scaletempo) => elements.push(scaletempo),
+                Err(e) => tracing::warn!("Pitch correction is unavailable: {}", e),
+            }
+        }
+
+        let rgvolume = if replaygain.mode == ReplayGainMode::Off {
+            None
+        } else {
+            let rgvolume = gst::ElementFactory::make("rgvolume")
+                .property("album-mode", replaygain.mode == ReplayGainMode::Album)
+                .property("pre-amp", replaygain.preamp)
+                .property("fallback-gain", fallback_gain)
+                .build();
+            let limiter = gst::ElementFactory::make("rglimiter")
+                .property("enabled", replaygain.prevent_clipping)
+                .build();
+            match (rgvolume, limiter) {
+                (Ok(rgvolume), Ok(limiter)) => {
+                    elements.push(rgvolume.clone());
+                    elements.push(limiter);
+                    Some(rgvolume)
+                }
+                (Err(e), _) | (_, Err(e)) => {
+                    tracing::warn!("ReplayGain is unavailable: {}", e);
+                    None
+                }
+            }
+        };
+
+        let equalizer = match gst::ElementFactory::make("equalizer-10bands").build() {
+            Ok(equalizer) => {
+                set_equalizer_gains(&equalizer, gains);
+                elements.push(equalizer.clone());
+                Some(equalizer)
+            }
+            Err(e) => {
+                tracing::warn!("The equalizer is unavailable: {}", e);
+                None
+            }
+        };
+
+        if elements.is_empty() {
+            return Ok(None);
+        }
+
+        let input = gst::ElementFactory::make("audioconvert").build()?;
+        let output = gst::ElementFactory::make("audioconvert").build()?;
+        elements.insert(0, input.clone());
+        elements.push(output.clone());
+
+        let bin = gst::Bin::new();
+        bin.add_many(&elements)?;
+        gst::Element::link_many(&elements)?;
+
+        let sink = input
+            .static_pad("sink")
+            .ok_or_else(|| anyhow::anyhow!("audioconvert has no sink pad"))?;
+        let src = output
+            .static_pad("src")
+            .ok_or_else(|| anyhow::anyhow!("audioconvert has no src pad"))?;
+        bin.add_pad(&gst::GhostPad::with_target(&sink)?)?;
+        bin.add_pad(&gst::GhostPad::with_target(&src)?)?;
+
+        Ok(Some(Self {
+            bin: bin.upcast(),
+            rgvolume,
+            equalizer,
+        }))
+    }
+}
+
+pub fn set_equalizer_gains(equalizer: &gst::Element, gains: &Gains) {
+    for (band, gain) in gains.iter().enumerate() {
+        equalizer.set_property(&format!("band{}", band), *gain);
+    }
+}
+
+/// Name a sound server gives a device, stable across launches unlike the
+/// order or the display name.
+fn device_id(device: &gst::Device) -> String {
+    device
+        .properties()
+        .and_then(|properties| {
+            ["node.name", "device.name", "device.id"]
+                .into_iter()
+                .find_map(|field| properties.get::<String>(field).ok())
+        })
+        .unwrap_or_else(|| device.display_name().to_string())
+}
+
+/// Audio outputs as id and display name.
+pub fn output_devices() -> Vec<(String, String)> {
+    let monitor = gst::DeviceMonitor::new();
+    monitor.add_filter(Some("Audio/Sink"), None);
+    if let Err(e) = monitor.start() {
+        tracing::warn!("Failed to list audio outputs: {}", e);
+        return Vec::new();
+    }
+    let devices = monitor
+        .devices()
+        .iter()
+        .map(|device| (device_id(device), device.display_name().to_string()))
+        .collect();
+    monitor.stop();
+    devices
+}
+
+/// Sink of the output `id`, `None` for the default output or when the
+/// device is gone.
+pub fn output_sink(id: &str) -> Option<gst::Element> {
+    if id.is_empty() {
+        return None;
+    }
+    let monitor = gst::DeviceMonitor::new();
+    monitor.add_filter(Some("Audio/Sink"), None);
+    monitor.start().ok()?;
+    let device = monitor
+        .devices()
+        .into_iter()
+        .find(|device| device_id(device) == id);
+    monitor.stop();
+
+    let Some(device) = device else {
+        tracing::warn!("Audio output {} is gone, using the default", id);
+        return None;
+    };
+    device
+        .create_element(None)
+        .inspect_err(|e| tracing::warn!("Failed to open audio output {}: {}", id, e))
+        .ok()
+}
+
+/// Seconds the end of the current song overlaps the next one, `None` when
+/// crossfade is off or both belong to one album, which plays gapless.
+pub fn crossfade_seconds(setting: i32, current_album: &str, next_album: &str) -> Option<f64> {
+    if setting <= 0 || (!current_album.is_empty() && current_album == next_album) {
+        return None;
+    }
+    Some(setting as f64)
+}
+
+/// Volume of the outgoing and the incoming song `progress` (0 to 1) into a
+/// crossfade, equal power so the loudness does not dip halfway.
+pub fn fade_levels(progress: f64) -> (f64, f64) {
+    let angle = progress.clamp(0.0, 1.0) * FRAC_PI_2;
+    (angle.cos(), angle.sin())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_crossfade() {
+        assert_eq!(crossfade_seconds(0, "a", "b"), None);
+        assert_eq!(crossfade_seconds(5, "a", "a"), None);
+        assert_eq!(crossfade_seconds(5, "a", "b"), Some(5.0));
+        assert_eq!(crossfade_seconds(5, "", ""), Some(5.0));
+
+        assert_eq!(fade_levels(0.0), (1.0, 0.0));
+        let (outgoing, incoming) = fade_levels(0.5);
+        assert!((outgoing - incoming).abs() < 1e-9);
+        assert!((outgoing * outgoing + incoming * incoming - 1.0).abs() < 1e-9);
+    }
+}
@@ -1 +1,3 @@
+pub mod audio;
 pub mod player;
+pub mod shuffle;
@@ -1,18 +1,65 @@
 use gst::prelude::*;
 use gtk::glib;
 
+use super::{
+    audio::{
+        self,
+        AudioFilter,
+        ReplayGain,
+    },
+    shuffle::Shuffle,
+};
 use crate::{
-    client::emby_client::EMBY_CLIENT,
-    ui::provider::core_song::CoreSong,
+    client::{
+        emby_client::{
+            BackType,
+            EmbyClient,
+            EMBY_CLIENT,
+        },
+        structs::{
+            ticks,
+            Back,
+        },
+    },
+    ui::{
+        models::SETTINGS,
+        provider::core_song::CoreSong,
+    },
+    utils::{
+        spawn,
+        spawn_tokio,
+        spawn_tokio_without_await,
+    },
 };
 
+/// Seconds between progress reports.
+const PROGRESS_INTERVAL: u32 = 10;
+/// Milliseconds between crossfade volume steps.
+const CROSSFADE_INTERVAL: u64 = 100;
+
+/// The pipeline of the previous song while it fades out.
+pub struct Crossfade {
+    outgoing: gst::Element,
+    started: std::time::Instant,
+    seconds: f64,
+    /// Volume both songs fade from and to.
+    volume: f64,
+}
+
 pub mod imp {
     use std::{
         cell::{
             Cell,
             RefCell,
         },
-        sync::OnceLock,
+        sync::{
+            atomic::{
+                AtomicBool,
+                Ordering,
+            },
+            Arc,
+            OnceLock,
+        },
     };
 
     use anyhow::Result;
@@ -34,6 +81,7 @@ pub mod imp {
     };
     use once_cell::sync::*;
     use tracing::debug;
+    use uuid::Uuid;
 
     use super::*;
     use crate::ui::widgets::song_widget::State;
@@ -71,6 +119,7 @@ pub mod imp {
         AboutToFinish,
         StreamStart,
         Eos,
+        AsyncDone,
     }
 
     pub struct GstreamerEventChannel {
@@ -87,7 +136,9 @@ pub mod imp {
     #[derive(Properties, Default)]
     #[properties(wrapper_type = super::MusicPlayer)]
     pub struct MusicPlayer {
-        pipeline: OnceCell<gst::Element>,
+        pipeline: RefCell<Option<gst::Element>>,
+        /// Handler of `about-to-finish` on the pipeline.
+        about_to_finish: RefCell<Option<glib::SignalHandlerId>>,
         #[property(get, set, nullable)]
         pub active_core_song: RefCell<Option<CoreSong>>,
         #[property(get, set, nullable)]
@@ -96,6 +147,39 @@ pub mod imp {
         pub repeat_mode: Cell<ListRepeatMode>,
         #[property(get, set, default_value = false)]
         pub gapless: RefCell<bool>,
+        #[property(get, set = Self::set_shuffle, explicit_notify, default_value = false)]
+        pub shuffle: Cell<bool>,
+
+        /// Play order of the active model while shuffling.
+        pub shuffle_order: RefCell<Shuffle<CoreSong>>,
+
+        /// Play session of the active song.
+        pub session: RefCell<Option<String>>,
+        /// Play session of the song queued for gapless playback.
+        pub next_session: RefCell<Option<String>>,
+        /// Set once the server confirmed the play session.
+        pub back: RefCell<Option<Back>>,
+        pub progress_timeout: RefCell<Option<glib::SourceId>>,
+        /// Play session of a song loaded paused, opened once it plays.
+        pub cued_session: RefCell<Option<String>>,
+        /// Seconds to seek to once the pipeline prerolled.
+        pub pending_position: Cell<Option<f64>>,
+        /// `rgvolume` of the pipeline's audio filter.
+        pub rgvolume: RefCell<Option<gst::Element>>,
+        /// `equalizer-10bands` of the pipeline's audio filter.
+        pub equalizer: RefCell<Option<gst::Element>>,
+        /// Output the pipeline's audio sink was made for.
+        pub output: RefCell<Option<String>>,
+        pub crossfade: RefCell<Option<Crossfade>>,
+        /// Fires when the crossfade should start, or for the next step of a
+        /// running one.
+        pub crossfade_timeout: RefCell<Option<glib::SourceId>>,
+        /// Audiobooks keep their pitch at other rates and never crossfade.
+        pub audiobook: Cell<bool>,
+        /// Playback rate, `None` for normal speed.
+        pub rate: Cell<Option<f64>>,
+        /// Rate the pipeline's current segment plays at.
+        pub applied_rate: Cell<Option<f64>>,
     }
 
     #[glib::derived_properties]
@@ -106,46 +190,29 @@ pub mod imp {
             // Initialize GStreamer
             gst::init().unwrap();
 
-            // Build the pipeline
-            let pipeline = gst::ElementFactory::make("playbin3").build().unwrap();
-            // Start playing
-            let bus = pipeline.bus().unwrap();
-            bus.add_signal_watch();
+            self.replace_pipeline();
 
-            bus.connect_message(Some("eos"), {
-                move |_, _| {
-                    let _ = GSTREAMER_EVENT_CHANNEL.tx.send(GstreamerEvent::Eos);
-                }
-            });
-            bus.connect_message(Some("buffering"), {
+            SETTINGS.connect_changed(
+                Some("music-equalizer"),
                 glib::clone!(
-                    #[strong]
-                    pipeline,
-                    move |_bus, msg| {
-                        if let gst::MessageView::Buffering(buffering) = msg.view() {
-                            let percent = buffering.percent();
-                            if percent < 100 {
-                                let _ = pipeline.set_state(gst::State::Paused);
-                            } else {
-                                let _ = pipeline.set_state(gst::State::Playing);
-                            }
+                    #[weak(rename_to = imp)]
+                    self,
+                    move |_, _| {
+                        if let Some(equalizer) = imp.equalizer.borrow().as_ref() {
+                            audio::set_equalizer_gains(equalizer, &SETTINGS.music_equalizer());
                         }
                     }
-                )
-            });
-
-            self.pipeline.set(pipeline).unwrap();
+                ),
+            );
 
-            self.connect_about_to_finish(move |_| {
-                let _ = GSTREAMER_EVENT_CHANNEL
-                    .tx
-                    .send(GstreamerEvent::AboutToFinish);
-                None
-            });
-
-            self.connect_stream_start(move |_, _| {
-                let _ = GSTREAMER_EVENT_CHANNEL.tx.send(GstreamerEvent::StreamStart);
-            });
+            SETTINGS.connect_changed(
+                Some("music-crossfade"),
+                glib::clone!(
+                    #[weak(rename_to = imp)]
+                    self,
+                    move |_, _| imp.schedule_crossfade()
+                ),
+            );
 
             glib::spawn_future_local(glib::clone!(
                 #[weak(rename_to = imp)]
@@ -162,9 +229,25 @@ pub mod imp {
                             GstreamerEvent::StreamStart => {
                                 let obj = imp.obj();
                                 if obj.gapless() {
+                                    imp.finish_session(true);
                                     let _ = imp.playlist_next();
+                                    if let (Some(core_song), Some(session)) =
+                                        (obj.active_core_song(), imp.next_session.take())
+                                    {
+                                        if let Some(rgvolume) = imp.rgvolume.borrow().as_ref() {
+                                            gst::prelude::ObjectExt::set_property(
+                                                rgvolume,
+                                                "fallback-gain",
+                                                core_song.normalization_gain(),
+                                            );
+                                        }
+                                        imp.open_session(&core_song, session);
+                                    }
+                                    imp.applied_rate.take();
+                                    imp.apply_rate();
                                 }
                                 obj.set_gapless(false);
+                                imp.schedule_crossfade();
                                 let Some(duration) =
                                     imp.pipeline().query_duration::<gst::ClockTime>()
                                 else {
@@ -174,8 +257,9 @@ pub mod imp {
                             }
                             GstreamerEvent::Eos => {
                                 let obj = imp.obj();
+                                imp.finish_session(true);
                                 if imp.playlist_next().is_err() {
-                                    return;
+                                    continue;
                                 };
                                 imp.stop();
                                 if obj.gapless() {
@@ -185,6 +269,14 @@ pub mod imp {
                                 }
                                 obj.set_gapless(false);
                             }
+                            GstreamerEvent::AsyncDone => {
+                                if let Some(position) = imp.pending_position.take() {
+                                    imp.set_position(position);
+                                } else {
+                                    imp.apply_rate();
+                                }
+                                imp.schedule_crossfade();
+                            }
                         }
                     }
                 }
@@ -210,25 +302,259 @@ pub mod imp {
     impl WidgetImpl for MusicPlayer {}
 
     impl MusicPlayer {
-        fn pipeline(&self) -> &gst::Element {
-            self.pipeline.get().unwrap()
+        fn pipeline(&self) -> gst::Element {
+            self.pipeline
+                .borrow()
+                .clone()
+                .expect("The pipeline is built on construction")
+        }
+
+        /// Builds a new playbin forwarding its events to the player, the old
+        /// one is returned silenced but still in its current state.
+        fn replace_pipeline(&self) -> Option<gst::Element> {
+            let pipeline = gst::ElementFactory::make("playbin3").build().unwrap();
+            let bus = pipeline.bus().unwrap();
+            bus.add_signal_watch();
+
+            bus.connect_message(Some("eos"), {
+                move |_, _| {
+                    let _ = GSTREAMER_EVENT_CHANNEL.tx.send(GstreamerEvent::Eos);
+                }
+            });
+            bus.connect_message(Some("async-done"), {
+                move |_, _| {
+                    let _ = GSTREAMER_EVENT_CHANNEL.tx.send(GstreamerEvent::AsyncDone);
+                }
+            });
+            bus.connect_message(Some("stream-start"), {
+                move |_, _| {
+                    let _ = GSTREAMER_EVENT_CHANNEL.tx.send(GstreamerEvent::StreamStart);
+                }
+            });
+            // Only resume what buffering paused, a cued song stays paused.
+            let buffering_paused = Arc::new(AtomicBool::new(false));
+            bus.connect_message(Some("buffering"), {
+                glib::clone!(
+                    #[weak]
+                    pipeline,
+                    move |_bus, msg| {
+                        if let gst::MessageView::Buffering(buffering) = msg.view() {
+                            let percent = buffering.percent();
+                            if percent < 100 {
+                                let (_, current, pending) = pipeline.state(gst::ClockTime::ZERO);
+                                if current == gst::State::Playing || pending == gst::State::Playing
+                                {
+                                    let _ = pipeline.set_state(gst::State::Paused);
+                                    buffering_paused.store(true, Ordering::Relaxed);
+                                }
+                            } else if buffering_paused.swap(false, Ordering::Relaxed) {
+                                let _ = pipeline.set_state(gst::State::Playing);
+                            }
+                        }
+                    }
+                )
+            });
+
+            let about_to_finish =
+                gst::prelude::ObjectExt::connect(&pipeline, "about-to-finish", false, |_| {
+                    let _ = GSTREAMER_EVENT_CHANNEL
+                        .tx
+                        .send(GstreamerEvent::AboutToFinish);
+                    None
+                });
+
+            let old = self.pipeline.replace(Some(pipeline));
+            let old_handler = self.about_to_finish.replace(Some(about_to_finish));
+            self.rgvolume.take();
+            self.equalizer.take();
+            self.output.take();
+            if let Some(old) = old.as_ref() {
+                if let Some(bus) = old.bus() {
+                    bus.remove_signal_watch();
+                }
+                if let Some(handler) = old_handler {
+                    gst::prelude::ObjectExt::disconnect(old, handler);
+                }
+            }
+            old
+        }
+
+        /// Sets the audio filter and output for `core_song`, the pipeline
+        /// must be stopped.
+        fn apply_audio_settings(&self, core_song: &CoreSong) {
+            let pipeline = self.pipeline();
+            let (filter, rgvolume, equalizer) = match AudioFilter::new(
+                &ReplayGain::load(),
+                core_song.normalization_gain(),
+                &SETTINGS.music_equalizer(),
+                self.audiobook.get(),
+            ) {
+                Some(filter) => (Some(filter.bin), filter.rgvolume, filter.equalizer),
+                None => (None, None, None),
+            };
+            gst::prelude::ObjectExt::set_property(&pipeline, "audio-filter", filter);
+            self.rgvolume.replace(rgvolume);
+            self.equalizer.replace(equalizer);
+
+            let output = SETTINGS.music_audio_output();
+            if self.output.borrow().as_deref() != Some(output.as_str()) {
+                gst::prelude::ObjectExt::set_property(
+                    &pipeline,
+                    "audio-sink",
+                    audio::output_sink(&output),
+                );
+                self.output.replace(Some(output));
+            }
+        }
+
+        /// Arms the crossfade for the end of the playing song, or the next
+        /// step of the running one.
+        fn schedule_crossfade(&self) {
+            self.clear_crossfade_timeout();
+            let delay = if self.crossfade.borrow().is_some() {
+                std::time::Duration::from_millis(CROSSFADE_INTERVAL)
+            } else {
+                let seconds = SETTINGS.music_crossfade();
+                if seconds <= 0 || self.audiobook.get() || !self.plays() {
+                    return;
+                }
+                let Some(remaining) = self.remaining_seconds() else {
+                    return;
+                };
+                let rate = self.rate.get().unwrap_or(1.0);
+                std::time::Duration::from_secs_f64(((remaining - seconds as f64) / rate).max(0.0))
+            };
+            let timeout = glib::timeout_add_local_once(
+                delay,
+                glib::clone!(
+                    #[weak(rename_to = imp)]
+                    self,
+                    move || {
+                        imp.crossfade_timeout.take();
+                        imp.on_crossfade_tick();
+                    }
+                ),
+            );
+            self.crossfade_timeout.replace(Some(timeout));
         }
 
-        pub fn connect_about_to_finish<F>(&self, cb: F)
-        where
-            F: Fn(&[gst::glib::Value]) -> Option<gst::glib::Value> + Send + Sync + 'static,
-        {
-            gst::prelude::ObjectExt::connect(self.pipeline(), "about-to-finish", false, cb);
+        fn clear_crossfade_timeout(&self) {
+            if let Some(timeout) = self.crossfade_timeout.take() {
+                timeout.remove();
+            }
+        }
+
+        /// Whether the pipeline plays or is about to.
+        fn plays(&self) -> bool {
+            let pipeline = self.pipeline();
+            match pipeline.pending_state() {
+                gst::State::VoidPending => pipeline.current_state() == gst::State::Playing,
+                pending => pending == gst::State::Playing,
+            }
         }
 
-        pub fn connect_stream_start<F>(&self, cb: F)
-        where
-            F: Fn(&gst::Bus, &gst::Message) + Send + Sync + 'static,
-        {
+        /// Length of `song`, from the pipeline once it knows it.
+        fn duration_seconds(&self, song: &CoreSong) -> f64 {
             self.pipeline()
-                .bus()
-                .unwrap()
-                .connect_message(Some("stream-start"), cb);
+                .query_duration::<gst::ClockTime>()
+                .map_or(song.duration() as f64, |duration| {
+                    duration.mseconds() as f64 / 1000.0
+                })
+        }
+
+        /// Seconds left of the playing song.
+        fn remaining_seconds(&self) -> Option<f64> {
+            let current = self.obj().active_core_song()?;
+            Some(self.duration_seconds(&current) - self.stream_position())
+        }
+
+        fn on_crossfade_tick(&self) {
+            let fade = self.crossfade.borrow().as_ref().map(|fade| {
+                (
+                    fade.started.elapsed().as_secs_f64() / fade.seconds,
+                    fade.volume,
+                    fade.outgoing.clone(),
+                )
+            });
+            if let Some((progress, volume, outgoing)) = fade {
+                if progress >= 1.0 {
+                    self.end_crossfade();
+                    return;
+                }
+                let (outgoing_level, incoming_level) = audio::fade_levels(progress);
+                set_pipeline_volume(&outgoing, volume * outgoing_level);
+                set_pipeline_volume(&self.pipeline(), volume * incoming_level);
+                self.schedule_crossfade();
+                return;
+            }
+
+            if self.state() != gst::State::Playing || self.audiobook.get() {
+                return;
+            }
+            let obj = self.obj();
+            let (Some(current), Some(next)) = (obj.active_core_song(), self.next_song()) else {
+                return;
+            };
+            if current == next {
+                return;
+            }
+            let Some(seconds) = audio::crossfade_seconds(
+                SETTINGS.music_crossfade(),
+                &current.album_id(),
+                &next.album_id(),
+            ) else {
+                return;
+            };
+            let duration = self.duration_seconds(&current);
+            // Songs shorter than the fades themselves are left alone.
+            if duration < seconds * 2.0 {
+                return;
+            }
+            let remaining = duration - self.stream_position();
+            if remaining <= 0.0 {
+                return;
+            }
+            // Woken early, the position moved on slower than the clock.
+            if remaining > seconds {
+                self.schedule_crossfade();
+                return;
+            }
+            self.start_crossfade(remaining);
+        }
+
+        /// Starts the next song on a new pipeline while the current one
+        /// fades out over its last `seconds`.
+        fn start_crossfade(&self, seconds: f64) {
+            let volume = self.volume();
+            self.finish_session(true);
+            self.obj().set_gapless(false);
+            self.next_session.take();
+            let Some(outgoing) = self.replace_pipeline() else {
+                return;
+            };
+            if self.playlist_next().is_err() {
+                let _ = outgoing.set_state(gst::State::Null);
+                return;
+            }
+            set_pipeline_volume(&self.pipeline(), 0.0);
+            self.prepre_play();
+            self.crossfade.replace(Some(Crossfade {
+                outgoing,
+                started: std::time::Instant::now(),
+                seconds,
+                volume,
+            }));
+            self.schedule_crossfade();
+        }
+
+        /// Stops the outgoing song right away.
+        fn end_crossfade(&self) {
+            let Some(fade) = self.crossfade.take() else {
+                return;
+            };
+            let _ = fade.outgoing.set_state(gst::State::Null);
+            set_pipeline_volume(&self.pipeline(), fade.volume);
+            self.schedule_crossfade();
         }
 
         pub fn playing(&self) {
@@ -245,33 +571,184 @@ pub mod imp {
                 }
             }
 
+            self.finish_session(false);
             self.stop();
-            let uri = EMBY_CLIENT.get_song_streaming_uri(&core_song.id());
+            self.cued_session.take();
+            self.pending_position.take();
+            self.apply_audio_settings(core_song);
+            let session = Uuid::new_v4().to_string();
+            let uri = EMBY_CLIENT.get_song_streaming_uri(&core_song.id(), &session);
 
-            gst::prelude::ObjectExt::set_property(self.pipeline(), "uri", uri);
+            gst::prelude::ObjectExt::set_property(&self.pipeline(), "uri", uri);
+            self.applied_rate.take();
             self.playing();
+            self.open_session(core_song, session);
+        }
+
+        /// Loads `core_song` paused at `position` seconds, the play session is
+        /// reported once it is unpaused.
+        pub fn cue(&self, core_song: &CoreSong, position: f64) {
+            core_song.set_state(State::Playing);
+            self.finish_session(false);
+            self.stop();
+            self.apply_audio_settings(core_song);
+            let session = Uuid::new_v4().to_string();
+            let uri = EMBY_CLIENT.get_song_streaming_uri(&core_song.id(), &session);
+
+            gst::prelude::ObjectExt::set_property(&self.pipeline(), "uri", uri);
+            self.applied_rate.take();
+            self.pending_position
+                .set((position > 0.0).then_some(position));
+            self.cued_session.replace(Some(session));
+            self.pipeline()
+                .set_state(gst::State::Paused)
+                .expect("Unable to set the pipeline to the `Paused` state");
         }
 
         pub fn add_song(&self, core_song: &CoreSong) {
-            let uri = EMBY_CLIENT.get_song_streaming_uri(&core_song.id());
-            gst::prelude::ObjectExt::set_property(self.pipeline(), "uri", uri);
+            let session = Uuid::new_v4().to_string();
+            let uri = EMBY_CLIENT.get_song_streaming_uri(&core_song.id(), &session);
+            gst::prelude::ObjectExt::set_property(&self.pipeline(), "uri", uri);
+            self.next_session.replace(Some(session));
+        }
+
+        /// Reports the start of `core_song` once its media source is known,
+        /// unless another song started meanwhile.
+        fn open_session(&self, core_song: &CoreSong, session: String) {
+            self.session.replace(Some(session.clone()));
+            let id = core_song.id();
+            spawn(glib::clone!(
+                #[weak(rename_to = imp)]
+                self,
+                async move {
+                    let source = match spawn_tokio({
+                        let id = id.clone();
+                        async move { EMBY_CLIENT.get_playbackinfo(&id).await }
+                    })
+                    .await
+                    {
+                        Ok(media) => media.media_sources.into_iter().next(),
+                        Err(e) => {
+                            tracing::warn!("Failed to get playback info of song: {}", e);
+                            None
+                        }
+                    };
+                    if imp.session.borrow().as_deref() != Some(session.as_str()) {
+                        return;
+                    }
+
+                    let back = Back {
+                        mediasourceid: source
+                            .as_ref()
+                            .map_or_else(|| id.clone(), |source| source.id.clone()),
+                        play_method: source
+                            .as_ref()
+                            .map(|source| EmbyClient::audio_play_method(source).to_string()),
+                        id,
+                        playsessionid: Some(session),
+                        tick: ticks(imp.stream_position()) as u64,
+                        start_tick: glib::DateTime::now_local().unwrap().to_unix() as u64,
+                        paused: false,
+                    };
+                    imp.back.replace(Some(back.clone()));
+                    spawn_tokio_without_await(async move {
+                        let _ = EMBY_CLIENT.position_back(&back, BackType::Start).await;
+                    });
+
+                    let obj = imp.obj();
+                    let timeout = glib::timeout_add_seconds_local(
+                        PROGRESS_INTERVAL,
+                        glib::clone!(
+                            #[weak]
+                            obj,
+                            #[upgrade_or]
+                            glib::ControlFlow::Break,
+                            move || {
+                                let imp = obj.imp();
+                                imp.report_progress(imp.state() != gst::State::Playing);
+                                glib::ControlFlow::Continue
+                            }
+                        ),
+                    );
+                    if let Some(timeout) = imp.progress_timeout.replace(Some(timeout)) {
+                        timeout.remove();
+                    }
+                }
+            ));
+        }
+
+        fn report_progress(&self, paused: bool) {
+            let Some(mut back) = self.back.borrow().clone() else {
+                return;
+            };
+            back.tick = ticks(self.stream_position()) as u64;
+            back.paused = paused;
+            spawn_tokio_without_await(async move {
+                let _ = EMBY_CLIENT.position_back(&back, BackType::Back).await;
+            });
+        }
+
+        /// Reports the active play session as stopped, `completed` songs are
+        /// also marked as played.
+        pub fn finish_session(&self, completed: bool) {
+            self.session.take();
+            if let Some(timeout) = self.progress_timeout.take() {
+                timeout.remove();
+            }
+            let Some(mut back) = self.back.take() else {
+                return;
+            };
+            back.tick = if completed {
+                self.obj()
+                    .active_core_song()
+                    .map_or(0, |core_song| ticks(core_song.duration() as f64) as u64)
+            } else {
+                ticks(self.stream_position()) as u64
+            };
+            spawn_tokio_without_await(async move {
+                let _ = EMBY_CLIENT.position_back(&back, BackType::Stop).await;
+                if completed {
+                    let _ = EMBY_CLIENT.set_as_played(&back.id).await;
+                }
+            });
+        }
+
+        /// Position regardless of the pipeline state, unlike `position`.
+        fn stream_position(&self) -> f64 {
+            self.pipeline()
+                .query_position::<gst::ClockTime>()
+                .map_or(0.0, |position| position.mseconds() as f64 / 1000.0)
+        }
+
+        /// Where playback of the active song would continue, even before a
+        /// cued song prerolled.
+        pub fn resume_position(&self) -> f64 {
+            self.pending_position
+                .get()
+                .unwrap_or_else(|| self.stream_position())
         }
 
         pub fn playlist_next(&self) -> Result<()> {
-            if let Some(core_song) = self.active_core_song.borrow().as_ref() {
+            let obj = self.obj();
+            let current = obj.active_core_song();
+            if let Some(core_song) = current.as_ref() {
                 core_song.set_state(State::Played);
             };
-            if let Some(core_song) = self.next_song() {
-                core_song.set_state(State::Playing);
-                debug!("Next Song: {}", core_song.name());
-                self.obj().set_active_core_song(Some(core_song));
-                Ok(())
-            } else {
-                Err(anyhow::Error::msg("No next song"))
+            let Some(core_song) = self.next_song() else {
+                return Err(anyhow::Error::msg("No next song"));
+            };
+            if self.shuffles() {
+                self.shuffle_order.borrow_mut().advance(current);
             }
+            core_song.set_state(State::Playing);
+            debug!("Next Song: {}", core_song.name());
+            obj.set_active_core_song(Some(core_song));
+            Ok(())
         }
 
         pub fn stop(&self) {
+            self.end_crossfade();
+            self.clear_crossfade_timeout();
             self.pipeline()
                 .set_state(gst::State::Null)
                 .expect("Unable to set the pipeline to the `Null` state");
@@ -282,6 +759,13 @@ pub mod imp {
             if obj.repeat_mode() == ListRepeatMode::RepeatOne {
                 return obj.active_core_song();
             }
+            if obj.shuffle() {
+                let mut order = self.shuffle_order.borrow_mut();
+                if order.is_exhausted() && obj.repeat_mode() == ListRepeatMode::Repeat {
+                    order.refill(self.songs(), obj.active_core_song().as_ref());
+                }
+                return order.peek_next().cloned();
+            }
             let model = self.active_model.borrow();
             let model = model.as_ref()?;
             let core_song_position = self.core_song_position()?;
@@ -302,6 +786,9 @@ pub mod imp {
             if obj.repeat_mode() == ListRepeatMode::RepeatOne {
                 return obj.active_core_song();
             }
+            if obj.shuffle() {
+                return self.shuffle_order.borrow().peek_previous().cloned();
+            }
             let model = self.active_model.borrow();
             let model = model.as_ref()?;
             let core_song_position = self.core_song_position()?;
@@ -316,6 +803,49 @@ pub mod imp {
             row.downcast::<CoreSong>().ok()
         }
 
+        /// Whether moving through the queue follows the shuffled order.
+        fn shuffles(&self) -> bool {
+            let obj = self.obj();
+            obj.shuffle() && obj.repeat_mode() != ListRepeatMode::RepeatOne
+        }
+
+        /// Songs of the active model, in queue order.
+        pub fn songs(&self) -> Vec<CoreSong> {
+            let model = self.active_model.borrow();
+            let Some(model) = model.as_ref() else {
+                return Vec::new();
+            };
+            model
+                .iter::<CoreSong>()
+                .filter_map(|core_song| core_song.ok())
+                .collect()
+        }
+
+        fn set_shuffle(&self, shuffle: bool) {
+            if self.shuffle.replace(shuffle) == shuffle {
+                return;
+            }
+            if shuffle {
+                self.shuffle_order
+                    .borrow_mut()
+                    .deal(self.songs(), self.obj().active_core_song().as_ref());
+            }
+            self.obj().notify_shuffle();
+        }
+
+        /// Songs left to play before the queue ends, ignoring repeat.
+        pub fn remaining(&self) -> usize {
+            if self.shuffles() {
+                return self.shuffle_order.borrow().remaining();
+            }
+            let (Some(model), Some(position)) =
+                (self.obj().active_model(), self.core_song_position())
+            else {
+                return 0;
+            };
+            (model.n_items() - position - 1) as usize
+        }
+
         pub fn core_song_position(&self) -> Option<u32> {
             let core_song = self.obj().active_core_song()?;
             let model = self.active_model.borrow();
@@ -341,28 +871,85 @@ pub mod imp {
         }
 
         pub fn pause(&self) {
+            self.end_crossfade();
+            self.clear_crossfade_timeout();
             self.pipeline()
                 .set_state(gst::State::Paused)
                 .expect("Unable to set the pipeline to the `Paused` state");
+            self.report_progress(true);
         }
 
         pub fn unpause(&self) {
             self.pipeline()
                 .set_state(gst::State::Playing)
                 .expect("Unable to set the pipeline to the `Playing` state");
+            self.schedule_crossfade();
+            if let Some(session) = self.cued_session.take() {
+                if let Some(core_song) = self.obj().active_core_song() {
+                    self.open_session(&core_song, session);
+                }
+                return;
+            }
+            self.report_progress(false);
         }
 
         pub fn state(&self) -> gst::State {
             self.pipeline().current_state()
         }
 
+        /// Linear volume, where 1.0 is 100%.
+        pub fn volume(&self) -> f64 {
+            if let Some(fade) = self.crossfade.borrow().as_ref() {
+                return fade.volume;
+            }
+            gst::prelude::ObjectExt::property(&self.pipeline(), "volume")
+        }
+
+        pub fn set_volume(&self, volume: f64) {
+            if let Some(fade) = self.crossfade.borrow_mut().as_mut() {
+                fade.volume = volume;
+                return;
+            }
+            set_pipeline_volume(&self.pipeline(), volume);
+        }
+
         pub fn set_position(&self, position: f64) {
-            let position = gst::ClockTime::from_seconds(position as u64);
-            if let Err(e) = self
-                .pipeline()
-                .seek_simple(gst::SeekFlags::FLUSH | gst::SeekFlags::KEY_UNIT, position)
-            {
+            self.seek(gst::ClockTime::from_mseconds((position * 1000.0) as u64));
+        }
+
+        /// Seeks to `position`, playing on at the playback rate.
+        fn seek(&self, position: gst::ClockTime) {
+            let rate = self.rate.get();
+            if let Err(e) = self.pipeline().seek(
+                rate.unwrap_or(1.0),
+                gst::SeekFlags::FLUSH | gst::SeekFlags::KEY_UNIT,
+                gst::SeekType::Set,
+                position,
+                gst::SeekType::None,
+                gst::ClockTime::NONE,
+            ) {
                 tracing::warn!("Failed to seek: {}", e);
+                return;
+            }
+            self.applied_rate.set(rate);
+        }
+
+        /// Sets the playback rate, where 1.0 is normal speed.
+        pub fn set_rate(&self, rate: f64) {
+            self.rate.set((rate != 1.0).then_some(rate));
+            if matches!(self.state(), gst::State::Playing | gst::State::Paused) {
+                self.apply_rate();
+            }
+        }
+
+        /// Plays on from where the pipeline is at the playback rate, unless
+        /// it already does.
+        fn apply_rate(&self) {
+            if self.applied_rate.get() == self.rate.get() {
+                return;
+            }
+            if let Some(position) = self.pipeline().query_position::<gst::ClockTime>() {
+                self.seek(position);
             }
         }
 
@@ -370,11 +957,108 @@ pub mod imp {
             if let Some(core_song) = self.active_core_song.borrow().as_ref() {
                 core_song.set_state(State::Played);
             };
-            self.active_model.replace(Some(active_model));
             self.active_core_song.replace(Some(active_core_song));
+            self.obj().set_active_model(Some(active_model));
+            if self.obj().shuffle() {
+                self.shuffle_order
+                    .borrow_mut()
+                    .deal(self.songs(), self.obj().active_core_song().as_ref());
+            }
+            self.prepre_play();
+        }
+
+        /// Restores a queue without playing it, see `cue`.
+        pub fn cue_model(
+            &self, active_model: gtk::gio::ListStore, active_core_song: CoreSong, position: f64,
+        ) {
+            self.active_core_song
+                .replace(Some(active_core_song.clone()));
+            self.obj().set_active_model(Some(active_model));
+            if self.obj().shuffle() {
+                self.shuffle_order
+                    .borrow_mut()
+                    .deal(self.songs(), Some(&active_core_song));
+            }
+            self.cue(&active_core_song, position);
+        }
+
+        /// Queues `core_song` right after the active song when `next` is set,
+        /// at the end otherwise. A song already in the queue is moved.
+        pub fn queue(&self, core_song: CoreSong, next: bool) {
+            let obj = self.obj();
+            let Some(model) = obj.active_model() else {
+                return;
+            };
+            if obj.active_core_song().as_ref() == Some(&core_song) {
+                return;
+            }
+            if let Some(position) = model.find(&core_song) {
+                model.remove(position);
+            }
+            if next {
+                let position = self.core_song_position().map_or(0, |position| position + 1);
+                model.insert(position, &core_song);
+            } else {
+                model.append(&core_song);
+            }
+            if obj.shuffle() {
+                let mut order = self.shuffle_order.borrow_mut();
+                if next {
+                    order.insert_next(core_song);
+                } else {
+                    order.add(core_song);
+                }
+            }
+        }
+
+        /// Removes the song at `position`, unless it is the one playing.
+        pub fn unqueue(&self, position: u32) {
+            let Some(model) = self.obj().active_model() else {
+                return;
+            };
+            if self.core_song_position() == Some(position) {
+                return;
+            }
+            let Some(core_song) = model.item(position).and_downcast::<CoreSong>() else {
+                return;
+            };
+            model.remove(position);
+            self.shuffle_order.borrow_mut().remove(&core_song);
+        }
+
+        /// Moves the song at `from` so it ends up at `to`.
+        pub fn move_queued(&self, from: u32, to: u32) {
+            let Some(model) = self.obj().active_model() else {
+                return;
+            };
+            let Some(core_song) = model.item(from) else {
+                return;
+            };
+            model.remove(from);
+            model.insert(to.min(model.n_items()), &core_song);
+        }
+
+        /// Plays `core_song` of the active model right away.
+        pub fn skip_to(&self, core_song: CoreSong) {
+            let obj = self.obj();
+            let current = obj.active_core_song();
+            if let Some(current) = current.as_ref() {
+                current.set_state(State::Played);
+            }
+            if obj.shuffle() {
+                self.shuffle_order.borrow_mut().jump(current, &core_song);
+            }
+            obj.set_active_core_song(Some(core_song));
             self.prepre_play();
         }
 
+        /// Plays `core_song` of the active model from `position` seconds.
+        pub fn skip_to_position(&self, core_song: CoreSong, position: f64) {
+            self.skip_to(core_song);
+            self.pending_position
+                .set((position > 0.0).then_some(position));
+        }
+
         pub fn prepre_play(&self) {
             let Some(active_core_song) = self.obj().active_core_song() else {
                 return;
@@ -393,10 +1077,14 @@ pub mod imp {
         }
 
         pub fn playlist_prev(&self) {
-            if let Some(core_song) = self.active_core_song.borrow().as_ref() {
+            let current = self.obj().active_core_song();
+            if let Some(core_song) = current.as_ref() {
                 core_song.set_state(State::Played);
             };
             if let Some(core_song) = self.prev_song() {
+                if self.shuffles() {
+                    self.shuffle_order.borrow_mut().retreat(current);
+                }
                 core_song.set_state(State::Playing);
                 debug!("Prev Song: {}", core_song.name());
                 self.active_core_song.replace(Some(core_song));
@@ -420,3 +1108,8 @@ impl MusicPlayer {
         glib::Object::builder().build()
     }
 }
+
+fn set_pipeline_volume(pipeline: &gst::Element, volume: f64) {
+    // playbin rejects values above 10.0
+    gst::prelude::ObjectExt::set_property(pipeline, "volume", volume.clamp(0.0, 10.0));
+}
@@ -0,0 +1,132 @@
+use rand::{
+    seq::SliceRandom,
+    Rng,
+};
+
+/// Random play order that remembers what was played, so going back returns
+/// to the previous song rather than the previous list entry.
+#[derive(Debug, Clone)]
+pub struct Shuffle<T> {
+    /// Songs still to come, the next one last.
+    upcoming: Vec<T>,
+    /// Songs played before the current one, the latest last.
+    history: Vec<T>,
+}
+
+impl<T> Default for Shuffle<T> {
+    fn default() -> Self {
+        Self {
+            upcoming: Vec::new(),
+            history: Vec::new(),
+        }
+    }
+}
+
+impl<T: Clone + PartialEq> Shuffle<T> {
+    /// Starts over with `items` in random order, leaving out `current`.
+    pub fn deal(&mut self, items: impl IntoIterator<Item = T>, current: Option<&T>) {
+        self.history.clear();
+        self.refill(items, current);
+    }
+
+    /// Deals `items` again once they all played, keeping the history.
+    pub fn refill(&mut self, items: impl IntoIterator<Item = T>, current: Option<&T>) {
+        self.upcoming = items
+            .into_iter()
+            .filter(|item| Some(item) != current)
+            .collect();
+        self.upcoming.shuffle(&mut rand::thread_rng());
+    }
+
+    pub fn is_exhausted(&self) -> bool {
+        self.upcoming.is_empty()
+    }
+
+    pub fn remaining(&self) -> usize {
+        self.upcoming.len()
+    }
+
+    pub fn peek_next(&self) -> Option<&T> {
+        self.upcoming.last()
+    }
+
+    pub fn peek_previous(&self) -> Option<&T> {
+        self.history.last()
+    }
+
+    /// Moves on to the next song, `current` goes to the history.
+    pub fn advance(&mut self, current: Option<T>) -> Option<T> {
+        let next = self.upcoming.pop()?;
+        self.history.extend(current);
+        Some(next)
+    }
+
+    /// Goes back to the last played song, `current` comes up next again.
+    pub fn retreat(&mut self, current: Option<T>) -> Option<T> {
+        let previous = self.history.pop()?;
+        self.upcoming.extend(current);
+        Some(previous)
+    }
+
+    /// Skips straight to `item`, `current` goes to the history.
+    pub fn jump(&mut self, current: Option<T>, item: &T) {
+        self.remove(item);
+        self.history.extend(current);
+    }
+
+    /// Plays `item` right after the current song.
+    pub fn insert_next(&mut self, item: T) {
+        self.remove(&item);
+        self.upcoming.push(item);
+    }
+
+    /// Puts `item` somewhere among the songs to come.
+    pub fn add(&mut self, item: T) {
+        self.remove(&item);
+        let index = rand::thread_rng().gen_range(0..=self.upcoming.len());
+        self.upcoming.insert(index, item);
+    }
+
+    pub fn remove(&mut self, item: &T) {
+        self.upcoming.retain(|upcoming| upcoming != item);
+        self.history.retain(|played| played != item);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_shuffle() {
+        let mut shuffle = Shuffle::default();
+        shuffle.deal(1..=5, Some(&3));
+        assert!(shuffle.peek_previous().is_none());
+
+        let mut played = vec![3];
+        let mut current = 3;
+        while let Some(next) = shuffle.advance(Some(current)) {
+            current = next;
+            played.push(current);
+        }
+        played.sort();
+        assert_eq!(played, vec![1, 2, 3, 4, 5]);
+        assert!(shuffle.is_exhausted());
+
+        let previous = *shuffle.peek_previous().unwrap();
+        assert_eq!(shuffle.retreat(Some(current)), Some(previous));
+        assert_eq!(shuffle.peek_next(), Some(&current));
+
+        shuffle.insert_next(9);
+        assert_eq!(shuffle.peek_next(), Some(&9));
+        shuffle.add(previous);
+        assert_ne!(shuffle.peek_previous(), Some(&previous));
+
+        shuffle.jump(Some(previous), &9);
+        assert_eq!(shuffle.peek_previous(), Some(&previous));
+        assert_ne!(shuffle.peek_next(), Some(&9));
+
+        shuffle.refill(1..=5, Some(&previous));
+        assert_eq!(shuffle.upcoming.len(), 4);
+    }
+}
@@ -1,4 +1,5 @@
 pub(crate) mod models;
+mod mpris;
 mod mpv;
 pub mod provider;
 pub mod widgets;
@@ -36,6 +37,7 @@ pub fn build_ui(app: &adw::Application) {
         .build();
     window.add_action_entries([about_action]);
     window.present();
+    mpris::mpris().own_name();
 }
 
 pub fn load_css() {
@@ -0,0 +1,215 @@
+use crate::client::structs::{
+    SimpleListItem,
+    TICKS_PER_SECOND,
+};
+
+/// Seconds into a chapter after which going back restarts it instead of
+/// going to the previous one.
+const CHAPTER_RESTART_SECONDS: f64 = 3.0;
+
+/// One file of a book, which may hold several chapters.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct BookFile {
+    pub id: String,
+    pub name: String,
+    pub duration: f64,
+    /// Seconds the server resumes the file from.
+    pub position: f64,
+    pub played: bool,
+    /// Names and starts of the chapters embedded in the file.
+    pub chapters: Vec<(String, f64)>,
+}
+
+impl BookFile {
+    pub fn from_item(item: &SimpleListItem) -> Self {
+        let user_data = item.user_data.as_ref();
+        Self {
+            id: item.id.clone(),
+            name: item.name.clone(),
+            duration: item.run_time_ticks.unwrap_or_default() as f64 / TICKS_PER_SECOND,
+            position: user_data
+                .and_then(|user_data| user_data.playback_position_ticks)
+                .unwrap_or_default() as f64
+                / TICKS_PER_SECOND,
+            played: user_data.is_some_and(|user_data| user_data.played),
+            chapters: item
+                .chapters
+                .iter()
+                .flatten()
+                .map(|chapter| {
+                    (
+                        chapter.name.clone().unwrap_or_default(),
+                        chapter.start_position_ticks as f64 / TICKS_PER_SECOND,
+                    )
+                })
+                .collect(),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Chapter {
+    pub name: String,
+    /// Index of the file the chapter is in.
+    pub file: usize,
+    /// Seconds into that file.
+    pub start: f64,
+}
+
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Audiobook {
+    pub files: Vec<BookFile>,
+    pub chapters: Vec<Chapter>,
+}
+
+impl Audiobook {
+    /// Files without embedded chapters make a chapter each.
+    pub fn new(files: Vec<BookFile>) -> Self {
+        let mut chapters = Vec::new();
+        for (index, file) in files.iter().enumerate() {
+            let embedded = file
+                .chapters
+                .iter()
+                .filter(|(_, start)| file.duration <= 0.0 || *start < file.duration)
+                .collect::<Vec<_>>();
+            if embedded.is_empty() {
+                chapters.push(Chapter {
+                    name: file.name.clone(),
+                    file: index,
+                    start: 0.0,
+                });
+                continue;
+            }
+            for (number, (name, start)) in embedded.into_iter().enumerate() {
+                let name = if name.trim().is_empty() {
+                    format!("{} {}", file.name, number + 1)
+                } else {
+                    name.clone()
+                };
+                chapters.push(Chapter {
+                    name,
+                    file: index,
+                    start: *start,
+                });
+            }
+        }
+        Self { files, chapters }
+    }
+
+    /// File and seconds to continue from: the file last listened to, or the
+    /// one after it once that was finished. A finished book starts over.
+    pub fn resume_point(&self) -> (usize, f64) {
+        let Some(last) = self
+            .files
+            .iter()
+            .rposition(|file| file.played || file.position > 0.0)
+        else {
+            return (0, 0.0);
+        };
+        let file = &self.files[last];
+        if !file.played && file.position > 0.0 {
+            return (last, file.position);
+        }
+        if last + 1 < self.files.len() {
+            return (last + 1, 0.0);
+        }
+        (0, 0.0)
+    }
+
+    /// Index of the chapter playing at `position` seconds into `file`.
+    pub fn chapter_at(&self, file: usize, position: f64) -> Option<usize> {
+        self.chapters
+            .iter()
+            .rposition(|chapter| (chapter.file, chapter.start) <= (file, position))
+    }
+
+    /// Start of the playing chapter, or of the previous one right after a
+    /// chapter began.
+    pub fn previous_chapter(&self, file: usize, position: f64) -> Option<&Chapter> {
+        let index = self.chapter_at(file, position)?;
+        let chapter = &self.chapters[index];
+        if chapter.file == file && position - chapter.start > CHAPTER_RESTART_SECONDS {
+            return Some(chapter);
+        }
+        index
+            .checked_sub(1)
+            .and_then(|index| self.chapters.get(index))
+    }
+
+    pub fn next_chapter(&self, file: usize, position: f64) -> Option<&Chapter> {
+        let index = self.chapter_at(file, position).map_or(0, |index| index + 1);
+        self.chapters.get(index)
+    }
+
+    /// Moves `delta` seconds from `position` into `file`, crossing into the
+    /// neighbouring files. Stays within the book.
+    pub fn skip(&self, file: usize, position: f64, delta: f64) -> (usize, f64) {
+        let mut file = file.min(self.files.len().saturating_sub(1));
+        let mut position = position + delta;
+        while position < 0.0 && file > 0 {
+            file -= 1;
+            position += self.files[file].duration;
+        }
+        while let Some(duration) = self
+            .files
+            .get(file)
+            .map(|file| file.duration)
+            .filter(|duration| *duration > 0.0 && position >= *duration)
+        {
+            if file + 1 >= self.files.len() {
+                return (file, duration);
+            }
+            position -= duration;
+            file += 1;
+        }
+        (file, position.max(0.0))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn file(name: &str, duration: f64, chapters: &[(&str, f64)]) -> BookFile {
+        BookFile {
+            id: name.to_string(),
+            name: name.to_string(),
+            duration,
+            chapters: chapters
+                .iter()
+                .map(|(name, start)| (name.to_string(), *start))
+                .collect(),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_audiobook() {
+        let mut book = Audiobook::new(vec![
+            file("One", 100.0, &[("Intro", 0.0), ("", 40.0)]),
+            file("Two", 60.0, &[]),
+        ]);
+        let names: Vec<_> = book.chapters.iter().map(|c| c.name.as_str()).collect();
+        assert_eq!(names, vec!["Intro", "One 2", "Two"]);
+
+        assert_eq!(book.chapter_at(0, 50.0), Some(1));
+        assert_eq!(book.chapter_at(1, 10.0), Some(2));
+        assert_eq!(book.previous_chapter(0, 50.0).unwrap().start, 40.0);
+        assert_eq!(book.previous_chapter(1, 1.0).unwrap().name, "One 2");
+        assert_eq!(book.next_chapter(0, 50.0).unwrap().file, 1);
+        assert!(book.next_chapter(1, 10.0).is_none());
+
+        assert_eq!(book.skip(0, 90.0, 30.0), (1, 20.0));
+        assert_eq!(book.skip(1, 10.0, -15.0), (0, 95.0));
+        assert_eq!(book.skip(0, 5.0, -15.0), (0, 0.0));
+        assert_eq!(book.skip(1, 50.0, 30.0), (1, 60.0));
+
+        assert_eq!(book.resume_point(), (0, 0.0));
+        book.files[0].played = true;
+        assert_eq!(book.resume_point(), (1, 0.0));
+        book.files[1].position = 12.0;
+        assert_eq!(book.resume_point(), (1, 12.0));
+        book.files[1].played = true;
+        assert_eq!(book.resume_point(), (0, 0.0));
+    }
+}
@@ -0,0 +1,142 @@
+use std::collections::HashSet;
+
+use gettextrs::gettext;
+
+use crate::client::structs::SimpleListItem;
+
+/// Albums with this many tracks or fewer are singles or EPs.
+const SINGLE_MAX_TRACKS: u32 = 6;
+/// Albums crediting this many distinct artists are compilations.
+const COMPILATION_MIN_ARTISTS: usize = 4;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum AlbumKind {
+    Album,
+    SingleOrEp,
+    Compilation,
+    AppearsOn,
+}
+
+impl AlbumKind {
+    pub const ALL: [Self; 4] = [
+        Self::Album,
+        Self::SingleOrEp,
+        Self::Compilation,
+        Self::AppearsOn,
+    ];
+
+    pub fn title(&self) -> String {
+        match self {
+            Self::Album => gettext("Albums"),
+            Self::SingleOrEp => gettext("Singles & EPs"),
+            Self::Compilation => gettext("Compilations"),
+            Self::AppearsOn => gettext("Appears On"),
+        }
+    }
+}
+
+pub fn album_kind(album: &SimpleListItem, artist_id: &str) -> AlbumKind {
+    let is_album_artist = album
+        .album_artists
+        .as_ref()
+        .is_some_and(|artists| artists.iter().any(|artist| artist.id == artist_id));
+    if !is_album_artist {
+        return AlbumKind::AppearsOn;
+    }
+
+    let artists = album
+        .artists
+        .iter()
+        .flatten()
+        .map(|artist| artist.to_lowercase())
+        .collect::<HashSet<_>>();
+    if artists.len() >= COMPILATION_MIN_ARTISTS {
+        return AlbumKind::Compilation;
+    }
+
+    match album.child_count {
+        Some(tracks) if tracks > 0 && tracks <= SINGLE_MAX_TRACKS => AlbumKind::SingleOrEp,
+        _ => AlbumKind::Album,
+    }
+}
+
+/// Groups the discography of `artist_id` by [`AlbumKind`], newest first.
+/// Appearances already in `albums` are skipped.
+pub fn group(
+    artist_id: &str, albums: Vec<SimpleListItem>, appearances: Vec<SimpleListItem>,
+) -> Vec<(AlbumKind, Vec<SimpleListItem>)> {
+    let mut seen = HashSet::new();
+    let mut groups: Vec<(AlbumKind, Vec<SimpleListItem>)> = AlbumKind::ALL
+        .iter()
+        .map(|kind| (*kind, Vec::new()))
+        .collect();
+
+    for album in albums.into_iter().chain(appearances) {
+        if !seen.insert(album.id.clone()) {
+            continue;
+        }
+        let kind = album_kind(&album, artist_id);
+        if let Some((_, items)) = groups.iter_mut().find(|(k, _)| *k == kind) {
+            items.push(album);
+        }
+    }
+
+    groups.retain(|(_, items)| !items.is_empty());
+    for (_, items) in &mut groups {
+        // Stable, keeps the server's name order within a year
+        items.sort_by(|a, b| b.production_year.cmp(&a.production_year));
+    }
+    groups
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::client::structs::View;
+
+    fn album(
+        id: &str, album_artist: &str, artists: &[&str], tracks: u32, year: u32,
+    ) -> SimpleListItem {
+        SimpleListItem {
+            id: id.to_string(),
+            album_artists: Some(vec![View {
+                name: album_artist.to_string(),
+                id: album_artist.to_string(),
+                collection_type: None,
+            }]),
+            artists: Some(artists.iter().map(|a| a.to_string()).collect()),
+            child_count: Some(tracks),
+            production_year: Some(year),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_group() {
+        let albums = vec![
+            album("a", "me", &["me"], 12, 2010),
+            album("b", "me", &["me"], 2, 2015),
+            album("c", "me", &["me"], 10, 2020),
+            album("d", "me", &["w", "x", "y", "z"], 20, 2001),
+        ];
+        let appearances = vec![
+            album("e", "other", &["other", "me"], 9, 2012),
+            album("a", "me", &["me"], 12, 2010),
+        ];
+
+        let groups = group("me", albums, appearances);
+        let ids: Vec<(AlbumKind, Vec<&str>)> = groups
+            .iter()
+            .map(|(kind, items)| (*kind, items.iter().map(|i| i.id.as_str()).collect()))
+            .collect();
+        assert_eq!(
+            ids,
+            vec![
+                (AlbumKind::Album, vec!["c", "a"]),
+                (AlbumKind::SingleOrEp, vec!["b"]),
+                (AlbumKind::Compilation, vec!["d"]),
+                (AlbumKind::AppearsOn, vec!["e"]),
+            ]
+        );
+    }
+}
@@ -0,0 +1,82 @@
+use gettextrs::gettext;
+
+/// Centre frequencies of the bands, the same as GStreamer's
+/// `equalizer-10bands`.
+pub const BANDS: [f64; 10] = [
+    29.0, 59.0, 119.0, 237.0, 474.0, 947.0, 1889.0, 3770.0, 7523.0, 15011.0,
+];
+
+pub const MIN_GAIN: f64 = -12.0;
+pub const MAX_GAIN: f64 = 12.0;
+
+/// Gain of each band in dB.
+pub type Gains = [f64; 10];
+
+pub fn band_label(frequency: f64) -> String {
+    if frequency >= 1000.0 {
+        format!("{:.1} kHz", frequency / 1000.0)
+    } else {
+        format!("{} Hz", frequency as i32)
+    }
+}
+
+pub fn presets() -> Vec<(String, Gains)> {
+    vec![
+        (gettext("Flat"), [0.0; 10]),
+        (
+            gettext("Bass Boost"),
+            [6.0, 5.0, 4.0, 2.0, 0.0, 0.0, 0.0, 0.0, 0.0, 0.0],
+        ),
+        (
+            gettext("Treble Boost"),
+            [0.0, 0.0, 0.0, 0.0, 0.0, 1.0, 2.0, 4.0, 5.0, 6.0],
+        ),
+        (
+            gettext("Vocal"),
+            [-2.0, -2.0, -1.0, 1.0, 3.0, 4.0, 3.0, 1.0, 0.0, -1.0],
+        ),
+        (
+            gettext("Rock"),
+            [4.0, 3.0, 2.0, 0.0, -1.0, -1.0, 1.0, 2.0, 3.0, 4.0],
+        ),
+        (
+            gettext("Pop"),
+            [-1.0, 1.0, 2.0, 3.0, 2.0, 0.0, -1.0, -1.0, 1.0, 1.0],
+        ),
+        (
+            gettext("Jazz"),
+            [3.0, 2.0, 1.0, 2.0, -1.0, -1.0, 0.0, 1.0, 2.0, 3.0],
+        ),
+        (
+            gettext("Classical"),
+            [3.0, 2.0, 1.0, 0.0, 0.0, 0.0, -1.0, 1.0, 2.0, 3.0],
+        ),
+    ]
+}
+
+/// Index of the preset `gains` matches, `None` for a custom curve.
+pub fn preset_index(gains: &Gains) -> Option<usize> {
+    presets().iter().position(|(_, preset)| preset == gains)
+}
+
+/// Gains from a stored list, missing bands are flat.
+pub fn gains(values: &[f64]) -> Gains {
+    let mut gains = [0.0; 10];
+    for (gain, value) in gains.iter_mut().zip(values) {
+        *gain = value.clamp(MIN_GAIN, MAX_GAIN);
+    }
+    gains
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_gains() {
+        assert_eq!(gains(&[]), [0.0; 10]);
+        assert_eq!(gains(&[20.0, -3.0])[..3], [MAX_GAIN, -3.0, 0.0]);
+        assert_eq!(preset_index(&gains(&[])), Some(0));
+        assert_eq!(preset_index(&gains(&[1.0])), None);
+    }
+}
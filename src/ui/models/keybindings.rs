@@ -0,0 +1,500 @@
+use std::{
+    cell::RefCell,
+    collections::BTreeMap,
+};
+
+use gettextrs::gettext;
+use gtk::{
+    gdk,
+    gio,
+    glib::translate::FromGlib,
+    prelude::*,
+};
+
+use super::SETTINGS;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum KeyScope {
+    /// Handled by the video player, unbound keys fall through to mpv.
+    Player,
+    /// Application accelerator for the given detailed action name.
+    App(&'static str),
+}
+
+#[derive(Debug, PartialEq, Eq)]
+pub struct KeyAction {
+    pub name: &'static str,
+    pub scope: KeyScope,
+    /// GTK accelerators, see `gtk::accelerator_parse`.
+    pub defaults: &'static [&'static str],
+}
+
+/// Every action that can be bound to a key.
+///
+/// Player defaults follow mpv's input.conf so nothing changes for people who
+/// never open the editor.
+pub const KEY_ACTIONS: &[KeyAction] = &[
+    KeyAction {
+        name: "toggle-pause",
+        scope: KeyScope::Player,
+        defaults: &["space", "p"],
+    },
+    KeyAction {
+        name: "seek-forward",
+        scope: KeyScope::Player,
+        defaults: &["Right"],
+    },
+    KeyAction {
+        name: "seek-backward",
+        scope: KeyScope::Player,
+        defaults: &["Left"],
+    },
+    KeyAction {
+        name: "seek-forward-long",
+        scope: KeyScope::Player,
+        defaults: &["Up"],
+    },
+    KeyAction {
+        name: "seek-backward-long",
+        scope: KeyScope::Player,
+        defaults: &["Down"],
+    },
+    KeyAction {
+        name: "volume-up",
+        scope: KeyScope::Player,
+        defaults: &["0", "asterisk"],
+    },
+    KeyAction {
+        name: "volume-down",
+        scope: KeyScope::Player,
+        defaults: &["9", "slash"],
+    },
+    KeyAction {
+        name: "mute",
+        scope: KeyScope::Player,
+        defaults: &["m"],
+    },
+    KeyAction {
+        name: "speed-up",
+        scope: KeyScope::Player,
+        defaults: &["bracketright"],
+    },
+    KeyAction {
+        name: "speed-down",
+        scope: KeyScope::Player,
+        defaults: &["bracketleft"],
+    },
+    KeyAction {
+        name: "speed-reset",
+        scope: KeyScope::Player,
+        defaults: &["BackSpace"],
+    },
+    KeyAction {
+        name: "sub-delay-increase",
+        scope: KeyScope::Player,
+        defaults: &["<Shift>z"],
+    },
+    KeyAction {
+        name: "sub-delay-decrease",
+        scope: KeyScope::Player,
+        defaults: &["z"],
+    },
+    KeyAction {
+        name: "cycle-audio",
+        scope: KeyScope::Player,
+        defaults: &["numbersign"],
+    },
+    KeyAction {
+        name: "cycle-sub",
+        scope: KeyScope::Player,
+        defaults: &["j"],
+    },
+    KeyAction {
+        name: "chapter-previous",
+        scope: KeyScope::Player,
+        defaults: &["Page_Down"],
+    },
+    KeyAction {
+        name: "chapter-next",
+        scope: KeyScope::Player,
+        defaults: &["Page_Up"],
+    },
+    KeyAction {
+        name: "previous-item",
+        scope: KeyScope::Player,
+        defaults: &["less"],
+    },
+    KeyAction {
+        name: "next-item",
+        scope: KeyScope::Player,
+        defaults: &["greater"],
+    },
+    KeyAction {
+        name: "fullscreen",
+        scope: KeyScope::Player,
+        defaults: &["f"],
+    },
+    KeyAction {
+        name: "screenshot",
+        scope: KeyScope::Player,
+        defaults: &["s"],
+    },
+    KeyAction {
+        name: "ab-loop",
+        scope: KeyScope::Player,
+        defaults: &["l"],
+    },
+    KeyAction {
+        name: "pip",
+        scope: KeyScope::Player,
+        defaults: &["<Shift>p"],
+    },
+    KeyAction {
+        name: "toggle-playlist",
+        scope: KeyScope::Player,
+        defaults: &["<Control>p"],
+    },
+    KeyAction {
+        name: "toggle-control-sidebar",
+        scope: KeyScope::Player,
+        defaults: &["<Control>b"],
+    },
+    KeyAction {
+        name: "search",
+        scope: KeyScope::App("win.search"),
+        defaults: &["<Control>f"],
+    },
+    KeyAction {
+        name: "sidebar",
+        scope: KeyScope::App("win.sidebar"),
+        defaults: &["F9"],
+    },
+    KeyAction {
+        name: "window-fullscreen",
+        scope: KeyScope::App("win.toggle-fullscreen"),
+        defaults: &["F11"],
+    },
+    KeyAction {
+        name: "preferences",
+        scope: KeyScope::App("setting.account"),
+        defaults: &["<Control>comma"],
+    },
+    KeyAction {
+        name: "shortcuts",
+        scope: KeyScope::App("win.show-help-overlay"),
+        defaults: &["<Control>question"],
+    },
+    KeyAction {
+        name: "about",
+        scope: KeyScope::App("win.about"),
+        defaults: &["<Control>n"],
+    },
+];
+
+/// Bindings changed from their defaults, by action name.
+type Overrides = BTreeMap<String, Vec<String>>;
+
+thread_local! {
+    /// Overrides for key presses in the player, parsed again only when the
+    /// setting changes.
+    static PLAYER_OVERRIDES: RefCell<Option<Overrides>> = const { RefCell::new(None) };
+}
+
+impl KeyAction {
+    pub fn title(&self) -> String {
+        match self.name {
+            "toggle-pause" => gettext("Play/Pause"),
+            "seek-forward" => gettext("Seek Forward"),
+            "seek-backward" => gettext("Seek Backward"),
+            "seek-forward-long" => gettext("Seek Forward 1 Minute"),
+            "seek-backward-long" => gettext("Seek Backward 1 Minute"),
+            "volume-up" => gettext("Volume Up"),
+            "volume-down" => gettext("Volume Down"),
+            "mute" => gettext("Mute"),
+            "speed-up" => gettext("Increase Playback Speed"),
+            "speed-down" => gettext("Decrease Playback Speed"),
+            "speed-reset" => gettext("Reset Playback Speed"),
+            "sub-delay-increase" => gettext("Increase Subtitle Delay"),
+            "sub-delay-decrease" => gettext("Decrease Subtitle Delay"),
+            "cycle-audio" => gettext("Next Audio Track"),
+            "cycle-sub" => gettext("Next Subtitle Track"),
+            "chapter-previous" => gettext("Previous Chapter"),
+            "chapter-next" => gettext("Next Chapter"),
+            "previous-item" => gettext("Previous Video"),
+            "next-item" => gettext("Next Video"),
+            "fullscreen" => gettext("Toggle Fullscreen"),
+            "screenshot" => gettext("Take Screenshot"),
+            "ab-loop" => gettext("Set/Clear A-B Loop"),
+            "pip" => gettext("Picture in Picture"),
+            "toggle-playlist" => gettext("Toggle Playlist"),
+            "toggle-control-sidebar" => gettext("Toggle Control Sidebar"),
+            "search" => gettext("Search"),
+            "sidebar" => gettext("Toggle Sidebar"),
+            "window-fullscreen" => gettext("Toggle Fullscreen"),
+            "preferences" => gettext("Preferences"),
+            "shortcuts" => gettext("Keyboard Shortcuts"),
+            "about" => gettext("About"),
+            _ => self.name.to_string(),
+        }
+    }
+
+    pub fn bindings(&self) -> Vec<String> {
+        resolve(&SETTINGS.key_bindings(), self)
+    }
+
+    pub fn is_default(&self) -> bool {
+        !SETTINGS.key_bindings().contains_key(self.name)
+    }
+}
+
+pub fn find(name: &str) -> Option<&'static KeyAction> {
+    KEY_ACTIONS.iter().find(|action| action.name == name)
+}
+
+/// Player action bound to a key press, if any.
+pub fn player_action(keyval: u32, state: gdk::ModifierType) -> Option<&'static KeyAction> {
+    let accel = accelerator(keyval, state)?;
+    PLAYER_OVERRIDES.with_borrow_mut(|cache| {
+        let overrides = cache.get_or_insert_with(|| {
+            SETTINGS.connect_changed(Some("key-bindings"), |_, _| {
+                PLAYER_OVERRIDES.set(Some(SETTINGS.key_bindings()));
+            });
+            SETTINGS.key_bindings()
+        });
+        KEY_ACTIONS
+            .iter()
+            .filter(|action| action.scope == KeyScope::Player)
+            .find(|action| resolve(overrides, action).contains(&accel))
+    })
+}
+
+/// The action already using `accel`, ignoring `except`.
+pub fn conflict(accel: &str, except: &str) -> Option<&'static KeyAction> {
+    find_conflict(&SETTINGS.key_bindings(), accel, except)
+}
+
+/// Bind `accel` to `name`, taking it away from any other action. With
+/// `replace` it becomes the only binding of `name`.
+pub fn assign(name: &str, accel: &str, replace: bool) {
+    let mut overrides = SETTINGS.key_bindings();
+    assign_in(&mut overrides, name, accel, replace);
+    save(&overrides);
+}
+
+/// Leave `name` without any key.
+pub fn clear(name: &str) {
+    let Some(action) = find(name) else {
+        return;
+    };
+    let mut overrides = SETTINGS.key_bindings();
+    update(&mut overrides, action, Vec::new());
+    save(&overrides);
+}
+
+pub fn reset(name: &str) {
+    let mut overrides = SETTINGS.key_bindings();
+    overrides.remove(name);
+    save(&overrides);
+}
+
+pub fn reset_all() {
+    save(&Overrides::new());
+}
+
+/// Every binding, including defaults, as pretty printed JSON.
+pub fn export() -> String {
+    let overrides = SETTINGS.key_bindings();
+    let bindings: BTreeMap<&str, Vec<String>> = KEY_ACTIONS
+        .iter()
+        .map(|action| (action.name, resolve(&overrides, action)))
+        .collect();
+    serde_json::to_string_pretty(&bindings).expect("Failed to serialize key bindings")
+}
+
+/// Replace the bindings with an exported file. Unknown actions and
+/// accelerators GTK can't parse are dropped.
+pub fn import(json: &str) -> Result<(), serde_json::Error> {
+    let imported: Overrides = serde_json::from_str(json)?;
+    let mut overrides = Overrides::new();
+    for (name, accels) in imported {
+        let Some(action) = find(&name) else {
+            continue;
+        };
+        let accels = accels
+            .into_iter()
+            .filter(|accel| gtk::accelerator_parse(accel).is_some())
+            .collect();
+        update(&mut overrides, action, accels);
+    }
+    save(&overrides);
+    Ok(())
+}
+
+/// Register the application accelerators with GTK.
+pub fn apply_app_accels() {
+    let Some(app) = gio::Application::default().and_downcast::<gtk::Application>() else {
+        return;
+    };
+    let overrides = SETTINGS.key_bindings();
+    for action in KEY_ACTIONS {
+        let KeyScope::App(detailed_name) = action.scope else {
+            continue;
+        };
+        let accels = resolve(&overrides, action);
+        app.set_accels_for_action(
+            detailed_name,
+            &accels.iter().map(String::as_str).collect::<Vec<_>>(),
+        );
+    }
+}
+
+/// Accelerator string for a key press, `None` for lone modifiers.
+///
+/// Letters keep Shift and are stored lowercase, other printable keys drop it
+/// since Shift is already part of the symbol, so `{` is `braceleft` and not
+/// `<Shift>braceleft`.
+pub fn accelerator(keyval: u32, state: gdk::ModifierType) -> Option<String> {
+    let key = unsafe { gdk::Key::from_glib(keyval) };
+    if key
+        .name()
+        .is_some_and(|name| MODIFIER_KEYS.contains(&name.as_str()))
+    {
+        return None;
+    }
+
+    let mut state = state & gtk::accelerator_get_default_mod_mask();
+    let lower = key.to_lower();
+    if lower == key
+        && key
+            .to_unicode()
+            .is_some_and(|c| !c.is_alphanumeric() && !c.is_whitespace())
+    {
+        state.remove(gdk::ModifierType::SHIFT_MASK);
+    }
+
+    Some(gtk::accelerator_name(lower, state).to_string())
+}
+
+const MODIFIER_KEYS: &[&str] = &[
+    "Shift_L",
+    "Shift_R",
+    "Control_L",
+    "Control_R",
+    "Alt_L",
+    "Alt_R",
+    "Meta_L",
+    "Meta_R",
+    "Super_L",
+    "Super_R",
+    "Hyper_L",
+    "Hyper_R",
+    "ISO_Level3_Shift",
+    "Caps_Lock",
+    "Num_Lock",
+];
+
+fn save(overrides: &Overrides) {
+    SETTINGS
+        .set_key_bindings(overrides)
+        .expect("Failed to set key bindings");
+}
+
+fn resolve(overrides: &Overrides, action: &KeyAction) -> Vec<String> {
+    overrides.get(action.name).cloned().unwrap_or_else(|| {
+        action
+            .defaults
+            .iter()
+            .map(|accel| accel.to_string())
+            .collect()
+    })
+}
+
+fn update(overrides: &mut Overrides, action: &KeyAction, accels: Vec<String>) {
+    let mut sorted: Vec<&str> = accels.iter().map(String::as_str).collect();
+    sorted.sort_unstable();
+    let mut defaults = action.defaults.to_vec();
+    defaults.sort_unstable();
+
+    if sorted == defaults {
+        overrides.remove(action.name);
+    } else {
+        overrides.insert(action.name.to_string(), accels);
+    }
+}
+
+fn find_conflict(overrides: &Overrides, accel: &str, except: &str) -> Option<&'static KeyAction> {
+    KEY_ACTIONS
+        .iter()
+        .filter(|action| action.name != except)
+        .find(|action| resolve(overrides, action).iter().any(|a| a == accel))
+}
+
+fn assign_in(overrides: &mut Overrides, name: &str, accel: &str, replace: bool) {
+    while let Some(other) = find_conflict(overrides, accel, name) {
+        let mut accels = resolve(overrides, other);
+        accels.retain(|a| a != accel);
+        update(overrides, other, accels);
+    }
+
+    let Some(action) = find(name) else {
+        return;
+    };
+    let mut accels = if replace {
+        Vec::new()
+    } else {
+        resolve(overrides, action)
+    };
+    if !accels.iter().any(|a| a == accel) {
+        accels.push(accel.to_string());
+    }
+    update(overrides, action, accels);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_unique_defaults() {
+        for action in KEY_ACTIONS {
+            for accel in action.defaults {
+                assert_eq!(
+                    find_conflict(&Overrides::new(), accel, action.name),
+                    None,
+                    "{} is bound twice",
+                    accel
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn test_assign() {
+        let mut overrides = Overrides::new();
+
+        assign_in(&mut overrides, "mute", "space", false);
+        assert_eq!(
+            resolve(&overrides, find("mute").unwrap()),
+            vec!["m", "space"]
+        );
+        assert_eq!(
+            resolve(&overrides, find("toggle-pause").unwrap()),
+            vec!["p"]
+        );
+        assert_eq!(
+            find_conflict(&overrides, "space", "").map(|action| action.name),
+            Some("mute")
+        );
+
+        assign_in(&mut overrides, "toggle-pause", "space", false);
+        assert!(overrides.get("toggle-pause").is_none());
+        assert!(overrides.get("mute").is_none());
+
+        assign_in(&mut overrides, "mute", "p", true);
+        assert_eq!(resolve(&overrides, find("mute").unwrap()), vec!["p"]);
+        assert_eq!(
+            resolve(&overrides, find("toggle-pause").unwrap()),
+            vec!["space"]
+        );
+    }
+}
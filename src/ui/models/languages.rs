@@ -0,0 +1,311 @@
+use super::SETTINGS;
+
+/// (639-1, 639-2/B, 639-2/T, English name)
+pub type Language = (&'static str, &'static str, &'static str, &'static str);
+
+/// ISO 639 language table.
+///
+/// Preferences are stored as 639-2/B codes because that is what Emby and
+/// Jellyfin report for media streams, but any of the three codes matches.
+pub const LANGUAGES: &[Language] = &[
+    ("aa", "aar", "aar", "Afar"),
+    ("ab", "abk", "abk", "Abkhazian"),
+    ("af", "afr", "afr", "Afrikaans"),
+    ("ak", "aka", "aka", "Akan"),
+    ("am", "amh", "amh", "Amharic"),
+    ("an", "arg", "arg", "Aragonese"),
+    ("ar", "ara", "ara", "Arabic"),
+    ("as", "asm", "asm", "Assamese"),
+    ("av", "ava", "ava", "Avaric"),
+    ("ay", "aym", "aym", "Aymara"),
+    ("az", "aze", "aze", "Azerbaijani"),
+    ("ba", "bak", "bak", "Bashkir"),
+    ("be", "bel", "bel", "Belarusian"),
+    ("bg", "bul", "bul", "Bulgarian"),
+    ("bi", "bis", "bis", "Bislama"),
+    ("bm", "bam", "bam", "Bambara"),
+    ("bn", "ben", "ben", "Bengali"),
+    ("bo", "tib", "bod", "Tibetan"),
+    ("br", "bre", "bre", "Breton"),
+    ("bs", "bos", "bos", "Bosnian"),
+    ("ca", "cat", "cat", "Catalan"),
+    ("ce", "che", "che", "Chechen"),
+    ("ch", "cha", "cha", "Chamorro"),
+    ("co", "cos", "cos", "Corsican"),
+    ("cr", "cre", "cre", "Cree"),
+    ("cs", "cze", "ces", "Czech"),
+    ("cv", "chv", "chv", "Chuvash"),
+    ("cy", "wel", "cym", "Welsh"),
+    ("da", "dan", "dan", "Danish"),
+    ("de", "ger", "deu", "German"),
+    ("dv", "div", "div", "Divehi"),
+    ("dz", "dzo", "dzo", "Dzongkha"),
+    ("ee", "ewe", "ewe", "Ewe"),
+    ("el", "gre", "ell", "Greek"),
+    ("en", "eng", "eng", "English"),
+    ("eo", "epo", "epo", "Esperanto"),
+    ("es", "spa", "spa", "Spanish"),
+    ("et", "est", "est", "Estonian"),
+    ("eu", "baq", "eus", "Basque"),
+    ("fa", "per", "fas", "Persian"),
+    ("ff", "ful", "ful", "Fulah"),
+    ("fi", "fin", "fin", "Finnish"),
+    ("fj", "fij", "fij", "Fijian"),
+    ("fo", "fao", "fao", "Faroese"),
+    ("fr", "fre", "fra", "French"),
+    ("fy", "fry", "fry", "Western Frisian"),
+    ("ga", "gle", "gle", "Irish"),
+    ("gd", "gla", "gla", "Scottish Gaelic"),
+    ("gl", "glg", "glg", "Galician"),
+    ("gn", "grn", "grn", "Guarani"),
+    ("gu", "guj", "guj", "Gujarati"),
+    ("gv", "glv", "glv", "Manx"),
+    ("ha", "hau", "hau", "Hausa"),
+    ("he", "heb", "heb", "Hebrew"),
+    ("hi", "hin", "hin", "Hindi"),
+    ("hr", "hrv", "hrv", "Croatian"),
+    ("ht", "hat", "hat", "Haitian"),
+    ("hu", "hun", "hun", "Hungarian"),
+    ("hy", "arm", "hye", "Armenian"),
+    ("id", "ind", "ind", "Indonesian"),
+    ("ig", "ibo", "ibo", "Igbo"),
+    ("is", "ice", "isl", "Icelandic"),
+    ("it", "ita", "ita", "Italian"),
+    ("iu", "iku", "iku", "Inuktitut"),
+    ("ja", "jpn", "jpn", "Japanese"),
+    ("jv", "jav", "jav", "Javanese"),
+    ("ka", "geo", "kat", "Georgian"),
+    ("kg", "kon", "kon", "Kongo"),
+    ("ki", "kik", "kik", "Kikuyu"),
+    ("kk", "kaz", "kaz", "Kazakh"),
+    ("kl", "kal", "kal", "Kalaallisut"),
+    ("km", "khm", "khm", "Khmer"),
+    ("kn", "kan", "kan", "Kannada"),
+    ("ko", "kor", "kor", "Korean"),
+    ("ks", "kas", "kas", "Kashmiri"),
+    ("ku", "kur", "kur", "Kurdish"),
+    ("kw", "cor", "cor", "Cornish"),
+    ("ky", "kir", "kir", "Kirghiz"),
+    ("la", "lat", "lat", "Latin"),
+    ("lb", "ltz", "ltz", "Luxembourgish"),
+    ("lg", "lug", "lug", "Ganda"),
+    ("ln", "lin", "lin", "Lingala"),
+    ("lo", "lao", "lao", "Lao"),
+    ("lt", "lit", "lit", "Lithuanian"),
+    ("lv", "lav", "lav", "Latvian"),
+    ("mg", "mlg", "mlg", "Malagasy"),
+    ("mi", "mao", "mri", "Maori"),
+    ("mk", "mac", "mkd", "Macedonian"),
+    ("ml", "mal", "mal", "Malayalam"),
+    ("mn", "mon", "mon", "Mongolian"),
+    ("mr", "mar", "mar", "Marathi"),
+    ("ms", "may", "msa", "Malay"),
+    ("mt", "mlt", "mlt", "Maltese"),
+    ("my", "bur", "mya", "Burmese"),
+    ("nb", "nob", "nob", "Norwegian Bokmål"),
+    ("ne", "nep", "nep", "Nepali"),
+    ("nl", "dut", "nld", "Dutch"),
+    ("nn", "nno", "nno", "Norwegian Nynorsk"),
+    ("no", "nor", "nor", "Norwegian"),
+    ("ny", "nya", "nya", "Chichewa"),
+    ("oc", "oci", "oci", "Occitan"),
+    ("om", "orm", "orm", "Oromo"),
+    ("or", "ori", "ori", "Oriya"),
+    ("pa", "pan", "pan", "Punjabi"),
+    ("pl", "pol", "pol", "Polish"),
+    ("ps", "pus", "pus", "Pashto"),
+    ("pt", "por", "por", "Portuguese"),
+    ("qu", "que", "que", "Quechua"),
+    ("rm", "roh", "roh", "Romansh"),
+    ("rn", "run", "run", "Rundi"),
+    ("ro", "rum", "ron", "Romanian"),
+    ("ru", "rus", "rus", "Russian"),
+    ("rw", "kin", "kin", "Kinyarwanda"),
+    ("sa", "san", "san", "Sanskrit"),
+    ("sc", "srd", "srd", "Sardinian"),
+    ("sd", "snd", "snd", "Sindhi"),
+    ("se", "sme", "sme", "Northern Sami"),
+    ("sg", "sag", "sag", "Sango"),
+    ("si", "sin", "sin", "Sinhala"),
+    ("sk", "slo", "slk", "Slovak"),
+    ("sl", "slv", "slv", "Slovenian"),
+    ("sm", "smo", "smo", "Samoan"),
+    ("sn", "sna", "sna", "Shona"),
+    ("so", "som", "som", "Somali"),
+    ("sq", "alb", "sqi", "Albanian"),
+    ("sr", "srp", "srp", "Serbian"),
+    ("ss", "ssw", "ssw", "Swati"),
+    ("st", "sot", "sot", "Southern Sotho"),
+    ("su", "sun", "sun", "Sundanese"),
+    ("sv", "swe", "swe", "Swedish"),
+    ("sw", "swa", "swa", "Swahili"),
+    ("ta", "tam", "tam", "Tamil"),
+    ("te", "tel", "tel", "Telugu"),
+    ("tg", "tgk", "tgk", "Tajik"),
+    ("th", "tha", "tha", "Thai"),
+    ("ti", "tir", "tir", "Tigrinya"),
+    ("tk", "tuk", "tuk", "Turkmen"),
+    ("tl", "tgl", "tgl", "Tagalog"),
+    ("tn", "tsn", "tsn", "Tswana"),
+    ("to", "ton", "ton", "Tonga"),
+    ("tr", "tur", "tur", "Turkish"),
+    ("ts", "tso", "tso", "Tsonga"),
+    ("tt", "tat", "tat", "Tatar"),
+    ("tw", "twi", "twi", "Twi"),
+    ("ug", "uig", "uig", "Uighur"),
+    ("uk", "ukr", "ukr", "Ukrainian"),
+    ("ur", "urd", "urd", "Urdu"),
+    ("uz", "uzb", "uzb", "Uzbek"),
+    ("ve", "ven", "ven", "Venda"),
+    ("vi", "vie", "vie", "Vietnamese"),
+    ("wa", "wln", "wln", "Walloon"),
+    ("wo", "wol", "wol", "Wolof"),
+    ("xh", "xho", "xho", "Xhosa"),
+    ("yi", "yid", "yid", "Yiddish"),
+    ("yo", "yor", "yor", "Yoruba"),
+    ("za", "zha", "zha", "Zhuang"),
+    ("zh", "chi", "zho", "Chinese"),
+    ("zu", "zul", "zul", "Zulu"),
+    ("", "fil", "fil", "Filipino"),
+    ("", "yue", "yue", "Cantonese"),
+    ("", "cmn", "cmn", "Mandarin"),
+    ("", "chs", "chs", "Chinese Simplified"),
+    ("", "cht", "cht", "Chinese Traditional"),
+    ("", "und", "und", "Undetermined"),
+];
+
+fn find(code: &str) -> Option<&'static Language> {
+    let code = code.to_lowercase();
+    LANGUAGES
+        .iter()
+        .find(|(a, b, t, _)| (!a.is_empty() && *a == code) || *b == code || *t == code)
+}
+
+/// Code of a language chosen in the single-choice setting the lists replaced.
+/// Subtitles matched 2 and 4 against "Chinese Simplified" and "Chinese
+/// Traditional" in stream titles.
+pub fn legacy_language_code(index: i32) -> Option<&'static str> {
+    let code = match index {
+        1 => "eng",
+        2 => "chs",
+        3 => "jpn",
+        4 => "cht",
+        5 => "ara",
+        6 => "nob",
+        7 => "por",
+        8 => "fre",
+        _ => return None,
+    };
+    Some(code)
+}
+
+/// Words naming the script of a Chinese stream in its title, `None` unless
+/// `code` asks for one script.
+///
+/// Servers tag either script as plain Chinese.
+pub fn chinese_script_hints(code: &str) -> Option<&'static [&'static str]> {
+    match code.to_lowercase().as_str() {
+        "chs" => Some(&["simplified", "简体", "简中"]),
+        "cht" => Some(&["traditional", "繁體", "繁体", "繁中"]),
+        _ => None,
+    }
+}
+
+/// `langs` as an mpv `alang`/`slang` list. The script codes are followed by
+/// plain Chinese, which is what mpv finds in the tracks.
+pub fn mpv_language_list(langs: &[String]) -> String {
+    let mut list: Vec<&str> = Vec::new();
+    for lang in langs {
+        let codes = match chinese_script_hints(lang) {
+            Some(_) => vec![lang.as_str(), "chi", "zho"],
+            None => vec![lang.as_str()],
+        };
+        for code in codes {
+            if !list.contains(&code) {
+                list.push(code);
+            }
+        }
+    }
+    list.join(",")
+}
+
+/// English name of `code`, or the code itself for unknown tags.
+pub fn language_name(code: &str) -> String {
+    find(code)
+        .map(|(_, _, _, name)| name.to_string())
+        .unwrap_or_else(|| code.to_string())
+}
+
+/// Whether two language tags name the same language, whatever ISO 639 form
+/// they are written in.
+pub fn language_matches(a: &str, b: &str) -> bool {
+    if a.eq_ignore_ascii_case(b) {
+        return true;
+    }
+    match (find(a), find(b)) {
+        (Some(a), Some(b)) => a == b,
+        _ => false,
+    }
+}
+
+/// ISO 639-2/B form of `code`, the one the servers store. The Chinese script
+/// codes are no ISO codes and become plain Chinese.
+pub fn iso639_2(code: &str) -> Option<&'static str> {
+    find(&code.to_lowercase()).map(|(_, code, _, _)| match *code {
+        "chs" | "cht" => "chi",
+        code => code,
+    })
+}
+
+/// Preferred subtitle languages first, then every other known language.
+pub fn subtitle_language_codes() -> Vec<String> {
+    let mut codes = SETTINGS.mpv_subtitle_preferred_langs();
+    for (_, code, _, _) in LANGUAGES {
+        if !codes.iter().any(|c| language_matches(c, code)) {
+            codes.push(code.to_string());
+        }
+    }
+    codes
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_language_matches() {
+        assert!(language_matches("fre", "fra"));
+        assert!(language_matches("fr", "FRE"));
+        assert!(language_matches("chi", "zho"));
+        assert!(!language_matches("eng", "jpn"));
+        assert!(language_matches("xyz", "XYZ"));
+        assert!(!language_matches("xyz", "eng"));
+    }
+
+    #[test]
+    fn test_language_name() {
+        assert_eq!(language_name("ger"), "German");
+        assert_eq!(language_name("de"), "German");
+        assert_eq!(language_name("xyz"), "xyz");
+    }
+
+    #[test]
+    fn test_iso639_2() {
+        assert_eq!(iso639_2("de"), Some("ger"));
+        assert_eq!(iso639_2("DEU"), Some("ger"));
+        assert_eq!(iso639_2("hi"), Some("hin"));
+        assert_eq!(iso639_2("cht"), Some("chi"));
+        assert_eq!(iso639_2("xyz"), None);
+    }
+
+    #[test]
+    fn test_legacy_language_code() {
+        assert_eq!(legacy_language_code(0), None);
+        assert_eq!(legacy_language_code(2), Some("chs"));
+        assert_eq!(legacy_language_code(4), Some("cht"));
+        assert_eq!(language_name("cht"), "Chinese Traditional");
+
+        let langs = ["cht".to_string(), "eng".to_string(), "chi".to_string()];
+        assert_eq!(mpv_language_list(&langs), "cht,chi,zho,eng");
+    }
+}
@@ -0,0 +1,227 @@
+use std::path::{
+    Path,
+    PathBuf,
+};
+
+use gtk::glib;
+
+use crate::client::{
+    emby_client::EMBY_CLIENT,
+    structs::{
+        LyricLine,
+        TICKS_PER_SECOND,
+    },
+};
+
+/// Codecs Emby gives lyric streams of audio items.
+const LYRIC_CODECS: &[&str] = &["lrc", "txt"];
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Line {
+    /// Seconds into the song, `None` for unsynced lyrics.
+    pub start: Option<f64>,
+    pub text: String,
+}
+
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Lyrics {
+    pub lines: Vec<Line>,
+}
+
+impl Lyrics {
+    /// Reads LRC, lines without a timestamp make unsynced lyrics.
+    pub fn parse_lrc(contents: &str) -> Self {
+        let mut offset = 0.0;
+        let mut synced = Vec::new();
+        let mut plain = Vec::new();
+
+        for line in contents.lines() {
+            let mut rest = line.trim();
+            let mut starts = Vec::new();
+            while let Some((tag, after)) =
+                rest.strip_prefix('[').and_then(|tag| tag.split_once(']'))
+            {
+                if let Some(start) = parse_timestamp(tag) {
+                    starts.push(start);
+                } else if let Some(value) = tag.strip_prefix("offset:") {
+                    // Milliseconds, positive shows lines earlier
+                    offset = value.trim().parse::<f64>().unwrap_or_default() / 1000.0;
+                } else if !starts.is_empty() {
+                    break;
+                }
+                rest = after;
+            }
+
+            if starts.is_empty() {
+                if !line.trim_start().starts_with('[') {
+                    plain.push(line.trim().to_string());
+                }
+                continue;
+            }
+            for start in starts {
+                synced.push((start, rest.trim().to_string()));
+            }
+        }
+
+        if synced.is_empty() {
+            while plain.last().is_some_and(|text| text.is_empty()) {
+                plain.pop();
+            }
+            return Self {
+                lines: plain
+                    .into_iter()
+                    .map(|text| Line { start: None, text })
+                    .collect(),
+            };
+        }
+
+        synced.sort_by(|a, b| a.0.total_cmp(&b.0));
+        Self {
+            lines: synced
+                .into_iter()
+                .map(|(start, text)| Line {
+                    start: Some((start - offset).max(0.0)),
+                    text,
+                })
+                .collect(),
+        }
+    }
+
+    pub fn from_server(lines: Vec<LyricLine>) -> Self {
+        Self {
+            lines: lines
+                .into_iter()
+                .map(|line| Line {
+                    start: line.start.map(|ticks| ticks as f64 / TICKS_PER_SECOND),
+                    text: line.text,
+                })
+                .collect(),
+        }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.lines.iter().all(|line| line.text.trim().is_empty())
+    }
+
+    pub fn is_synced(&self) -> bool {
+        self.lines.iter().any(|line| line.start.is_some())
+    }
+
+    /// Index of the line sung at `position`, `None` before the first one.
+    pub fn line_at(&self, position: f64) -> Option<usize> {
+        if !self.is_synced() {
+            return None;
+        }
+        self.lines
+            .iter()
+            .rposition(|line| line.start.is_some_and(|start| start <= position))
+    }
+}
+
+/// `mm:ss.xx` to seconds.
+fn parse_timestamp(tag: &str) -> Option<f64> {
+    let (minutes, seconds) = tag.split_once(':')?;
+    let minutes = minutes.trim().parse::<u32>().ok()?;
+    let seconds = seconds.trim().replace(':', ".").parse::<f64>().ok()?;
+    Some(minutes as f64 * 60.0 + seconds)
+}
+
+/// Where `.lrc` files named `Artist - Title.lrc` or `Title.lrc` are looked
+/// up when the server has no lyrics.
+pub fn lyrics_dir() -> PathBuf {
+    glib::user_config_dir().join("tsukimi").join("lyrics")
+}
+
+fn local_candidates(media_path: Option<&str>, artist: &str, name: &str) -> Vec<PathBuf> {
+    let mut candidates = Vec::new();
+    // The server's path only resolves when its library is mounted here too
+    if let Some(path) = media_path {
+        candidates.push(Path::new(path).with_extension("lrc"));
+    }
+    let name = name.replace(['/', '\\'], "_");
+    if !artist.is_empty() {
+        let artist = artist.replace(['/', '\\'], "_");
+        candidates.push(lyrics_dir().join(format!("{} - {}.lrc", artist, name)));
+    }
+    candidates.push(lyrics_dir().join(format!("{}.lrc", name)));
+    candidates
+}
+
+/// Lyrics of the song `id` from the server, or a local `.lrc` file.
+pub async fn fetch(id: &str, artist: &str, name: &str) -> Option<Lyrics> {
+    if EMBY_CLIENT.is_jellyfin() {
+        match EMBY_CLIENT.get_lyrics(id).await {
+            Ok(response) => {
+                let lyrics = Lyrics::from_server(response.lyrics);
+                if !lyrics.is_empty() {
+                    return Some(lyrics);
+                }
+            }
+            Err(e) => tracing::debug!("No lyrics on the server for {}: {}", id, e),
+        }
+    }
+
+    let source = match EMBY_CLIENT.get_item_info(id).await {
+        Ok(item) => item
+            .media_sources
+            .and_then(|sources| sources.into_iter().next()),
+        Err(e) => {
+            tracing::warn!("Failed to get song info: {}", e);
+            None
+        }
+    };
+
+    if let Some(source) = &source {
+        for stream in source.media_streams.iter().filter(|stream| {
+            stream.stream_type == "Subtitle"
+                && stream
+                    .codec
+                    .as_deref()
+                    .is_some_and(|codec| LYRIC_CODECS.contains(&codec))
+        }) {
+            let codec = stream.codec.as_deref().unwrap_or("lrc");
+            match EMBY_CLIENT
+                .get_subtitle_text(id, &source.id, stream.index, codec)
+                .await
+            {
+                Ok(contents) => {
+                    let lyrics = Lyrics::parse_lrc(&contents);
+                    if !lyrics.is_empty() {
+                        return Some(lyrics);
+                    }
+                }
+                Err(e) => tracing::warn!("Failed to get lyrics: {}", e),
+            }
+        }
+    }
+
+    let media_path = source.as_ref().and_then(|source| source.path.as_deref());
+    local_candidates(media_path, artist, name)
+        .into_iter()
+        .find_map(|path| std::fs::read_to_string(path).ok())
+        .map(|contents| Lyrics::parse_lrc(&contents))
+        .filter(|lyrics| !lyrics.is_empty())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_lrc() {
+        let lyrics = Lyrics::parse_lrc(
+            "[ar:Someone]\n[offset:500]\n[00:12.50][01:02.00]Chorus\n[00:05.00]First\n[00:20]\n",
+        );
+        let starts: Vec<_> = lyrics.lines.iter().map(|line| line.start).collect();
+        assert_eq!(starts, vec![Some(4.5), Some(12.0), Some(19.5), Some(61.5)]);
+        assert_eq!(lyrics.lines[1].text, "Chorus");
+        assert_eq!(lyrics.line_at(3.0), None);
+        assert_eq!(lyrics.line_at(15.0), Some(1));
+        assert_eq!(lyrics.line_at(100.0), Some(3));
+
+        let plain = Lyrics::parse_lrc("One\n\nTwo\n\n");
+        assert!(!plain.is_synced());
+        assert_eq!(plain.lines.len(), 3);
+        assert_eq!(plain.line_at(10.0), None);
+    }
+}
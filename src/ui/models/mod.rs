@@ -1,5 +1,17 @@
 use once_cell::sync::Lazy;
+pub mod audiobook;
+pub mod discography;
+pub mod equalizer;
+pub mod keybindings;
+pub mod languages;
+pub mod lyrics;
+pub mod music_queue;
+pub mod resume;
+pub mod screenshots;
 pub mod settings;
+pub mod shader_profiles;
+pub mod sleep_timer;
+pub mod track_memory;
 pub use self::settings::Settings;
 use crate::client::emby_client::EMBY_CLIENT;
 pub static SETTINGS: Lazy<Settings> = Lazy::new(Settings::default);
@@ -0,0 +1,126 @@
+use serde::{
+    Deserialize,
+    Serialize,
+};
+
+use super::emby_cache_path;
+use crate::ui::provider::core_song::CoreSong;
+
+const MUSIC_QUEUE_FILE: &str = "music-queue.json";
+
+/// What the toolbar shows of a queued song, so it can be restored without
+/// asking the server.
+#[derive(Serialize, Deserialize, Default, PartialEq, Clone, Debug)]
+pub struct QueuedSong {
+    pub id: String,
+    #[serde(default)]
+    pub name: String,
+    #[serde(default)]
+    pub artist: String,
+    #[serde(default)]
+    pub album_id: String,
+    #[serde(default)]
+    pub album: String,
+    #[serde(default)]
+    pub have_single_track_image: bool,
+    /// Seconds.
+    #[serde(default)]
+    pub duration: u64,
+    #[serde(default)]
+    pub normalization_gain: f64,
+}
+
+impl From<&CoreSong> for QueuedSong {
+    fn from(core_song: &CoreSong) -> Self {
+        Self {
+            id: core_song.id(),
+            name: core_song.name(),
+            artist: core_song.artist(),
+            album_id: core_song.album_id(),
+            album: core_song.album(),
+            have_single_track_image: core_song.have_single_track_image(),
+            duration: core_song.duration(),
+            normalization_gain: core_song.normalization_gain(),
+        }
+    }
+}
+
+impl QueuedSong {
+    pub fn core_song(&self) -> CoreSong {
+        let core_song = CoreSong::new(&self.id);
+        core_song.set_name(self.name.clone());
+        core_song.set_artist(self.artist.clone());
+        core_song.set_album_id(self.album_id.clone());
+        core_song.set_album(self.album.clone());
+        core_song.set_have_single_track_image(self.have_single_track_image);
+        core_song.set_duration(self.duration);
+        core_song.set_normalization_gain(self.normalization_gain);
+        core_song
+    }
+}
+
+/// Music player state kept in the server's cache between launches.
+#[derive(Serialize, Deserialize, PartialEq, Clone, Debug)]
+pub struct MusicQueue {
+    pub songs: Vec<QueuedSong>,
+    /// Index of the active song in `songs`.
+    pub index: usize,
+    /// Seconds into the active song.
+    #[serde(default)]
+    pub position: f64,
+    #[serde(default)]
+    pub repeat_mode: String,
+    #[serde(default)]
+    pub shuffle: bool,
+    #[serde(default = "default_volume")]
+    pub volume: f64,
+}
+
+fn default_volume() -> f64 {
+    1.0
+}
+
+impl MusicQueue {
+    pub fn load() -> Option<Self> {
+        let contents = std::fs::read_to_string(emby_cache_path().join(MUSIC_QUEUE_FILE)).ok()?;
+        serde_json::from_str::<Self>(&contents)
+            .ok()
+            .filter(|queue| queue.index < queue.songs.len())
+    }
+
+    pub fn save(&self) {
+        let path = emby_cache_path().join(MUSIC_QUEUE_FILE);
+        match serde_json::to_string(self) {
+            Ok(serialized) => {
+                if let Err(e) = std::fs::write(path, serialized) {
+                    tracing::warn!("Failed to write music queue: {}", e);
+                }
+            }
+            Err(e) => tracing::warn!("Failed to serialize music queue: {}", e),
+        }
+    }
+
+    pub fn clear() {
+        let path = emby_cache_path().join(MUSIC_QUEUE_FILE);
+        if path.exists() {
+            if let Err(e) = std::fs::remove_file(path) {
+                tracing::warn!("Failed to remove music queue: {}", e);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_deserialize_defaults() {
+        let queue: MusicQueue =
+            serde_json::from_str(r#"{"songs":[{"id":"1"},{"id":"2"}],"index":1}"#).unwrap();
+        assert_eq!(queue.songs[1].id, "2");
+        assert_eq!(queue.position, 0.0);
+        assert_eq!(queue.volume, 1.0);
+        assert!(!queue.shuffle);
+    }
+}
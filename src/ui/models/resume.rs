@@ -0,0 +1,90 @@
+use std::collections::BTreeMap;
+
+use serde::{
+    Deserialize,
+    Serialize,
+};
+
+use super::SETTINGS;
+
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub enum ResumeBehavior {
+    #[default]
+    Ask = 0,
+    Resume = 1,
+    StartOver = 2,
+}
+
+impl From<i32> for ResumeBehavior {
+    fn from(value: i32) -> Self {
+        match value {
+            1 => Self::Resume,
+            2 => Self::StartOver,
+            _ => Self::Ask,
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, Default, PartialEq)]
+pub struct ResumeDefaults {
+    /// Behavior by library id.
+    #[serde(default)]
+    pub libraries: BTreeMap<String, i32>,
+}
+
+impl ResumeDefaults {
+    pub fn load() -> Self {
+        SETTINGS.mpv_resume_defaults()
+    }
+
+    pub fn save(&self) {
+        if let Err(e) = SETTINGS.set_mpv_resume_defaults(self) {
+            tracing::warn!("Failed to save resume defaults: {}", e);
+        }
+    }
+
+    pub fn get(&self, library_id: &str) -> Option<ResumeBehavior> {
+        self.libraries
+            .get(library_id)
+            .map(|behavior| ResumeBehavior::from(*behavior))
+    }
+
+    /// `None` makes the library follow the global setting again.
+    pub fn set(&mut self, library_id: &str, behavior: Option<ResumeBehavior>) {
+        match behavior {
+            Some(behavior) => self
+                .libraries
+                .insert(library_id.to_string(), behavior as i32),
+            None => self.libraries.remove(library_id),
+        };
+    }
+
+    pub fn behavior_for(&self, library_id: Option<&str>) -> ResumeBehavior {
+        library_id
+            .and_then(|id| self.get(id))
+            .unwrap_or_else(|| ResumeBehavior::from(SETTINGS.mpv_resume_behavior()))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_library_defaults() {
+        let mut defaults = ResumeDefaults::default();
+        defaults.set("movies", Some(ResumeBehavior::StartOver));
+        defaults.set("shows", Some(ResumeBehavior::Resume));
+        assert_eq!(defaults.get("movies"), Some(ResumeBehavior::StartOver));
+
+        defaults.set("shows", None);
+        assert_eq!(defaults.get("shows"), None);
+
+        let json = serde_json::to_string(&defaults).unwrap();
+        assert_eq!(json, r#"{"libraries":{"movies":2}}"#);
+        assert_eq!(
+            serde_json::from_str::<ResumeDefaults>("{}").unwrap(),
+            ResumeDefaults::default()
+        );
+    }
+}
@@ -0,0 +1,121 @@
+use std::path::{
+    Path,
+    PathBuf,
+};
+
+use gtk::glib;
+
+use super::SETTINGS;
+
+/// Extension and content type for each `mpv-screenshot-format` value.
+const FORMATS: &[(&str, &str)] = &[
+    ("png", "image/png"),
+    ("jpg", "image/jpeg"),
+    ("webp", "image/webp"),
+];
+
+/// Folder screenshots are saved to, one sub folder per item.
+pub fn screenshot_dir() -> PathBuf {
+    let dir = SETTINGS.mpv_screenshot_dir();
+    if !dir.is_empty() {
+        return PathBuf::from(dir);
+    }
+    glib::user_special_dir(glib::UserDirectory::Pictures)
+        .unwrap_or_else(glib::home_dir)
+        .join("Tsukimi")
+}
+
+pub fn item_dir(id: &str) -> PathBuf {
+    screenshot_dir().join(id)
+}
+
+/// Path for a new screenshot of item `id`, named after its title and the
+/// playback position so frames sort in the order they appear.
+pub fn new_screenshot_path(id: &str, title: &str, position: f64) -> PathBuf {
+    let (extension, _) = FORMATS
+        .get(SETTINGS.mpv_screenshot_format() as usize)
+        .unwrap_or(&FORMATS[0]);
+    let dir = item_dir(id);
+    let stem = file_stem(title, position);
+
+    let mut path = dir.join(format!("{}.{}", stem, extension));
+    let mut n = 1;
+    while path.exists() {
+        path = dir.join(format!("{} ({}).{}", stem, n, extension));
+        n += 1;
+    }
+    path
+}
+
+/// Screenshots of item `id`, newest first.
+pub fn list(id: &str) -> Vec<PathBuf> {
+    let Ok(entries) = std::fs::read_dir(item_dir(id)) else {
+        return Vec::new();
+    };
+
+    let mut files: Vec<_> = entries
+        .flatten()
+        .filter(|entry| content_type(&entry.path()).is_some())
+        .map(|entry| {
+            let modified = entry.metadata().and_then(|m| m.modified()).ok();
+            (modified, entry.path())
+        })
+        .collect();
+    files.sort_by(|a, b| b.cmp(a));
+    files.into_iter().map(|(_, path)| path).collect()
+}
+
+pub fn content_type(path: &Path) -> Option<&'static str> {
+    let extension = path.extension()?.to_str()?.to_lowercase();
+    FORMATS
+        .iter()
+        .find(|(ext, _)| *ext == extension || (*ext == "jpg" && extension == "jpeg"))
+        .map(|(_, content_type)| *content_type)
+}
+
+/// `title` made safe for a file name, followed by `position` as `HH-MM-SS`.
+pub fn file_stem(title: &str, position: f64) -> String {
+    let title: String = title
+        .chars()
+        .map(|c| match c {
+            '/' | '\\' | ':' | '*' | '?' | '"' | '<' | '>' | '|' => '_',
+            c if c.is_control() => '_',
+            c => c,
+        })
+        .collect();
+    let title = title.trim();
+    let title = if title.is_empty() {
+        "Screenshot"
+    } else {
+        title
+    };
+
+    let position = position.max(0.0) as u64;
+    format!(
+        "{} {:02}-{:02}-{:02}",
+        title,
+        position / 3600,
+        position / 60 % 60,
+        position % 60
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_file_stem() {
+        assert_eq!(file_stem("Pilot", 3723.4), "Pilot 01-02-03");
+        assert_eq!(file_stem("A/B: C?", 5.0), "A_B_ C_ 00-00-05");
+        assert_eq!(file_stem("  ", -1.0), "Screenshot 00-00-00");
+    }
+
+    #[test]
+    fn test_content_type() {
+        assert_eq!(content_type(Path::new("a.PNG")), Some("image/png"));
+        assert_eq!(content_type(Path::new("a.jpeg")), Some("image/jpeg"));
+        assert_eq!(content_type(Path::new("a.txt")), None);
+        assert_eq!(content_type(Path::new("a")), None);
+    }
+}
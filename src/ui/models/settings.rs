@@ -1,4 +1,7 @@
-use std::ops::Deref;
+use std::{
+    collections::BTreeMap,
+    ops::Deref,
+};
 
 use gtk::{
     gio,
@@ -9,6 +12,15 @@ use gtk::{
     prelude::*,
 };
 
+use super::{
+    equalizer::{
+        self,
+        Gains,
+    },
+    languages::legacy_language_code,
+    resume::ResumeDefaults,
+    shader_profiles::ShaderProfiles,
+};
 use crate::{
     client::Account,
     ui::provider::descriptor::{
@@ -36,6 +48,16 @@ impl Settings {
     const KEY_ACCENT_COLOR_CODE: &'static str = "accent-color-code";
     const KEY_ACCENT_FG_COLOR_CODE: &'static str = "accent-fg-color-code";
     const KEY_MUSIC_REPEAT_MODE: &'static str = "music-repeat-mode";
+    const KEY_MUSIC_SHUFFLE: &'static str = "music-shuffle"; // bool
+    const KEY_MUSIC_REPLAYGAIN: &'static str = "music-replaygain"; // i32
+    const KEY_MUSIC_REPLAYGAIN_PREAMP: &'static str = "music-replaygain-preamp"; // f64
+    const KEY_MUSIC_REPLAYGAIN_PREVENT_CLIPPING: &'static str = "music-replaygain-prevent-clipping"; // bool
+    const KEY_MUSIC_CROSSFADE: &'static str = "music-crossfade"; // i32
+    const KEY_MUSIC_EQUALIZER: &'static str = "music-equalizer"; // Vec<f64>
+    const KEY_MUSIC_AUDIO_OUTPUT: &'static str = "music-audio-output"; // String
+    const KEY_AUDIOBOOK_SPEED: &'static str = "audiobook-speed"; // f64
+    const KEY_MINI_PLAYER_WIDTH: &'static str = "mini-player-width"; // i32
+    const KEY_MINI_PLAYER_HEIGHT: &'static str = "mini-player-height"; // i32
     const KEY_MPV_SEEK_FORWARD_STEP: &'static str = "mpv-seek-forward-step";
     const KEY_MPV_SEEK_BACKWARD_STEP: &'static str = "mpv-seek-backward-step";
     const KEY_MPV_CONFIG: &'static str = "mpv-config";
@@ -55,11 +77,28 @@ impl Settings {
     const KEY_MPV_AUDIO_CHANNEL: &'static str = "mpv-audio-channel"; // i32
     const KEY_MPV_SUBTITLE_SCALE: &'static str = "mpv-subtitle-scale"; // f64
     const KEY_MPV_VIDEO_SCALE: &'static str = "mpv-video-scale"; // i32
+    const KEY_MPV_DEBAND: &'static str = "mpv-deband"; // bool
     const KEY_MPV_CONFIG_DIR: &'static str = "mpv-config-path"; // String
     const KEY_POST_SCALE: &'static str = "post-scale"; // f64
     const KEY_IS_REFRESH: &'static str = "is-refresh"; // bool
     const KEY_DEVICE_UUID: &'static str = "device-uuid"; // String
     const KEY_MAIN_THEME: &'static str = "main-theme"; // i32
+    const KEY_MPV_AUDIO_PREFERRED_LANGS: &'static str = "mpv-audio-preferred-langs"; // String
+    const KEY_MPV_SUBTITLE_PREFERRED_LANGS: &'static str = "mpv-subtitle-preferred-langs"; // String
+    const KEY_MPV_SUBTITLE_PREFER_FORCED: &'static str = "mpv-subtitle-prefer-forced"; // bool
+    const KEY_MPV_SUBTITLE_PREFER_SDH: &'static str = "mpv-subtitle-prefer-sdh"; // bool
+    const KEY_KEY_BINDINGS: &'static str = "key-bindings"; // String
+    const KEY_MPV_SCREENSHOT_SUBTITLES: &'static str = "mpv-screenshot-subtitles"; // bool
+    const KEY_MPV_SCREENSHOT_FORMAT: &'static str = "mpv-screenshot-format"; // i32
+    const KEY_MPV_SCREENSHOT_DIR: &'static str = "mpv-screenshot-dir"; // String
+    const KEY_MPV_AB_LOOP_COUNT: &'static str = "mpv-ab-loop-count"; // i32
+    const KEY_MPV_SHADER_PROFILES: &'static str = "mpv-shader-profiles"; // String
+    const KEY_MPV_AUDIO_NORMALIZATION: &'static str = "mpv-audio-normalization"; // i32
+    const KEY_MPV_DIALOGUE_BOOST: &'static str = "mpv-dialogue-boost"; // bool
+    const KEY_MPV_AUDIO_PITCH_CORRECTION: &'static str = "mpv-audio-pitch-correction"; // bool
+    const KEY_MPV_EQUALIZER: &'static str = "mpv-equalizer"; // Vec<f64>
+    const KEY_MPV_RESUME_BEHAVIOR: &'static str = "mpv-resume-behavior"; // i32
+    const KEY_MPV_RESUME_DEFAULTS: &'static str = "mpv-resume-defaults"; // String
 
     pub fn main_theme(&self) -> i32 {
         self.int(Self::KEY_MAIN_THEME)
@@ -97,6 +136,10 @@ impl Settings {
         self.int(Self::KEY_MPV_VIDEO_SCALE)
     }
 
+    pub fn mpv_deband(&self) -> bool {
+        self.boolean(Self::KEY_MPV_DEBAND)
+    }
+
     pub fn mpv_audio_channel(&self) -> i32 {
         self.int(Self::KEY_MPV_AUDIO_CHANNEL)
     }
@@ -221,12 +264,151 @@ impl Settings {
         self.string(Self::KEY_MPV_SUBTITLE_FONT).to_string()
     }
 
-    pub fn mpv_audio_preferred_lang(&self) -> i32 {
-        self.int(Self::KEY_MPV_AUDIO_PREFERRED_LANG)
+    pub fn mpv_audio_preferred_langs(&self) -> Vec<String> {
+        self.preferred_langs(
+            Self::KEY_MPV_AUDIO_PREFERRED_LANGS,
+            Self::KEY_MPV_AUDIO_PREFERRED_LANG,
+        )
+    }
+
+    pub fn set_mpv_audio_preferred_langs(&self, langs: Vec<String>) -> Result<(), glib::BoolError> {
+        self.set_preferred_langs(
+            Self::KEY_MPV_AUDIO_PREFERRED_LANGS,
+            Self::KEY_MPV_AUDIO_PREFERRED_LANG,
+            langs,
+        )
+    }
+
+    pub fn mpv_subtitle_preferred_langs(&self) -> Vec<String> {
+        self.preferred_langs(
+            Self::KEY_MPV_SUBTITLE_PREFERRED_LANGS,
+            Self::KEY_MPV_SUBTITLE_PREFERRED_LANG,
+        )
+    }
+
+    pub fn set_mpv_subtitle_preferred_langs(
+        &self, langs: Vec<String>,
+    ) -> Result<(), glib::BoolError> {
+        self.set_preferred_langs(
+            Self::KEY_MPV_SUBTITLE_PREFERRED_LANGS,
+            Self::KEY_MPV_SUBTITLE_PREFERRED_LANG,
+            langs,
+        )
+    }
+
+    // Falls back to the old single-choice setting until the list is edited once
+    fn preferred_langs(&self, key: &str, legacy_key: &str) -> Vec<String> {
+        let langs: Vec<String> =
+            serde_json::from_str(self.string(key).as_ref()).unwrap_or_default();
+        if !langs.is_empty() {
+            return langs;
+        }
+        legacy_language_code(self.int(legacy_key))
+            .map(|code| vec![code.to_string()])
+            .unwrap_or(langs)
+    }
+
+    fn set_preferred_langs(
+        &self, key: &str, legacy_key: &str, langs: Vec<String>,
+    ) -> Result<(), glib::BoolError> {
+        self.set_int(legacy_key, 0)?;
+        self.set_string(
+            key,
+            &serde_json::to_string(&langs).expect("Failed to serialize languages"),
+        )
+    }
+
+    pub fn mpv_subtitle_prefer_forced(&self) -> bool {
+        self.boolean(Self::KEY_MPV_SUBTITLE_PREFER_FORCED)
+    }
+
+    pub fn mpv_subtitle_prefer_sdh(&self) -> bool {
+        self.boolean(Self::KEY_MPV_SUBTITLE_PREFER_SDH)
+    }
+
+    pub fn key_bindings(&self) -> BTreeMap<String, Vec<String>> {
+        serde_json::from_str(self.string(Self::KEY_KEY_BINDINGS).as_ref()).unwrap_or_default()
+    }
+
+    pub fn set_key_bindings(
+        &self, bindings: &BTreeMap<String, Vec<String>>,
+    ) -> Result<(), glib::BoolError> {
+        self.set_string(
+            Self::KEY_KEY_BINDINGS,
+            &serde_json::to_string(bindings).expect("Failed to serialize key bindings"),
+        )
+    }
+
+    pub fn mpv_screenshot_subtitles(&self) -> bool {
+        self.boolean(Self::KEY_MPV_SCREENSHOT_SUBTITLES)
+    }
+
+    pub fn mpv_screenshot_format(&self) -> i32 {
+        self.int(Self::KEY_MPV_SCREENSHOT_FORMAT)
+    }
+
+    pub fn mpv_screenshot_dir(&self) -> String {
+        self.string(Self::KEY_MPV_SCREENSHOT_DIR).to_string()
+    }
+
+    pub fn set_mpv_screenshot_dir(&self, dir: &str) -> Result<(), glib::BoolError> {
+        self.set_string(Self::KEY_MPV_SCREENSHOT_DIR, dir)
+    }
+
+    pub fn mpv_ab_loop_count(&self) -> i32 {
+        self.int(Self::KEY_MPV_AB_LOOP_COUNT)
+    }
+
+    pub fn mpv_shader_profiles(&self) -> ShaderProfiles {
+        serde_json::from_str(self.string(Self::KEY_MPV_SHADER_PROFILES).as_ref())
+            .unwrap_or_default()
+    }
+
+    pub fn set_mpv_shader_profiles(
+        &self, profiles: &ShaderProfiles,
+    ) -> Result<(), glib::BoolError> {
+        self.set_string(
+            Self::KEY_MPV_SHADER_PROFILES,
+            &serde_json::to_string(profiles).expect("Failed to serialize shader profiles"),
+        )
+    }
+
+    pub fn mpv_audio_normalization(&self) -> i32 {
+        self.int(Self::KEY_MPV_AUDIO_NORMALIZATION)
+    }
+
+    pub fn mpv_dialogue_boost(&self) -> bool {
+        self.boolean(Self::KEY_MPV_DIALOGUE_BOOST)
+    }
+
+    pub fn mpv_audio_pitch_correction(&self) -> bool {
+        self.boolean(Self::KEY_MPV_AUDIO_PITCH_CORRECTION)
+    }
+
+    pub fn mpv_equalizer(&self) -> Gains {
+        equalizer::gains(&self.get::<Vec<f64>>(Self::KEY_MPV_EQUALIZER))
+    }
+
+    pub fn set_mpv_equalizer(&self, gains: &Gains) -> Result<(), glib::BoolError> {
+        self.set_value(Self::KEY_MPV_EQUALIZER, &gains[..].to_variant())
+    }
+
+    pub fn mpv_resume_behavior(&self) -> i32 {
+        self.int(Self::KEY_MPV_RESUME_BEHAVIOR)
     }
 
-    pub fn mpv_subtitle_preferred_lang(&self) -> i32 {
-        self.int(Self::KEY_MPV_SUBTITLE_PREFERRED_LANG)
+    pub fn mpv_resume_defaults(&self) -> ResumeDefaults {
+        serde_json::from_str(self.string(Self::KEY_MPV_RESUME_DEFAULTS).as_ref())
+            .unwrap_or_default()
+    }
+
+    pub fn set_mpv_resume_defaults(
+        &self, defaults: &ResumeDefaults,
+    ) -> Result<(), glib::BoolError> {
+        self.set_string(
+            Self::KEY_MPV_RESUME_DEFAULTS,
+            &serde_json::to_string(defaults).expect("Failed to serialize resume defaults"),
+        )
     }
 
     pub fn mpv_default_volume(&self) -> i32 {
@@ -292,6 +474,70 @@ impl Settings {
         self.string(Self::KEY_MUSIC_REPEAT_MODE).to_string()
     }
 
+    pub fn set_music_shuffle(&self, music_shuffle: bool) -> Result<(), glib::BoolError> {
+        self.set_boolean(Self::KEY_MUSIC_SHUFFLE, music_shuffle)
+    }
+
+    pub fn music_shuffle(&self) -> bool {
+        self.boolean(Self::KEY_MUSIC_SHUFFLE)
+    }
+
+    pub fn music_replaygain(&self) -> i32 {
+        self.int(Self::KEY_MUSIC_REPLAYGAIN)
+    }
+
+    pub fn music_replaygain_preamp(&self) -> f64 {
+        self.double(Self::KEY_MUSIC_REPLAYGAIN_PREAMP)
+    }
+
+    pub fn music_replaygain_prevent_clipping(&self) -> bool {
+        self.boolean(Self::KEY_MUSIC_REPLAYGAIN_PREVENT_CLIPPING)
+    }
+
+    pub fn music_crossfade(&self) -> i32 {
+        self.int(Self::KEY_MUSIC_CROSSFADE)
+    }
+
+    pub fn music_equalizer(&self) -> Gains {
+        equalizer::gains(&self.get::<Vec<f64>>(Self::KEY_MUSIC_EQUALIZER))
+    }
+
+    pub fn set_music_equalizer(&self, gains: &Gains) -> Result<(), glib::BoolError> {
+        self.set_value(Self::KEY_MUSIC_EQUALIZER, &gains[..].to_variant())
+    }
+
+    pub fn music_audio_output(&self) -> String {
+        self.string(Self::KEY_MUSIC_AUDIO_OUTPUT).to_string()
+    }
+
+    pub fn set_music_audio_output(&self, music_audio_output: &str) -> Result<(), glib::BoolError> {
+        self.set_string(Self::KEY_MUSIC_AUDIO_OUTPUT, music_audio_output)
+    }
+
+    pub fn audiobook_speed(&self) -> f64 {
+        self.double(Self::KEY_AUDIOBOOK_SPEED)
+    }
+
+    pub fn set_audiobook_speed(&self, audiobook_speed: f64) -> Result<(), glib::BoolError> {
+        self.set_double(Self::KEY_AUDIOBOOK_SPEED, audiobook_speed)
+    }
+
+    pub fn mini_player_width(&self) -> i32 {
+        self.int(Self::KEY_MINI_PLAYER_WIDTH)
+    }
+
+    pub fn set_mini_player_width(&self, mini_player_width: i32) -> Result<(), glib::BoolError> {
+        self.set_int(Self::KEY_MINI_PLAYER_WIDTH, mini_player_width)
+    }
+
+    pub fn mini_player_height(&self) -> i32 {
+        self.int(Self::KEY_MINI_PLAYER_HEIGHT)
+    }
+
+    pub fn set_mini_player_height(&self, mini_player_height: i32) -> Result<(), glib::BoolError> {
+        self.set_int(Self::KEY_MINI_PLAYER_HEIGHT, mini_player_height)
+    }
+
     pub fn set_accent_fg_color_code(
         &self, accent_fg_color_code: &str,
     ) -> Result<(), glib::BoolError> {
@@ -0,0 +1,261 @@
+use std::{
+    collections::BTreeMap,
+    path::{
+        Path,
+        PathBuf,
+    },
+};
+
+use gtk::glib;
+use serde::{
+    Deserialize,
+    Serialize,
+};
+
+use super::SETTINGS;
+
+const SHADER_EXTENSIONS: &[&str] = &["glsl", "hook"];
+
+/// Collection types a default profile can be assigned to, besides single
+/// libraries.
+pub const COLLECTION_TYPES: &[&str] = &["movies", "tvshows", "homevideos", "musicvideos", "mixed"];
+
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq)]
+pub struct Deband {
+    pub iterations: i64,
+    pub threshold: i64,
+    pub range: i64,
+    pub grain: i64,
+}
+
+impl Default for Deband {
+    // mpv's own defaults
+    fn default() -> Self {
+        Self {
+            iterations: 1,
+            threshold: 48,
+            range: 16,
+            grain: 32,
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, Default, PartialEq)]
+pub struct ShaderProfile {
+    pub name: String,
+    /// Shader files relative to `shaders_dir`, applied in order.
+    #[serde(default)]
+    pub shaders: Vec<String>,
+    /// Index understood by `match_video_upscale`.
+    #[serde(default)]
+    pub scale: i32,
+    #[serde(default)]
+    pub deband: Option<Deband>,
+}
+
+impl ShaderProfile {
+    /// Value for mpv's `glsl-shaders`, shaders that were removed since the
+    /// profile was made are skipped.
+    pub fn glsl_shaders(&self) -> String {
+        let dir = shaders_dir();
+        let paths: Vec<PathBuf> = self
+            .shaders
+            .iter()
+            .map(|shader| dir.join(shader))
+            .filter(|path| path.exists())
+            .collect();
+        std::env::join_paths(paths)
+            .map(|paths| paths.to_string_lossy().to_string())
+            .unwrap_or_default()
+    }
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, Default, PartialEq)]
+pub struct ShaderProfiles {
+    #[serde(default)]
+    pub profiles: Vec<ShaderProfile>,
+    /// Profile name by library id or collection type.
+    #[serde(default)]
+    pub defaults: BTreeMap<String, String>,
+}
+
+impl ShaderProfiles {
+    pub fn load() -> Self {
+        SETTINGS.mpv_shader_profiles()
+    }
+
+    pub fn save(&self) {
+        if let Err(e) = SETTINGS.set_mpv_shader_profiles(self) {
+            tracing::warn!("Failed to save shader profiles: {}", e);
+        }
+    }
+
+    pub fn get(&self, name: &str) -> Option<&ShaderProfile> {
+        self.profiles.iter().find(|profile| profile.name == name)
+    }
+
+    pub fn names(&self) -> Vec<String> {
+        self.profiles
+            .iter()
+            .map(|profile| profile.name.clone())
+            .collect()
+    }
+
+    /// Insert or replace the profile called `old_name`, keeping its place and
+    /// the defaults pointing at it.
+    pub fn upsert(&mut self, old_name: Option<&str>, profile: ShaderProfile) {
+        let position = old_name.and_then(|old_name| {
+            self.profiles
+                .iter()
+                .position(|profile| profile.name == old_name)
+        });
+        if let Some(old_name) = old_name {
+            for name in self.defaults.values_mut() {
+                if name == old_name {
+                    name.clone_from(&profile.name);
+                }
+            }
+        }
+        match position {
+            Some(position) => self.profiles[position] = profile,
+            None => self.profiles.push(profile),
+        }
+    }
+
+    pub fn remove(&mut self, name: &str) {
+        self.profiles.retain(|profile| profile.name != name);
+        self.defaults.retain(|_, profile| profile != name);
+    }
+
+    pub fn set_default(&mut self, key: &str, name: Option<&str>) {
+        match name {
+            Some(name) => self.defaults.insert(key.to_string(), name.to_string()),
+            None => self.defaults.remove(key),
+        };
+    }
+
+    /// Default profile for an item in library `library_id`, a library's own
+    /// assignment wins over the one of its collection type.
+    pub fn default_for(
+        &self, library_id: Option<&str>, collection_type: Option<&str>,
+    ) -> Option<&ShaderProfile> {
+        library_id
+            .and_then(|id| self.defaults.get(id))
+            .or_else(|| collection_type.and_then(|kind| self.defaults.get(kind)))
+            .and_then(|name| self.get(name))
+    }
+}
+
+pub fn shaders_dir() -> PathBuf {
+    glib::user_config_dir().join("tsukimi").join("shaders")
+}
+
+fn is_shader(path: &Path) -> bool {
+    path.extension()
+        .and_then(|extension| extension.to_str())
+        .is_some_and(|extension| SHADER_EXTENSIONS.contains(&extension.to_lowercase().as_str()))
+}
+
+/// Installed shaders relative to `shaders_dir`, packs are one folder deep.
+pub fn installed_shaders() -> Vec<String> {
+    let dir = shaders_dir();
+    let mut shaders = Vec::new();
+    let Ok(entries) = std::fs::read_dir(&dir) else {
+        return shaders;
+    };
+    for path in entries.flatten().map(|entry| entry.path()) {
+        if path.is_dir() {
+            let Ok(pack) = std::fs::read_dir(&path) else {
+                continue;
+            };
+            shaders.extend(
+                pack.flatten()
+                    .map(|entry| entry.path())
+                    .filter(|path| is_shader(path)),
+            );
+        } else if is_shader(&path) {
+            shaders.push(path);
+        }
+    }
+    let mut shaders: Vec<String> = shaders
+        .iter()
+        .filter_map(|path| path.strip_prefix(&dir).ok())
+        .map(|path| path.to_string_lossy().replace('\\', "/"))
+        .collect();
+    shaders.sort();
+    shaders
+}
+
+/// Copy every shader in `source` into a pack folder of the same name.
+/// Returns how many shaders were imported.
+pub fn import_pack(source: &Path) -> std::io::Result<usize> {
+    let name = source
+        .file_name()
+        .ok_or_else(|| std::io::Error::from(std::io::ErrorKind::InvalidInput))?;
+    let target = shaders_dir().join(name);
+    std::fs::create_dir_all(&target)?;
+
+    let mut count = 0;
+    for path in std::fs::read_dir(source)?
+        .flatten()
+        .map(|entry| entry.path())
+        .filter(|path| is_shader(path))
+    {
+        if let Some(file_name) = path.file_name() {
+            std::fs::copy(&path, target.join(file_name))?;
+            count += 1;
+        }
+    }
+    Ok(count)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn profile(name: &str) -> ShaderProfile {
+        ShaderProfile {
+            name: name.to_string(),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_default_for() {
+        let mut profiles = ShaderProfiles::default();
+        profiles.upsert(None, profile("Anime"));
+        profiles.upsert(None, profile("Film"));
+        profiles.set_default("tvshows", Some("Anime"));
+        profiles.set_default("lib1", Some("Film"));
+
+        let name = |library: Option<&str>, kind: Option<&str>| {
+            profiles
+                .default_for(library, kind)
+                .map(|profile| profile.name.clone())
+        };
+        assert_eq!(
+            name(Some("lib1"), Some("tvshows")),
+            Some("Film".to_string())
+        );
+        assert_eq!(
+            name(Some("lib2"), Some("tvshows")),
+            Some("Anime".to_string())
+        );
+        assert_eq!(name(None, Some("movies")), None);
+    }
+
+    #[test]
+    fn test_rename_and_remove() {
+        let mut profiles = ShaderProfiles::default();
+        profiles.upsert(None, profile("Anime"));
+        profiles.set_default("tvshows", Some("Anime"));
+
+        profiles.upsert(Some("Anime"), profile("Anime4K"));
+        assert_eq!(profiles.names(), vec!["Anime4K"]);
+        assert_eq!(profiles.defaults.get("tvshows").unwrap(), "Anime4K");
+
+        profiles.remove("Anime4K");
+        assert!(profiles.profiles.is_empty());
+        assert!(profiles.defaults.is_empty());
+    }
+}
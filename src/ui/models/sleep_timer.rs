@@ -0,0 +1,191 @@
+use std::{
+    cell::{
+        Cell,
+        RefCell,
+    },
+    time::{
+        Duration,
+        Instant,
+    },
+};
+
+use gtk::glib;
+
+/// Seconds the volume takes to fade out before playback pauses.
+pub const FADE_SECONDS: f64 = 30.0;
+/// Timers are checked every second, expire a little early so the end of an
+/// item is never missed.
+const EXPIRE_MARGIN: f64 = 1.5;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum SleepMode {
+    Minutes(u32),
+    /// When the current episode or track ends.
+    EndOfItem,
+    /// When the last track of the current album ends.
+    EndOfAlbum,
+}
+
+impl SleepMode {
+    /// Parses the target of the sleep timer actions: minutes, `0` for the end
+    /// of the item and `-1` for the end of the album.
+    pub fn from_target(target: i32) -> Option<Self> {
+        match target {
+            -1 => Some(Self::EndOfAlbum),
+            0 => Some(Self::EndOfItem),
+            minutes if minutes > 0 => Some(Self::Minutes(minutes as u32)),
+            _ => None,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum SleepStep {
+    /// Keep playing at this fraction of the volume.
+    Play(f64),
+    Expire,
+}
+
+#[derive(Debug, Clone, Copy)]
+pub struct SleepTimer {
+    mode: SleepMode,
+    deadline: Option<Instant>,
+}
+
+impl SleepTimer {
+    pub fn new(mode: SleepMode) -> Self {
+        let deadline = match mode {
+            SleepMode::Minutes(minutes) => {
+                Some(Instant::now() + Duration::from_secs(minutes as u64 * 60))
+            }
+            _ => None,
+        };
+        Self { mode, deadline }
+    }
+
+    pub fn mode(&self) -> SleepMode {
+        self.mode
+    }
+
+    /// Seconds left, `until_end` is what the player has left of the item or
+    /// album and is ignored by fixed timers.
+    pub fn remaining(&self, until_end: f64) -> f64 {
+        match self.deadline {
+            Some(deadline) => deadline
+                .saturating_duration_since(Instant::now())
+                .as_secs_f64(),
+            None => until_end.max(0.0),
+        }
+    }
+}
+
+/// A running [`SleepTimer`], for players that differ only in how they reach
+/// the volume and the end of what is playing.
+#[derive(Debug, Default)]
+pub struct SleepState {
+    timer: Cell<Option<SleepTimer>>,
+    timeout: RefCell<Option<glib::SourceId>>,
+    /// Volume before the timer started to fade it out.
+    volume: Cell<Option<f64>>,
+}
+
+impl SleepState {
+    /// Starts a timer, `tick` runs every second and should call
+    /// [`Self::tick`].
+    pub fn start<F>(&self, mode: SleepMode, tick: F)
+    where
+        F: Fn() -> glib::ControlFlow + 'static,
+    {
+        self.timer.set(Some(SleepTimer::new(mode)));
+        if let Some(timeout) = self
+            .timeout
+            .replace(Some(glib::timeout_add_seconds_local(1, tick)))
+        {
+            timeout.remove();
+        }
+    }
+
+    /// Stops the timer, `set_volume` gets back the volume it faded from.
+    pub fn cancel(&self, set_volume: impl FnOnce(f64)) {
+        if let Some(timeout) = self.timeout.take() {
+            timeout.remove();
+        }
+        self.timer.set(None);
+        if let Some(volume) = self.volume.take() {
+            set_volume(volume);
+        }
+    }
+
+    pub fn mode(&self) -> Option<SleepMode> {
+        self.timer.get().map(|timer| timer.mode())
+    }
+
+    /// Volume before the fade, while it is fading.
+    pub fn saved_volume(&self) -> Option<f64> {
+        self.volume.get()
+    }
+
+    /// Moves the timer on and returns the seconds left, `None` once there
+    /// is no timer.
+    ///
+    /// `until_end` is what the player has left of the item or album. Near
+    /// the end the volume fades from `volume` through `set_volume`, and on
+    /// expiring `expire` stops playback before the volume is restored.
+    pub fn tick(
+        &self, until_end: impl FnOnce(SleepMode) -> f64, volume: impl FnOnce() -> f64,
+        set_volume: impl FnOnce(f64), expire: impl FnOnce(),
+    ) -> Option<f64> {
+        let timer = self.timer.get()?;
+        let remaining = timer.remaining(until_end(timer.mode()));
+
+        match step(remaining) {
+            SleepStep::Play(level) => {
+                if level < 1.0 {
+                    let volume = self.volume.get().unwrap_or_else(volume);
+                    self.volume.set(Some(volume));
+                    set_volume(volume * level);
+                }
+                Some(remaining)
+            }
+            SleepStep::Expire => {
+                // The tick returning ends the timeout
+                self.timeout.take();
+                self.timer.set(None);
+                expire();
+                if let Some(volume) = self.volume.take() {
+                    set_volume(volume);
+                }
+                None
+            }
+        }
+    }
+}
+
+pub fn step(remaining: f64) -> SleepStep {
+    if remaining <= EXPIRE_MARGIN {
+        SleepStep::Expire
+    } else {
+        SleepStep::Play(((remaining - EXPIRE_MARGIN) / FADE_SECONDS).min(1.0))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_step() {
+        assert_eq!(SleepMode::from_target(15), Some(SleepMode::Minutes(15)));
+        assert_eq!(SleepMode::from_target(0), Some(SleepMode::EndOfItem));
+        assert_eq!(SleepMode::from_target(-2), None);
+
+        assert_eq!(step(600.0), SleepStep::Play(1.0));
+        assert_eq!(step(16.5), SleepStep::Play(0.5));
+        assert_eq!(step(1.0), SleepStep::Expire);
+
+        let timer = SleepTimer::new(SleepMode::EndOfItem);
+        assert_eq!(timer.remaining(42.0), 42.0);
+        let timer = SleepTimer::new(SleepMode::Minutes(15));
+        assert!(timer.remaining(42.0) > 899.0);
+    }
+}
@@ -0,0 +1,135 @@
+use std::collections::HashMap;
+
+use serde::{
+    Deserialize,
+    Serialize,
+};
+
+use super::emby_cache_path;
+
+const TRACK_MEMORY_FILE: &str = "track-memory.json";
+
+/// Audio/subtitle choices remembered for a single item or a whole series.
+#[derive(Serialize, Deserialize, Default, PartialEq, Clone, Debug)]
+pub struct TrackChoice {
+    #[serde(default)]
+    pub audio_lang: Option<String>,
+    #[serde(default)]
+    pub sub_lang: Option<String>,
+    #[serde(default)]
+    pub sub_title: Option<String>,
+    #[serde(default)]
+    pub sub_off: bool,
+    #[serde(default)]
+    pub secondary_sub_lang: Option<String>,
+    #[serde(default)]
+    pub secondary_sub_title: Option<String>,
+    /// Local subtitle files added to this item, not shared with the series.
+    #[serde(default)]
+    pub local_subs: Vec<String>,
+    #[serde(default)]
+    pub sub_delay: Option<f64>,
+    #[serde(default)]
+    pub speed: Option<f64>,
+    #[serde(default)]
+    pub series_id: Option<String>,
+}
+
+impl TrackChoice {
+    pub fn is_empty(&self) -> bool {
+        self.audio_lang.is_none()
+            && self.sub_lang.is_none()
+            && self.sub_title.is_none()
+            && !self.sub_off
+            && self.secondary_sub_lang.is_none()
+            && self.secondary_sub_title.is_none()
+            && self.local_subs.is_empty()
+            && self.sub_delay.is_none()
+            && self.speed.is_none()
+    }
+
+    /// Whether the remembered subtitle is one of the local files rather than
+    /// a stream provided by the server.
+    pub fn local_sub_selected(&self) -> bool {
+        self.sub_title.as_ref().is_some_and(|title| {
+            self.local_subs
+                .iter()
+                .any(|path| &local_sub_title(path) == title)
+        })
+    }
+}
+
+/// Title a local subtitle file is added to mpv with.
+pub fn local_sub_title(path: &str) -> String {
+    std::path::Path::new(path)
+        .file_name()
+        .map(|name| name.to_string_lossy().to_string())
+        .unwrap_or_else(|| path.to_string())
+}
+
+pub struct TrackMemory;
+
+impl TrackMemory {
+    fn load() -> HashMap<String, TrackChoice> {
+        std::fs::read_to_string(emby_cache_path().join(TRACK_MEMORY_FILE))
+            .ok()
+            .and_then(|contents| serde_json::from_str(&contents).ok())
+            .unwrap_or_default()
+    }
+
+    fn store(memory: &HashMap<String, TrackChoice>) {
+        let path = emby_cache_path().join(TRACK_MEMORY_FILE);
+        match serde_json::to_string(memory) {
+            Ok(serialized) => {
+                if let Err(e) = std::fs::write(path, serialized) {
+                    tracing::warn!("Failed to write track memory: {}", e);
+                }
+            }
+            Err(e) => tracing::warn!("Failed to serialize track memory: {}", e),
+        }
+    }
+
+    /// The item's own choice wins over the one remembered for its series.
+    pub fn get(id: &str, series_id: Option<&str>) -> Option<TrackChoice> {
+        let memory = Self::load();
+        memory
+            .get(id)
+            .or_else(|| series_id.and_then(|series_id| memory.get(series_id)))
+            .cloned()
+    }
+
+    /// Remember `choice` for the item, and for its series so that the next
+    /// episode starts with the same tracks.
+    pub fn set(id: &str, series_id: Option<&str>, choice: TrackChoice) {
+        let mut memory = Self::load();
+        let mut choice = choice;
+        choice.series_id = series_id.map(|s| s.to_string());
+        if let Some(series_id) = series_id {
+            let mut series_choice = choice.clone();
+            series_choice.series_id = None;
+            series_choice.local_subs.clear();
+            memory.insert(series_id.to_string(), series_choice);
+        }
+        memory.insert(id.to_string(), choice);
+        Self::store(&memory);
+    }
+
+    /// Replace the entry of `id` only, used by the item page editor.
+    pub fn replace(id: &str, choice: TrackChoice) {
+        let mut memory = Self::load();
+        if choice.is_empty() {
+            memory.remove(id);
+        } else {
+            memory.insert(id.to_string(), choice);
+        }
+        Self::store(&memory);
+    }
+
+    /// Forget the entry of `id`, and of every episode belonging to it when
+    /// `id` is a series.
+    pub fn clear(id: &str) {
+        let mut memory = Self::load();
+        memory.retain(|key, choice| key != id && choice.series_id.as_deref() != Some(id));
+        Self::store(&memory);
+    }
+}
@@ -0,0 +1,731 @@
+use std::{
+    cell::RefCell,
+    rc::Rc,
+};
+
+use gtk::{
+    gio,
+    glib,
+    prelude::*,
+};
+use tracing::{
+    debug,
+    warn,
+};
+
+const BUS_NAME: &str = "org.mpris.MediaPlayer2.tsukimi";
+const OBJECT_PATH: &str = "/org/mpris/MediaPlayer2";
+const ROOT_INTERFACE: &str = "org.mpris.MediaPlayer2";
+const PLAYER_INTERFACE: &str = "org.mpris.MediaPlayer2.Player";
+const TRACK_PATH: &str = "/moe/tsuna/tsukimi/Track";
+const NO_TRACK: &str = "/org/mpris/MediaPlayer2/TrackList/NoTrack";
+
+/// Properties sent when the active player changes. `Position` is left out on
+/// purpose, clients poll it and listen to `Seeked`.
+const PLAYER_PROPERTIES: &[&str] = &[
+    "PlaybackStatus",
+    "LoopStatus",
+    "Rate",
+    "MinimumRate",
+    "MaximumRate",
+    "Shuffle",
+    "Metadata",
+    "Volume",
+    "CanGoNext",
+    "CanGoPrevious",
+    "CanPlay",
+    "CanPause",
+    "CanSeek",
+];
+
+const INTROSPECTION_XML: &str = r#"
+<node>
+  <interface name="org.mpris.MediaPlayer2">
+    <method name="Raise"/>
+    <method name="Quit"/>
+    <property name="CanQuit" type="b" access="read"/>
+    <property name="CanRaise" type="b" access="read"/>
+    <property name="HasTrackList" type="b" access="read"/>
+    <property name="Identity" type="s" access="read"/>
+    <property name="DesktopEntry" type="s" access="read"/>
+    <property name="SupportedUriSchemes" type="as" access="read"/>
+    <property name="SupportedMimeTypes" type="as" access="read"/>
+  </interface>
+  <interface name="org.mpris.MediaPlayer2.Player">
+    <method name="Next"/>
+    <method name="Previous"/>
+    <method name="Pause"/>
+    <method name="PlayPause"/>
+    <method name="Stop"/>
+    <method name="Play"/>
+    <method name="Seek">
+      <arg direction="in" name="Offset" type="x"/>
+    </method>
+    <method name="SetPosition">
+      <arg direction="in" name="TrackId" type="o"/>
+      <arg direction="in" name="Position" type="x"/>
+    </method>
+    <method name="OpenUri">
+      <arg direction="in" name="Uri" type="s"/>
+    </method>
+    <signal name="Seeked">
+      <arg name="Position" type="x"/>
+    </signal>
+    <property name="PlaybackStatus" type="s" access="read"/>
+    <property name="LoopStatus" type="s" access="readwrite"/>
+    <property name="Rate" type="d" access="readwrite"/>
+    <property name="Shuffle" type="b" access="readwrite"/>
+    <property name="Metadata" type="a{sv}" access="read"/>
+    <property name="Volume" type="d" access="readwrite"/>
+    <property name="Position" type="x" access="read"/>
+    <property name="MinimumRate" type="d" access="read"/>
+    <property name="MaximumRate" type="d" access="read"/>
+    <property name="CanGoNext" type="b" access="read"/>
+    <property name="CanGoPrevious" type="b" access="read"/>
+    <property name="CanPlay" type="b" access="read"/>
+    <property name="CanPause" type="b" access="read"/>
+    <property name="CanSeek" type="b" access="read"/>
+    <property name="CanControl" type="b" access="read"/>
+  </interface>
+</node>
+"#;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PlaybackStatus {
+    Playing,
+    Paused,
+    Stopped,
+}
+
+impl PlaybackStatus {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            PlaybackStatus::Playing => "Playing",
+            PlaybackStatus::Paused => "Paused",
+            PlaybackStatus::Stopped => "Stopped",
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum LoopStatus {
+    None,
+    Track,
+    Playlist,
+}
+
+impl LoopStatus {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            LoopStatus::None => "None",
+            LoopStatus::Track => "Track",
+            LoopStatus::Playlist => "Playlist",
+        }
+    }
+
+    pub fn from_string(s: &str) -> Option<Self> {
+        match s {
+            "None" => Some(LoopStatus::None),
+            "Track" => Some(LoopStatus::Track),
+            "Playlist" => Some(LoopStatus::Playlist),
+            _ => None,
+        }
+    }
+}
+
+/// What the desktop shows for the current track. Lengths are in microseconds
+/// like everything else on the MPRIS interface.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Metadata {
+    pub id: String,
+    pub title: String,
+    pub artists: Vec<String>,
+    pub album: Option<String>,
+    pub series: Option<String>,
+    pub length: Option<i64>,
+    pub art_url: Option<String>,
+}
+
+impl Metadata {
+    pub fn track_id(&self) -> String {
+        if self.id.is_empty() {
+            return NO_TRACK.to_string();
+        }
+        track_id(&self.id)
+    }
+
+    fn to_variant(&self) -> glib::Variant {
+        let dict = glib::VariantDict::new(None);
+        if let Ok(path) = glib::variant::ObjectPath::try_from(self.track_id()) {
+            dict.insert_value("mpris:trackid", &path.to_variant());
+        }
+        if self.id.is_empty() {
+            return dict.end();
+        }
+        dict.insert_value("xesam:title", &self.title.to_variant());
+        if !self.artists.is_empty() {
+            dict.insert_value("xesam:artist", &self.artists.to_variant());
+        }
+        // Desktop widgets only show the album line, so an episode shows its series there.
+        if let Some(album) = self.album.as_ref().or(self.series.as_ref()) {
+            dict.insert_value("xesam:album", &album.to_variant());
+        }
+        if let Some(length) = self.length {
+            dict.insert_value("mpris:length", &length.to_variant());
+        }
+        if let Some(art_url) = &self.art_url {
+            dict.insert_value("mpris:artUrl", &art_url.to_variant());
+        }
+        dict.end()
+    }
+}
+
+/// Emby ids may contain characters that are not allowed in an object path.
+fn track_id(id: &str) -> String {
+    let id: String = id
+        .chars()
+        .map(|c| if c.is_ascii_alphanumeric() { c } else { '_' })
+        .collect();
+    format!("{}/{}", TRACK_PATH, id)
+}
+
+/// `file://` uri of a cached image, as expected by `mpris:artUrl`.
+pub fn art_url(path: &str) -> Option<String> {
+    if path.is_empty() {
+        return None;
+    }
+    Some(gio::File::for_path(path).uri().to_string())
+}
+
+/// A player that can be controlled over MPRIS. Times are in microseconds.
+pub trait MprisPlayer {
+    /// Tells the players apart, so a player that is not active stays quiet.
+    fn name(&self) -> &'static str;
+    fn metadata(&self) -> Metadata;
+    fn playback_status(&self) -> PlaybackStatus;
+    fn position(&self) -> i64;
+    fn volume(&self) -> f64;
+    fn set_volume(&self, volume: f64);
+    fn play(&self);
+    fn pause(&self);
+    fn play_pause(&self);
+    fn stop(&self);
+    fn next(&self);
+    fn previous(&self);
+    fn set_position(&self, position: i64);
+
+    fn seek(&self, offset: i64) {
+        self.set_position((self.position() + offset).max(0));
+    }
+
+    fn rate(&self) -> f64 {
+        1.0
+    }
+
+    fn set_rate(&self, _rate: f64) {}
+
+    fn rate_range(&self) -> (f64, f64) {
+        (1.0, 1.0)
+    }
+
+    fn shuffle(&self) -> bool {
+        false
+    }
+
+    fn set_shuffle(&self, _shuffle: bool) {}
+
+    fn loop_status(&self) -> LoopStatus {
+        LoopStatus::None
+    }
+
+    fn set_loop_status(&self, _status: LoopStatus) {}
+
+    fn can_go_next(&self) -> bool {
+        true
+    }
+
+    fn can_go_previous(&self) -> bool {
+        true
+    }
+}
+
+#[derive(Default)]
+struct MprisInner {
+    connection: RefCell<Option<gio::DBusConnection>>,
+    owner_id: RefCell<Option<gio::OwnerId>>,
+    player: RefCell<Option<Rc<dyn MprisPlayer>>>,
+}
+
+/// `org.mpris.MediaPlayer2` service reflecting whichever player is active.
+#[derive(Default, Clone)]
+pub struct Mpris {
+    inner: Rc<MprisInner>,
+}
+
+thread_local! {
+    static MPRIS: Mpris = Mpris::default();
+}
+
+pub fn mpris() -> Mpris {
+    MPRIS.with(Mpris::clone)
+}
+
+impl Mpris {
+    /// Own the well-known name on the session bus and export the interfaces.
+    pub fn own_name(&self) {
+        if self.inner.owner_id.borrow().is_some() {
+            return;
+        }
+
+        let mpris = self.clone();
+        let owner_id = gio::bus_own_name(
+            gio::BusType::Session,
+            BUS_NAME,
+            gio::BusNameOwnerFlags::NONE,
+            move |connection, _| {
+                if let Err(e) = mpris.register(&connection) {
+                    warn!("Failed to register MPRIS interface: {}", e);
+                }
+            },
+            |_, name| {
+                debug!("Acquired D-Bus name {}", name);
+            },
+            |_, name| {
+                warn!("Lost D-Bus name {}", name);
+            },
+        );
+        self.inner.owner_id.replace(Some(owner_id));
+    }
+
+    /// Export the interfaces on `connection`. Separate from [`Self::own_name`]
+    /// so the service can run on a private bus.
+    pub fn register(&self, connection: &gio::DBusConnection) -> Result<(), glib::Error> {
+        let node = gio::DBusNodeInfo::for_xml(INTROSPECTION_XML)?;
+
+        for interface_name in [ROOT_INTERFACE, PLAYER_INTERFACE] {
+            let Some(interface_info) = node.lookup_interface(interface_name) else {
+                continue;
+            };
+
+            let method_mpris = self.clone();
+            let get_mpris = self.clone();
+            let set_mpris = self.clone();
+            connection
+                .register_object(OBJECT_PATH, &interface_info)
+                .method_call(
+                    move |_, _, _, interface_name, method_name, parameters, invocation| {
+                        method_mpris.method_call(
+                            interface_name,
+                            method_name,
+                            &parameters,
+                            invocation,
+                        );
+                    },
+                )
+                .get_property(move |_, _, _, interface_name, property_name| {
+                    get_mpris.property(interface_name, property_name)
+                })
+                .set_property(move |_, _, _, _, property_name, value| {
+                    set_mpris.set_property(property_name, &value)
+                })
+                .build()?;
+        }
+
+        self.inner.connection.replace(Some(connection.clone()));
+        Ok(())
+    }
+
+    pub fn set_player(&self, player: Rc<dyn MprisPlayer>) {
+        self.inner.player.replace(Some(player));
+        self.emit_changed(PLAYER_PROPERTIES);
+    }
+
+    /// Drop `name` if it is the active player, e.g. when playback stops.
+    pub fn unset_player(&self, name: &str) {
+        if !self.is_active(name) {
+            return;
+        }
+        self.inner.player.replace(None);
+        self.emit_changed(PLAYER_PROPERTIES);
+    }
+
+    pub fn is_active(&self, name: &str) -> bool {
+        self.player().is_some_and(|player| player.name() == name)
+    }
+
+    /// Notify clients that `properties` of player `name` changed.
+    pub fn changed(&self, name: &str, properties: &[&str]) {
+        if self.is_active(name) {
+            self.emit_changed(properties);
+        }
+    }
+
+    /// Notify clients that player `name` jumped to `position`.
+    pub fn seeked(&self, name: &str, position: i64) {
+        if !self.is_active(name) {
+            return;
+        }
+        let Some(connection) = self.connection() else {
+            return;
+        };
+        if let Err(e) = connection.emit_signal(
+            None,
+            OBJECT_PATH,
+            PLAYER_INTERFACE,
+            "Seeked",
+            Some(&(position,).to_variant()),
+        ) {
+            warn!("Failed to emit MPRIS Seeked: {}", e);
+        }
+    }
+
+    fn player(&self) -> Option<Rc<dyn MprisPlayer>> {
+        self.inner.player.borrow().clone()
+    }
+
+    fn connection(&self) -> Option<gio::DBusConnection> {
+        self.inner.connection.borrow().clone()
+    }
+
+    fn emit_changed(&self, properties: &[&str]) {
+        let Some(connection) = self.connection() else {
+            return;
+        };
+
+        let changed = glib::VariantDict::new(None);
+        for property in properties {
+            changed.insert_value(property, &self.property(PLAYER_INTERFACE, property));
+        }
+
+        let parameters = glib::Variant::tuple_from_iter([
+            PLAYER_INTERFACE.to_variant(),
+            changed.end(),
+            Vec::<String>::new().to_variant(),
+        ]);
+
+        if let Err(e) = connection.emit_signal(
+            None,
+            OBJECT_PATH,
+            "org.freedesktop.DBus.Properties",
+            "PropertiesChanged",
+            Some(&parameters),
+        ) {
+            warn!("Failed to emit MPRIS PropertiesChanged: {}", e);
+        }
+    }
+
+    fn method_call(
+        &self, interface_name: &str, method_name: &str, parameters: &glib::Variant,
+        invocation: gio::DBusMethodInvocation,
+    ) {
+        if interface_name == ROOT_INTERFACE {
+            let application = gio::Application::default().and_downcast::<gtk::Application>();
+            match method_name {
+                "Raise" => {
+                    if let Some(window) = application.and_then(|app| app.active_window()) {
+                        window.present();
+                    }
+                }
+                "Quit" => {
+                    if let Some(application) = application {
+                        application.quit();
+                    }
+                }
+                _ => {}
+            }
+            invocation.return_value(None);
+            return;
+        }
+
+        if method_name == "OpenUri" {
+            invocation.return_dbus_error(
+                "org.freedesktop.DBus.Error.NotSupported",
+                "Opening uris is not supported",
+            );
+            return;
+        }
+
+        // Without a player every method is a no-op, as the spec asks for.
+        let Some(player) = self.player() else {
+            invocation.return_value(None);
+            return;
+        };
+
+        match method_name {
+            "Next" => player.next(),
+            "Previous" => player.previous(),
+            "Pause" => player.pause(),
+            "PlayPause" => player.play_pause(),
+            "Stop" => player.stop(),
+            "Play" => player.play(),
+            "Seek" => {
+                if let Some((offset,)) = parameters.get::<(i64,)>() {
+                    player.seek(offset);
+                }
+            }
+            "SetPosition" => {
+                let track_id = parameters.child_value(0);
+                let position = parameters.child_value(1).get::<i64>().unwrap_or(-1);
+                let metadata = player.metadata();
+                // Stale requests for a previous track must be ignored.
+                if track_id.str() == Some(metadata.track_id().as_str())
+                    && position >= 0
+                    && !metadata.length.is_some_and(|length| position > length)
+                {
+                    player.set_position(position);
+                }
+            }
+            _ => {}
+        }
+        invocation.return_value(None);
+    }
+
+    fn property(&self, interface_name: &str, property_name: &str) -> glib::Variant {
+        if interface_name == ROOT_INTERFACE {
+            return match property_name {
+                "CanQuit" | "CanRaise" => true.to_variant(),
+                "HasTrackList" => false.to_variant(),
+                "Identity" => "Tsukimi".to_variant(),
+                "DesktopEntry" => "moe.tsuna.tsukimi".to_variant(),
+                _ => Vec::<String>::new().to_variant(),
+            };
+        }
+
+        let player = self.player();
+        let player = player.as_deref();
+        match property_name {
+            "PlaybackStatus" => player
+                .map_or(PlaybackStatus::Stopped, |p| p.playback_status())
+                .as_str()
+                .to_variant(),
+            "LoopStatus" => player
+                .map_or(LoopStatus::None, |p| p.loop_status())
+                .as_str()
+                .to_variant(),
+            "Rate" => player.map_or(1.0, |p| p.rate()).to_variant(),
+            "MinimumRate" => player.map_or(1.0, |p| p.rate_range().0).to_variant(),
+            "MaximumRate" => player.map_or(1.0, |p| p.rate_range().1).to_variant(),
+            "Shuffle" => player.is_some_and(|p| p.shuffle()).to_variant(),
+            "Metadata" => player
+                .map(|p| p.metadata())
+                .unwrap_or_default()
+                .to_variant(),
+            "Volume" => player.map_or(1.0, |p| p.volume()).to_variant(),
+            "Position" => player.map_or(0, |p| p.position()).to_variant(),
+            "CanGoNext" => player.is_some_and(|p| p.can_go_next()).to_variant(),
+            "CanGoPrevious" => player.is_some_and(|p| p.can_go_previous()).to_variant(),
+            "CanControl" => true.to_variant(),
+            _ => player.is_some().to_variant(),
+        }
+    }
+
+    fn set_property(&self, property_name: &str, value: &glib::Variant) -> bool {
+        let Some(player) = self.player() else {
+            return false;
+        };
+
+        match property_name {
+            "LoopStatus" => {
+                let Some(status) = value.str().and_then(LoopStatus::from_string) else {
+                    return false;
+                };
+                player.set_loop_status(status);
+            }
+            "Rate" => {
+                let Some(rate) = value.get::<f64>() else {
+                    return false;
+                };
+                let (min, max) = player.rate_range();
+                // A rate of zero means pause, which clients should do with Pause.
+                if rate <= 0.0 {
+                    return false;
+                }
+                player.set_rate(rate.clamp(min, max));
+            }
+            "Shuffle" => {
+                let Some(shuffle) = value.get::<bool>() else {
+                    return false;
+                };
+                player.set_shuffle(shuffle);
+            }
+            "Volume" => {
+                let Some(volume) = value.get::<f64>() else {
+                    return false;
+                };
+                player.set_volume(volume.max(0.0));
+            }
+            _ => return false,
+        }
+        true
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::cell::Cell;
+
+    use super::*;
+
+    #[test]
+    fn test_track_id() {
+        assert_eq!(
+            track_id("3f2a-b9"),
+            "/moe/tsuna/tsukimi/Track/3f2a_b9".to_string()
+        );
+        assert_eq!(Metadata::default().track_id(), NO_TRACK);
+    }
+
+    #[test]
+    fn test_metadata_variant() {
+        let metadata = Metadata {
+            id: "42".to_string(),
+            title: "Pilot".to_string(),
+            series: Some("Show".to_string()),
+            length: Some(60_000_000),
+            ..Default::default()
+        };
+        let dict = glib::VariantDict::new(Some(&metadata.to_variant()));
+        assert_eq!(
+            dict.lookup::<String>("xesam:album").ok().flatten(),
+            Some("Show".to_string())
+        );
+        assert_eq!(
+            dict.lookup::<i64>("mpris:length").ok().flatten(),
+            Some(60_000_000)
+        );
+        assert!(!dict.contains("mpris:artUrl"));
+    }
+
+    #[derive(Default)]
+    struct TestPlayer {
+        playing: Cell<bool>,
+        position: Cell<i64>,
+    }
+
+    impl MprisPlayer for TestPlayer {
+        fn name(&self) -> &'static str {
+            "test"
+        }
+
+        fn metadata(&self) -> Metadata {
+            Metadata {
+                id: "1".to_string(),
+                title: "Test".to_string(),
+                length: Some(10_000_000),
+                ..Default::default()
+            }
+        }
+
+        fn playback_status(&self) -> PlaybackStatus {
+            if self.playing.get() {
+                PlaybackStatus::Playing
+            } else {
+                PlaybackStatus::Paused
+            }
+        }
+
+        fn position(&self) -> i64 {
+            self.position.get()
+        }
+
+        fn volume(&self) -> f64 {
+            1.0
+        }
+
+        fn set_volume(&self, _volume: f64) {}
+
+        fn play(&self) {
+            self.playing.set(true);
+        }
+
+        fn pause(&self) {
+            self.playing.set(false);
+        }
+
+        fn play_pause(&self) {
+            self.playing.set(!self.playing.get());
+        }
+
+        fn stop(&self) {}
+
+        fn next(&self) {}
+
+        fn previous(&self) {}
+
+        fn set_position(&self, position: i64) {
+            self.position.set(position);
+        }
+    }
+
+    #[test]
+    fn test_private_bus() {
+        if glib::find_program_in_path("dbus-daemon").is_none() {
+            return;
+        }
+
+        let bus = gio::TestDBus::new(gio::TestDBusFlags::NONE);
+        bus.up();
+
+        let context = glib::MainContext::default();
+        let connection = gio::bus_get_sync(gio::BusType::Session, None::<&gio::Cancellable>)
+            .expect("private session bus");
+        let name = connection.unique_name().map(|name| name.to_string());
+
+        let player = Rc::new(TestPlayer::default());
+        let mpris = Mpris::default();
+        mpris.register(&connection).expect("register MPRIS");
+        mpris.set_player(player.clone());
+
+        let call = |interface: &str, method: &str, parameters: Option<glib::Variant>| {
+            context
+                .block_on(connection.call_future(
+                    name.as_deref(),
+                    OBJECT_PATH,
+                    interface,
+                    method,
+                    parameters.as_ref(),
+                    None,
+                    gio::DBusCallFlags::NONE,
+                    -1,
+                ))
+                .expect("D-Bus call")
+        };
+
+        call(PLAYER_INTERFACE, "PlayPause", None);
+        assert!(player.playing.get());
+
+        call(
+            PLAYER_INTERFACE,
+            "SetPosition",
+            Some(glib::Variant::tuple_from_iter([
+                glib::variant::ObjectPath::try_from(track_id("1"))
+                    .unwrap()
+                    .to_variant(),
+                5_000_000i64.to_variant(),
+            ])),
+        );
+        assert_eq!(player.position.get(), 5_000_000);
+
+        call(
+            PLAYER_INTERFACE,
+            "Seek",
+            Some((-1_000_000i64,).to_variant()),
+        );
+        assert_eq!(player.position.get(), 4_000_000);
+
+        let status = call(
+            "org.freedesktop.DBus.Properties",
+            "Get",
+            Some((PLAYER_INTERFACE, "PlaybackStatus").to_variant()),
+        );
+        assert_eq!(
+            status
+                .child_value(0)
+                .as_variant()
+                .and_then(|v| v.str().map(String::from)),
+            Some("Playing".to_string())
+        );
+
+        bus.down();
+    }
+}
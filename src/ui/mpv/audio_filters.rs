@@ -0,0 +1,126 @@
+use crate::ui::models::{
+    equalizer::{
+        Gains,
+        BANDS,
+    },
+    SETTINGS,
+};
+
+const SWAP_CHANNELS: &str = "pan=[stereo|c0=c1|c1=c0]";
+const DIALOGUE_BOOST: &str = "lavfi=[pan=stereo|FL=FC+0.30*FL+0.30*BL|FR=FC+0.30*FR+0.30*BR]";
+const LOUDNORM: &str = "lavfi=[loudnorm=I=-16:TP=-1.5:LRA=11]";
+const NIGHT_MODE: &str = "lavfi=[dynaudnorm=f=75:g=25:p=0.55]";
+
+/// Selected audio channel entry that swaps left and right.
+pub const SWAP_CHANNELS_INDEX: u32 = 4;
+
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub enum Normalization {
+    #[default]
+    Off,
+    /// EBU R128 loudness normalization.
+    Loudness,
+    /// Dynamic normalization, quiet scenes are lifted and loud ones tamed.
+    NightMode,
+}
+
+impl From<u32> for Normalization {
+    fn from(value: u32) -> Self {
+        match value {
+            1 => Self::Loudness,
+            2 => Self::NightMode,
+            _ => Self::Off,
+        }
+    }
+}
+
+/// Everything that ends up in mpv's `af`.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct AudioFilters {
+    pub swap_channels: bool,
+    pub dialogue_boost: bool,
+    /// The playing audio track has more than two channels.
+    pub surround: bool,
+    pub equalizer: Gains,
+    pub normalization: Normalization,
+}
+
+impl AudioFilters {
+    pub fn from_settings() -> Self {
+        Self {
+            swap_channels: SETTINGS.mpv_audio_channel() == SWAP_CHANNELS_INDEX as i32,
+            dialogue_boost: SETTINGS.mpv_dialogue_boost(),
+            surround: false,
+            equalizer: SETTINGS.mpv_equalizer(),
+            normalization: Normalization::from(SETTINGS.mpv_audio_normalization() as u32),
+        }
+    }
+
+    /// Filter chain for mpv's `af`. Normalization comes last so it also
+    /// evens out what the equalizer boosted.
+    pub fn chain(&self) -> String {
+        let mut filters = Vec::new();
+        // The centre channel is folded into both sides, the pan fails on
+        // layouts without one
+        if self.dialogue_boost && self.surround {
+            filters.push(DIALOGUE_BOOST.to_string());
+        }
+        if self.swap_channels {
+            filters.push(SWAP_CHANNELS.to_string());
+        }
+
+        let bands: Vec<String> = BANDS
+            .iter()
+            .zip(self.equalizer)
+            .filter(|(_, gain)| *gain != 0.0)
+            .map(|(frequency, gain)| format!("equalizer=f={frequency}:t=o:w=1:g={gain}"))
+            .collect();
+        if !bands.is_empty() {
+            filters.push(format!("lavfi=[{}]", bands.join(",")));
+        }
+
+        match self.normalization {
+            Normalization::Off => {}
+            Normalization::Loudness => filters.push(LOUDNORM.to_string()),
+            Normalization::NightMode => filters.push(NIGHT_MODE.to_string()),
+        }
+        filters.join(",")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_chain() {
+        assert_eq!(AudioFilters::default().chain(), "");
+
+        let stereo = AudioFilters {
+            dialogue_boost: true,
+            ..Default::default()
+        };
+        assert_eq!(stereo.chain(), "");
+        let surround = AudioFilters {
+            surround: true,
+            ..stereo
+        };
+        assert_eq!(surround.chain(), DIALOGUE_BOOST);
+
+        let mut equalizer = [0.0; 10];
+        equalizer[0] = 3.0;
+        equalizer[9] = -1.5;
+        let filters = AudioFilters {
+            swap_channels: true,
+            equalizer,
+            normalization: Normalization::NightMode,
+            ..Default::default()
+        };
+        assert_eq!(
+            filters.chain(),
+            "pan=[stereo|c0=c1|c1=c0],\
+             lavfi=[equalizer=f=29:t=o:w=1:g=3,equalizer=f=15011:t=o:w=1:g=-1.5],\
+             lavfi=[dynaudnorm=f=75:g=25:p=0.55]"
+        );
+    }
+}
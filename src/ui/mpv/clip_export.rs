@@ -0,0 +1,149 @@
+use std::path::PathBuf;
+
+use flume::{
+    unbounded,
+    Receiver,
+    Sender,
+};
+use libmpv2::{
+    events::{
+        Event,
+        EventContext,
+        PropertyData,
+    },
+    Mpv,
+};
+use tracing::warn;
+
+/// Frame rate and width of exported GIFs, full size ones get huge quickly.
+const GIF_FILTER: &str = "lavfi=[fps=15,scale=480:-2:flags=lanczos]";
+
+/// `mpv_end_file_reason` for a clip that was written to the end.
+const END_FILE_EOF: u32 = 0;
+
+pub enum ExportEvent {
+    /// Fraction of the clip written so far.
+    Progress(f64),
+    Done,
+    Error(String),
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ClipFormat {
+    Video,
+    Gif,
+}
+
+impl ClipFormat {
+    pub fn for_path(path: &std::path::Path) -> Self {
+        match path.extension().and_then(|e| e.to_str()) {
+            Some(extension) if extension.eq_ignore_ascii_case("gif") => Self::Gif,
+            _ => Self::Video,
+        }
+    }
+}
+
+pub struct ClipExport {
+    pub url: String,
+    pub output: PathBuf,
+    pub start: f64,
+    pub end: f64,
+    pub format: ClipFormat,
+    pub http_proxy: Option<String>,
+}
+
+impl ClipExport {
+    /// Encode the clip with a second, headless mpv instance in mpv's
+    /// encoding mode so playback keeps going while it runs.
+    pub fn spawn(self) -> Receiver<ExportEvent> {
+        let (tx, rx) = unbounded::<ExportEvent>();
+        let spawned = std::thread::Builder::new()
+            .name("mpv clip export".into())
+            .spawn({
+                let tx = tx.clone();
+                move || {
+                    if let Err(e) = self.run(&tx) {
+                        let _ = tx.send(ExportEvent::Error(e.to_string()));
+                    }
+                }
+            });
+        if let Err(e) = spawned {
+            let _ = tx.send(ExportEvent::Error(e.to_string()));
+        }
+        rx
+    }
+
+    fn run(&self, tx: &Sender<ExportEvent>) -> libmpv2::Result<()> {
+        let mpv = Mpv::with_initializer(|init| {
+            init.set_property("o", self.output.to_string_lossy().to_string())?;
+            init.set_property("start", self.start.to_string())?;
+            init.set_property("end", self.end.to_string())?;
+            init.set_property("user-agent", "Tsukimi")?;
+            init.set_property("ytdl", false)?;
+            if let Some(proxy) = &self.http_proxy {
+                init.set_property("http-proxy", proxy.as_str())?;
+            }
+            if self.format == ClipFormat::Gif {
+                init.set_property("of", "gif")?;
+                init.set_property("ovc", "gif")?;
+                init.set_property("aid", "no")?;
+                init.set_property("sid", "no")?;
+                init.set_property("vf", GIF_FILTER)?;
+            }
+            Ok(())
+        })?;
+
+        let mut event_context = EventContext::new(mpv.ctx);
+        event_context.disable_deprecated_events()?;
+        event_context.observe_property("time-pos", libmpv2::Format::Double, 0)?;
+
+        mpv.command("loadfile", &[&self.url, "replace"])?;
+
+        let length = (self.end - self.start).max(f64::EPSILON);
+        let result = loop {
+            match event_context.wait_event(1000.0) {
+                Some(Ok(Event::PropertyChange {
+                    name: "time-pos",
+                    change: PropertyData::Double(position),
+                    ..
+                })) => {
+                    let fraction = ((position - self.start) / length).clamp(0.0, 1.0);
+                    let _ = tx.send(ExportEvent::Progress(fraction));
+                }
+                Some(Ok(Event::EndFile(reason))) => {
+                    break if reason == END_FILE_EOF {
+                        ExportEvent::Done
+                    } else {
+                        ExportEvent::Error(format!("mpv stopped encoding ({})", reason))
+                    };
+                }
+                Some(Ok(Event::Shutdown)) => {
+                    break ExportEvent::Error("mpv shut down".to_string());
+                }
+                Some(Err(e)) => warn!("MPV clip export Error: {}", e),
+                _ => {}
+            }
+        };
+
+        // The file is only finalized once mpv is destroyed.
+        drop(event_context);
+        drop(mpv);
+        let _ = tx.send(result);
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::path::Path;
+
+    use super::*;
+
+    #[test]
+    fn test_clip_format() {
+        assert_eq!(ClipFormat::for_path(Path::new("a.GIF")), ClipFormat::Gif);
+        assert_eq!(ClipFormat::for_path(Path::new("a.mp4")), ClipFormat::Video);
+        assert_eq!(ClipFormat::for_path(Path::new("a")), ClipFormat::Video);
+    }
+}
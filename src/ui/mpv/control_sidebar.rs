@@ -11,18 +11,41 @@ use gtk::{
 };
 use libmpv2::SetData;
 
-use super::options_matcher::{
-    match_audio_channels,
-    match_hwdec_interop,
-    match_sub_border_style,
-    match_video_upscale,
+use super::{
+    audio_filters::{
+        AudioFilters,
+        SWAP_CHANNELS_INDEX,
+    },
+    options_matcher::{
+        match_audio_channels,
+        match_hwdec_interop,
+        match_sub_border_style,
+        match_video_upscale,
+    },
 };
 use crate::{
     toast,
-    ui::models::SETTINGS,
+    ui::{
+        models::{
+            equalizer::Gains,
+            screenshots::screenshot_dir,
+            shader_profiles::ShaderProfiles,
+            SETTINGS,
+        },
+        widgets::{
+            equalizer_editor::EqualizerEditor,
+            shader_profiles_dialog::ShaderProfilesDialog,
+        },
+    },
 };
 
 mod imp {
+    use std::cell::{
+        Cell,
+        OnceCell,
+        RefCell,
+    };
+
     use glib::subclass::InitializingObject;
     use gtk::glib;
 
@@ -90,11 +113,36 @@ mod imp {
         #[template_child]
         pub sub_speed_adj: TemplateChild<gtk::Adjustment>,
 
+        #[template_child]
+        pub secondary_sub_position_adj: TemplateChild<gtk::Adjustment>,
+        #[template_child]
+        pub secondary_sub_offset_adj: TemplateChild<gtk::Adjustment>,
+        #[template_child]
+        pub secondary_sub_style_switchrow: TemplateChild<adw::SwitchRow>,
+
         #[template_child]
         pub audio_offset_adj: TemplateChild<gtk::Adjustment>,
         #[template_child]
         pub audio_channel_combo: TemplateChild<adw::ComboRow>,
 
+        #[template_child]
+        pub audio_normalization_combo: TemplateChild<adw::ComboRow>,
+        #[template_child]
+        pub dialogue_boost_switchrow: TemplateChild<adw::SwitchRow>,
+        #[template_child]
+        pub pitch_correction_switchrow: TemplateChild<adw::SwitchRow>,
+        #[template_child]
+        pub equalizer_row: TemplateChild<adw::ExpanderRow>,
+        #[template_child]
+        pub equalizer_preset_combo: TemplateChild<adw::ComboRow>,
+        pub equalizer: OnceCell<EqualizerEditor>,
+
+        #[template_child]
+        pub shader_profile_combo: TemplateChild<adw::ComboRow>,
+        /// Profile names in the order of `shader_profile_combo`, after "None".
+        pub shader_profiles: RefCell<Vec<String>>,
+        pub updating_shader_profiles: Cell<bool>,
+
         #[template_child]
         pub video_upsacle_filter_combo: TemplateChild<adw::ComboRow>,
 
@@ -112,6 +160,16 @@ mod imp {
 
         #[template_child]
         pub stretch_image_subs_to_screen_switchrow: TemplateChild<adw::SwitchRow>,
+
+        #[template_child]
+        pub ab_loop_count_adj: TemplateChild<gtk::Adjustment>,
+
+        #[template_child]
+        pub screenshot_subtitles_switchrow: TemplateChild<adw::SwitchRow>,
+        #[template_child]
+        pub screenshot_format_combo: TemplateChild<adw::ComboRow>,
+        #[template_child]
+        pub screenshot_dir_row: TemplateChild<adw::ActionRow>,
     }
 
     #[glib::object_subclass]
@@ -134,7 +192,9 @@ mod imp {
     impl ObjectImpl for MPVControlSidebar {
         fn constructed(&self) {
             self.parent_constructed();
-            self.obj().bind_actions();
+            let obj = self.obj();
+            obj.bind_actions();
+            obj.build_equalizer();
         }
     }
 
@@ -147,6 +207,16 @@ mod imp {
                 return;
             }
             self.player.set(player.as_ref());
+
+            let obj = self.obj();
+            if let Some(player) = player {
+                player.connect_shader_profile_notify(glib::clone!(
+                    #[weak]
+                    obj,
+                    move |_| obj.update_shader_profiles()
+                ));
+            }
+            obj.update_shader_profiles();
         }
     }
 }
@@ -269,6 +339,9 @@ impl MPVControlSidebar {
         SETTINGS
             .bind("mpv-cache-time", &imp.cache_time_adj.get(), "value")
             .build();
+        SETTINGS
+            .bind("mpv-ab-loop-count", &imp.ab_loop_count_adj.get(), "value")
+            .build();
         SETTINGS
             .bind("mpv-deband", &imp.deband_switch.get(), "active")
             .build();
@@ -279,6 +352,27 @@ impl MPVControlSidebar {
                 "selected",
             )
             .build();
+        SETTINGS
+            .bind(
+                "mpv-audio-normalization",
+                &imp.audio_normalization_combo.get(),
+                "selected",
+            )
+            .build();
+        SETTINGS
+            .bind(
+                "mpv-dialogue-boost",
+                &imp.dialogue_boost_switchrow.get(),
+                "active",
+            )
+            .build();
+        SETTINGS
+            .bind(
+                "mpv-audio-pitch-correction",
+                &imp.pitch_correction_switchrow.get(),
+                "active",
+            )
+            .build();
         SETTINGS
             .bind("mpv-subtitle-scale", &imp.sub_scale_adj.get(), "value")
             .build();
@@ -289,6 +383,98 @@ impl MPVControlSidebar {
                 "selected",
             )
             .build();
+        SETTINGS
+            .bind(
+                "mpv-screenshot-subtitles",
+                &imp.screenshot_subtitles_switchrow.get(),
+                "active",
+            )
+            .build();
+        SETTINGS
+            .bind(
+                "mpv-screenshot-format",
+                &imp.screenshot_format_combo.get(),
+                "selected",
+            )
+            .build();
+        imp.screenshot_dir_row
+            .set_subtitle(&screenshot_dir().to_string_lossy());
+    }
+
+    #[template_callback]
+    async fn on_screenshot_dir_clicked(&self) {
+        let window = self.root().and_downcast::<gtk::Window>();
+        let filedialog = gtk::FileDialog::builder()
+            .modal(true)
+            .title(gettext("Select Screenshot Folder"))
+            .initial_folder(&gio::File::for_path(screenshot_dir()))
+            .build();
+
+        let Ok(file) = filedialog.select_folder_future(window.as_ref()).await else {
+            return;
+        };
+        let Some(path) = file.path() else {
+            return;
+        };
+
+        let path = path.to_string_lossy();
+        let _ = SETTINGS.set_mpv_screenshot_dir(&path);
+        self.imp().screenshot_dir_row.set_subtitle(&path);
+    }
+
+    /// Refill the profile combo and select the profile the player runs with.
+    pub fn update_shader_profiles(&self) {
+        let imp = self.imp();
+        let names = ShaderProfiles::load().names();
+        let current = self
+            .player()
+            .map(|player| player.shader_profile())
+            .unwrap_or_default();
+        let selected = names
+            .iter()
+            .position(|name| *name == current)
+            .map_or(0, |position| position + 1);
+
+        let mut items = vec![gettext("None")];
+        items.extend(names.iter().cloned());
+        imp.shader_profiles.replace(names);
+
+        let model = gtk::StringList::new(&items.iter().map(String::as_str).collect::<Vec<_>>());
+        imp.updating_shader_profiles.set(true);
+        imp.shader_profile_combo.set_model(Some(&model));
+        imp.shader_profile_combo.set_selected(selected as u32);
+        imp.updating_shader_profiles.set(false);
+    }
+
+    #[template_callback]
+    fn on_shader_profile(&self, _param: glib::ParamSpec, combo: adw::ComboRow) {
+        let imp = self.imp();
+        if imp.updating_shader_profiles.get() {
+            return;
+        }
+        let Some(player) = self.player() else {
+            return;
+        };
+        let name = (combo.selected() as usize)
+            .checked_sub(1)
+            .and_then(|index| imp.shader_profiles.borrow().get(index).cloned())
+            .unwrap_or_default();
+        if name == player.shader_profile() {
+            return;
+        }
+        let profiles = ShaderProfiles::load();
+        player.apply_shader_profile(profiles.get(&name));
+    }
+
+    #[template_callback]
+    fn on_manage_shader_profiles(&self) {
+        let dialog = ShaderProfilesDialog::new();
+        dialog.connect_closed(glib::clone!(
+            #[weak(rename_to = obj)]
+            self,
+            move |_| obj.update_shader_profiles()
+        ));
+        dialog.present(Some(self));
     }
 
     pub fn set_mpv_property<V>(&self, property: &str, value: V)
@@ -356,6 +542,13 @@ impl MPVControlSidebar {
         self.set_mpv_property("cache-secs", spin.value());
     }
 
+    #[template_callback]
+    pub fn on_ab_loop_count(&self, _param: glib::ParamSpec, spin: adw::SpinRow) {
+        if let Some(player) = self.player() {
+            player.set_ab_loop_count(spin.value() as i32);
+        }
+    }
+
     #[template_callback]
     pub fn on_buffer_speed(&self, _param: glib::ParamSpec, switch: adw::SwitchRow) {
         SETTINGS
@@ -434,6 +627,23 @@ impl MPVControlSidebar {
         self.set_mpv_property("sub-delay", spin.value() / 1000.0);
     }
 
+    #[template_callback]
+    pub fn on_secondary_sub_position(&self, _param: glib::ParamSpec, spin: adw::SpinRow) {
+        // Default: 0, the top of the screen
+        self.set_mpv_property("secondary-sub-pos", spin.value());
+    }
+
+    #[template_callback]
+    pub fn on_secondary_sub_offset(&self, _param: glib::ParamSpec, spin: adw::SpinRow) {
+        self.set_mpv_property("secondary-sub-delay", spin.value() / 1000.0);
+    }
+
+    #[template_callback]
+    pub fn on_secondary_sub_style(&self, _param: glib::ParamSpec, switch: adw::SwitchRow) {
+        let ass_override = if switch.is_active() { "yes" } else { "strip" };
+        self.set_mpv_property("secondary-sub-ass-override", ass_override);
+    }
+
     #[template_callback]
     pub fn on_border_style(&self, _param: glib::ParamSpec, combo: adw::ComboRow) {
         let border_style = match_sub_border_style(combo.selected() as i32);
@@ -497,6 +707,16 @@ impl MPVControlSidebar {
         toast!(self, gettext("Subtitle offset settings cleared."));
     }
 
+    #[template_callback]
+    fn on_secondary_sub_clear(&self, _button: gtk::Button) {
+        let imp = self.imp();
+        imp.secondary_sub_position_adj.set_value(0.0);
+        imp.secondary_sub_offset_adj.set_value(0.0);
+        imp.secondary_sub_style_switchrow.set_active(false);
+
+        toast!(self, gettext("Secondary subtitle settings cleared."));
+    }
+
     #[template_callback]
     fn on_audio_offset(&self, _param: glib::ParamSpec, spin: adw::SpinRow) {
         self.set_mpv_property("audio-delay", spin.value() / 1000.0);
@@ -543,15 +763,74 @@ impl MPVControlSidebar {
     fn on_audio_channel(&self, _param: glib::ParamSpec, combo: adw::ComboRow) {
         let selected = combo.selected();
 
-        if selected == 4 {
-            self.set_mpv_property("af", "pan=[stereo|c0=c1|c1=c0]");
-            return;
+        if selected != SWAP_CHANNELS_INDEX {
+            let channel = match_audio_channels(selected as i32);
+            self.set_mpv_property("audio-channels", channel);
+        }
+
+        self.update_audio_filters();
+    }
+
+    fn build_equalizer(&self) {
+        let imp = self.imp();
+        let editor = EqualizerEditor::new(
+            &imp.equalizer_row,
+            &imp.equalizer_preset_combo,
+            || SETTINGS.mpv_equalizer(),
+            glib::clone!(
+                #[weak(rename_to = obj)]
+                self,
+                move |gains: &Gains| {
+                    let _ = SETTINGS.set_mpv_equalizer(gains);
+                    obj.update_audio_filters();
+                }
+            ),
+        );
+        let _ = imp.equalizer.set(editor);
+    }
+
+    fn audio_filters(&self) -> AudioFilters {
+        let imp = self.imp();
+        AudioFilters {
+            swap_channels: imp.audio_channel_combo.selected() == SWAP_CHANNELS_INDEX,
+            dialogue_boost: imp.dialogue_boost_switchrow.is_active(),
+            surround: self
+                .player()
+                .is_some_and(|player| player.audio_channel_count() > 2),
+            equalizer: imp
+                .equalizer
+                .get()
+                .map_or_else(|| SETTINGS.mpv_equalizer(), EqualizerEditor::gains),
+            normalization: imp.audio_normalization_combo.selected().into(),
         }
+    }
+
+    fn update_audio_filters(&self) {
+        self.set_mpv_property("af", self.audio_filters().chain());
+    }
 
-        let channel = match_audio_channels(selected as i32);
+    #[template_callback]
+    fn on_audio_normalization(&self, _param: glib::ParamSpec, _combo: adw::ComboRow) {
+        self.update_audio_filters();
+    }
 
-        self.set_mpv_property("af", "");
-        self.set_mpv_property("audio-channels", channel);
+    #[template_callback]
+    fn on_dialogue_boost(&self, _param: glib::ParamSpec, _switch: adw::SwitchRow) {
+        self.update_audio_filters();
+    }
+
+    #[template_callback]
+    fn on_pitch_correction(&self, _param: glib::ParamSpec, switch: adw::SwitchRow) {
+        self.set_mpv_property("audio-pitch-correction", switch.is_active());
+    }
+
+    #[template_callback]
+    fn on_audio_filters_clear(&self, _button: gtk::Button) {
+        let imp = self.imp();
+        imp.audio_normalization_combo.set_selected(0);
+        imp.dialogue_boost_switchrow.set_active(false);
+        imp.pitch_correction_switchrow.set_active(true);
+        imp.equalizer_preset_combo.set_selected(0);
     }
 
     #[template_callback]
@@ -1,7 +1,13 @@
+pub mod audio_filters;
+pub mod clip_export;
 pub mod control_sidebar;
 pub mod menu_actions;
 pub mod mpvglarea;
+pub mod multipart;
 pub mod options_matcher;
 pub mod page;
+pub mod pip_window;
+pub mod syncplay;
+pub mod syncplay_panel;
 pub mod tsukimi_mpv;
 pub mod video_scale;
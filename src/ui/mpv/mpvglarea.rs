@@ -12,14 +12,29 @@ use gtk::{
 use libmpv2::SetData;
 use tracing::info;
 
-use super::tsukimi_mpv::{
-    TrackSelection,
-    ACTIVE,
+use super::{
+    options_matcher::match_video_upscale,
+    tsukimi_mpv::{
+        TrackSelection,
+        ACTIVE,
+    },
+};
+use crate::{
+    client::emby_client::EMBY_CLIENT,
+    ui::models::{
+        shader_profiles::ShaderProfile,
+        SETTINGS,
+    },
 };
-use crate::client::emby_client::EMBY_CLIENT;
 
 mod imp {
-    use std::thread::JoinHandle;
+    use std::{
+        cell::{
+            Cell,
+            RefCell,
+        },
+        thread::JoinHandle,
+    };
 
     use gettextrs::gettext;
     use gtk::{
@@ -33,16 +48,23 @@ mod imp {
     use crate::{
         close_on_error,
         ui::mpv::tsukimi_mpv::{
+            TrackSelection,
             TsukimiMPV,
             RENDER_UPDATE,
         },
     };
 
     // Object holding the state
-    #[derive(Default)]
+    #[derive(Default, glib::Properties)]
+    #[properties(wrapper_type = super::MPVGLArea)]
     pub struct MPVGLArea {
         pub mpv: TsukimiMPV,
         pub mpv_event_loop: OnceCell<JoinHandle<()>>,
+        /// Video track to restore once realized again.
+        pub vid: Cell<i64>,
+        /// Name of the applied shader profile, empty for none.
+        #[property(get, set)]
+        pub shader_profile: RefCell<String>,
     }
 
     #[glib::object_subclass]
@@ -52,6 +74,7 @@ mod imp {
         type ParentType = gtk::GLArea;
     }
 
+    #[glib::derived_properties]
     impl ObjectImpl for MPVGLArea {
         fn constructed(&self) {
             self.parent_constructed();
@@ -76,17 +99,33 @@ mod imp {
 
             self.mpv.connect_render_update(gl_context);
 
-            glib::spawn_future_local(glib::clone!(
-                #[weak]
-                obj,
-                async move {
-                    while let Ok(true) = RENDER_UPDATE.rx.recv_async().await {
-                        obj.queue_render();
+            // mpv drops the video track when the old render context goes
+            // away, e.g. when moving into the picture-in-picture window
+            let vid = self.vid.take();
+            if vid > 0 {
+                self.mpv.set_vid(TrackSelection::Track(vid));
+            }
+
+            self.mpv_event_loop.get_or_init(|| {
+                glib::spawn_future_local(glib::clone!(
+                    #[weak]
+                    obj,
+                    async move {
+                        while let Ok(true) = RENDER_UPDATE.rx.recv_async().await {
+                            obj.queue_render();
+                        }
                     }
-                }
-            ));
+                ));
 
-            self.mpv.process_events();
+                self.mpv.process_events()
+            });
+        }
+
+        fn unrealize(&self) {
+            self.vid.set(self.mpv.get_track_id("vid"));
+            self.obj().make_current();
+            self.mpv.disconnect_render_update();
+            self.parent_unrealize();
         }
     }
 
@@ -135,7 +174,12 @@ impl MPVGLArea {
             .store(ACTIVE, std::sync::atomic::Ordering::SeqCst);
         atomic_wait::wake_all(&*mpv.event_thread_alive);
 
-        let url = EMBY_CLIENT.get_streaming_url(url);
+        // Multi-part timelines already carry absolute urls
+        let url = if url.starts_with("edl://") {
+            url.to_string()
+        } else {
+            EMBY_CLIENT.get_streaming_url(url)
+        };
 
         info!("Now Playing: {}", url);
         mpv.load_video(&url);
@@ -149,6 +193,10 @@ impl MPVGLArea {
         self.imp().mpv.add_sub(url)
     }
 
+    pub fn add_sub_file(&self, path: &str, title: &str, select: bool) {
+        self.imp().mpv.add_sub_file(path, title, select)
+    }
+
     pub fn seek_forward(&self, value: i64) {
         self.imp().mpv.seek_forward(value)
     }
@@ -207,6 +255,10 @@ impl MPVGLArea {
         self.imp().mpv.set_aid(value)
     }
 
+    pub fn audio_channel_count(&self) -> i64 {
+        self.imp().mpv.audio_channel_count()
+    }
+
     pub fn get_track_id(&self, type_: &str) -> i64 {
         self.imp().mpv.get_track_id(type_)
     }
@@ -215,6 +267,67 @@ impl MPVGLArea {
         self.imp().mpv.set_sid(value)
     }
 
+    pub fn set_secondary_sid(&self, value: TrackSelection) {
+        self.imp().mpv.set_secondary_sid(value)
+    }
+
+    pub fn add_property(&self, property: &str, value: f64) {
+        self.imp().mpv.add_property(property, value)
+    }
+
+    pub fn multiply_property(&self, property: &str, value: f64) {
+        self.imp().mpv.multiply_property(property, value)
+    }
+
+    pub fn cycle_property(&self, property: &str) {
+        self.imp().mpv.cycle_property(property)
+    }
+
+    pub fn screenshot_to_file(&self, path: &str, subtitles: bool) {
+        self.imp().mpv.screenshot_to_file(path, subtitles)
+    }
+
+    pub fn set_ab_loop(&self, a: Option<f64>, b: Option<f64>) {
+        self.imp().mpv.set_ab_loop(a, b)
+    }
+
+    pub fn set_ab_loop_count(&self, count: i32) {
+        self.imp().mpv.set_ab_loop_count(count)
+    }
+
+    pub fn path(&self) -> Option<String> {
+        self.imp().mpv.path()
+    }
+
+    /// Apply the shaders, scaler and deband of `profile`, `None` goes back
+    /// to the scaler and deband from the settings.
+    pub fn apply_shader_profile(&self, profile: Option<&ShaderProfile>) {
+        let mpv = &self.imp().mpv;
+        match profile {
+            Some(profile) => {
+                mpv.set_property("glsl-shaders", profile.glsl_shaders());
+                mpv.set_property("scale", match_video_upscale(profile.scale));
+                mpv.set_property("deband", profile.deband.is_some());
+                if let Some(deband) = profile.deband {
+                    mpv.set_property("deband-iterations", deband.iterations);
+                    mpv.set_property("deband-threshold", deband.threshold);
+                    mpv.set_property("deband-range", deband.range);
+                    mpv.set_property("deband-grain", deband.grain);
+                }
+            }
+            None => {
+                mpv.set_property("glsl-shaders", "");
+                mpv.set_property("scale", match_video_upscale(SETTINGS.mpv_video_scale()));
+                mpv.set_property("deband", SETTINGS.mpv_deband());
+            }
+        }
+        self.set_shader_profile(
+            profile
+                .map(|profile| profile.name.clone())
+                .unwrap_or_default(),
+        );
+    }
+
     pub fn press_key(&self, key: u32, state: gtk::gdk::ModifierType) {
         self.imp().mpv.press_key(key, state)
     }
@@ -227,6 +340,14 @@ impl MPVGLArea {
         self.imp().mpv.set_speed(value)
     }
 
+    pub fn speed(&self) -> f64 {
+        self.imp().mpv.speed()
+    }
+
+    pub fn sub_delay(&self) -> f64 {
+        self.imp().mpv.sub_delay()
+    }
+
     pub fn set_volume(&self, value: i64) {
         self.imp().mpv.set_volume(value)
     }
@@ -0,0 +1,202 @@
+use anyhow::{
+    anyhow,
+    Result,
+};
+
+use super::page::extract_url;
+use crate::{
+    client::{
+        emby_client::EMBY_CLIENT,
+        structs::{
+            Back,
+            TICKS_PER_SECOND,
+        },
+    },
+    ui::{
+        provider::tu_item::TuItem,
+        widgets::item_utils::make_video_version_choice_from_matcher,
+    },
+    utils::spawn_tokio,
+};
+
+/// One file of a multi-part movie.
+#[derive(Debug, Clone)]
+pub struct Part {
+    pub back: Back,
+    /// Absolute streaming url.
+    pub url: String,
+    /// Seconds.
+    pub duration: f64,
+    /// Saved playback position in seconds.
+    pub position: f64,
+}
+
+/// A multi-part movie played as one logical item through an mpv EDL
+/// timeline, positions outside of this module are always global.
+#[derive(Debug, Clone, Default)]
+pub struct MultiPart {
+    pub parts: Vec<Part>,
+}
+
+impl MultiPart {
+    pub fn edl(&self) -> String {
+        let segments = self
+            .parts
+            .iter()
+            .map(|part| format!("%{}%{}", part.url.len(), part.url))
+            .collect::<Vec<_>>();
+        format!("edl://{}", segments.join(";"))
+    }
+
+    pub fn duration(&self) -> f64 {
+        self.parts.iter().map(|part| part.duration).sum()
+    }
+
+    /// Global position where part `index` starts.
+    pub fn offset(&self, index: usize) -> f64 {
+        self.parts
+            .iter()
+            .take(index)
+            .map(|part| part.duration)
+            .sum()
+    }
+
+    /// Part index and position within that part for a global `position`.
+    pub fn locate(&self, position: f64) -> (usize, f64) {
+        let mut start = 0.0;
+        for (index, part) in self.parts.iter().enumerate() {
+            if position < start + part.duration || index + 1 == self.parts.len() {
+                return (index, (position - start).max(0.0));
+            }
+            start += part.duration;
+        }
+        (0, position)
+    }
+
+    /// Global position of the furthest part that was left unfinished.
+    pub fn resume_position(&self) -> f64 {
+        self.parts
+            .iter()
+            .enumerate()
+            .rev()
+            .find(|(_, part)| part.position > 0.0)
+            .map_or(0.0, |(index, part)| self.offset(index) + part.position)
+    }
+
+    /// Resume percentage of the whole timeline.
+    pub fn resume_percentage(&self) -> f64 {
+        let duration = self.duration();
+        if duration <= 0.0 {
+            return 0.0;
+        }
+        self.resume_position() / duration * 100.0
+    }
+}
+
+/// The timeline of `item` when it is a multi-part movie, `url` and `back` are
+/// its own already resolved file.
+pub async fn load_parts(
+    item: &TuItem, url: &str, back: Option<Back>, matcher: Option<String>,
+) -> Result<Option<MultiPart>> {
+    let Some(back) = back.filter(|_| item.part_count() > 1) else {
+        return Ok(None);
+    };
+
+    let first = Part {
+        back,
+        url: EMBY_CLIENT.get_streaming_url(url),
+        duration: item.run_time_ticks() as f64 / TICKS_PER_SECOND,
+        position: item.playback_position_ticks() as f64 / TICKS_PER_SECOND,
+    };
+    let id = item.id();
+    let multipart =
+        spawn_tokio(async move { fetch_parts(&id, first, matcher.as_deref()).await }).await?;
+    Ok(Some(multipart))
+}
+
+/// Completes `first`, the part that is already resolved, with the additional
+/// parts of item `id` in the version `matcher` picks.
+async fn fetch_parts(id: &str, first: Part, matcher: Option<&str>) -> Result<MultiPart> {
+    let additional = EMBY_CLIENT.get_additional(id).await?;
+
+    let mut parts = vec![first];
+    for item in additional.items {
+        let playback = EMBY_CLIENT.get_playbackinfo(&item.id).await?;
+        let source = match matcher {
+            Some(matcher) => {
+                let names = playback
+                    .media_sources
+                    .iter()
+                    .map(|source| source.name.clone())
+                    .collect();
+                make_video_version_choice_from_matcher(names, matcher)
+                    .and_then(|index| playback.media_sources.get(index))
+            }
+            None => playback.media_sources.first(),
+        }
+        .ok_or_else(|| anyhow!("No media sources found"))?;
+        let url = extract_url(source).ok_or_else(|| anyhow!("No media sources found"))?;
+
+        let run_time_ticks = source.run_time_ticks.or(item.run_time_ticks);
+        let position_ticks = item
+            .user_data
+            .as_ref()
+            .and_then(|user_data| user_data.playback_position_ticks);
+
+        parts.push(Part {
+            back: Back {
+                id: item.id,
+                playsessionid: playback.play_session_id,
+                mediasourceid: source.id.clone(),
+                tick: 0,
+                start_tick: chrono::Utc::now().timestamp() as u64,
+                play_method: None,
+                paused: false,
+            },
+            url: EMBY_CLIENT.get_streaming_url(&url),
+            duration: run_time_ticks.unwrap_or_default() as f64 / TICKS_PER_SECOND,
+            position: position_ticks.unwrap_or_default() as f64 / TICKS_PER_SECOND,
+        });
+    }
+
+    Ok(MultiPart { parts })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn part(url: &str, duration: f64, position: f64) -> Part {
+        Part {
+            back: Back {
+                id: url.to_string(),
+                playsessionid: None,
+                mediasourceid: url.to_string(),
+                tick: 0,
+                start_tick: 0,
+                play_method: None,
+                paused: false,
+            },
+            url: url.to_string(),
+            duration,
+            position,
+        }
+    }
+
+    #[test]
+    fn test_timeline() {
+        let multipart = MultiPart {
+            parts: vec![
+                part("http://a/1", 100.0, 0.0),
+                part("http://a/22", 50.0, 20.0),
+            ],
+        };
+        assert_eq!(multipart.edl(), "edl://%10%http://a/1;%11%http://a/22");
+        assert_eq!(multipart.duration(), 150.0);
+        assert_eq!(multipart.locate(30.0), (0, 30.0));
+        assert_eq!(multipart.locate(120.0), (1, 20.0));
+        assert_eq!(multipart.locate(200.0), (1, 100.0));
+        assert_eq!(multipart.resume_position(), 120.0);
+        assert_eq!(multipart.resume_percentage(), 80.0);
+    }
+}
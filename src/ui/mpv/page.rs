@@ -1,3 +1,5 @@
+use std::rc::Rc;
+
 use adw::prelude::*;
 use gettextrs::gettext;
 use glib::Object;
@@ -12,7 +14,19 @@ use gtk::{
 use url::Url;
 
 use super::{
+    audio_filters::AudioFilters,
+    clip_export::{
+        ClipExport,
+        ClipFormat,
+        ExportEvent,
+    },
     mpvglarea::MPVGLArea,
+    multipart::{
+        load_parts,
+        MultiPart,
+    },
+    pip_window::MPVPipWindow,
+    syncplay::SyncPlay,
     tsukimi_mpv::{
         ListenEvent,
         MpvTrack,
@@ -31,6 +45,7 @@ use crate::{
         },
         error::UserFacingError,
         structs::{
+            ticks,
             Back,
             MediaSource,
         },
@@ -38,28 +53,76 @@ use crate::{
     close_on_error,
     toast,
     ui::{
-        models::SETTINGS,
+        models::{
+            keybindings,
+            languages::mpv_language_list,
+            screenshots,
+            shader_profiles::ShaderProfiles,
+            sleep_timer::{
+                SleepMode,
+                SleepState,
+            },
+            track_memory::{
+                local_sub_title,
+                TrackChoice,
+                TrackMemory,
+            },
+            SETTINGS,
+        },
+        mpris::{
+            self,
+            mpris,
+            Metadata,
+            MprisPlayer,
+            PlaybackStatus,
+        },
         provider::tu_item::TuItem,
         widgets::{
             check_row::CheckRow,
             item_utils::{
                 make_subtitle_version_choice,
+                make_subtitle_version_choice_from_memory,
                 make_video_version_choice_from_matcher,
             },
+            screenshot_gallery::ScreenshotGallery,
             song_widget::format_duration,
+            subtitle_search_dialog::SubtitleSearchDialog,
             window::Window,
         },
     },
     utils::{
+        get_image_with_cache,
         spawn,
         spawn_g_timeout,
         spawn_tokio,
+        spawn_tokio_without_await,
     },
 };
 
 const MIN_MOTION_TIME: i64 = 100000;
-const PREV_CHAPTER_KEYVAL: u32 = 65366;
-const NEXT_CHAPTER_KEYVAL: u32 = 65365;
+const MPRIS_NAME: &str = "video";
+const SEEK_LONG_STEP: i64 = 60;
+const VOLUME_STEP: f64 = 2.0;
+const SPEED_STEP: f64 = 1.1;
+const SUB_DELAY_STEP: f64 = 0.1;
+const SUB_FILE_EXTENSIONS: &[&str] = &["srt", "ass", "ssa", "vtt", "sub", "sup"];
+
+#[derive(Clone, Copy, PartialEq, Eq)]
+enum TrackType {
+    Audio,
+    Sub,
+    SecondarySub,
+}
+
+impl TrackType {
+    fn property(self) -> &'static str {
+        match self {
+            TrackType::Audio => "aid",
+            TrackType::Sub => "sid",
+            TrackType::SecondarySub => "secondary-sid",
+        }
+    }
+}
 
 mod imp {
 
@@ -82,10 +145,15 @@ mod imp {
     use crate::{
         client::structs::Back,
         ui::{
-            models::SETTINGS,
+            models::{
+                track_memory::TrackChoice,
+                SETTINGS,
+            },
             mpv::{
                 menu_actions::MenuActions,
                 mpvglarea::MPVGLArea,
+                pip_window::MPVPipWindow,
+                syncplay::SyncPlay,
                 video_scale::VideoScale,
             },
             provider::tu_item::TuItem,
@@ -105,6 +173,8 @@ mod imp {
         #[property(get, set = Self::set_paused)]
         pub paused: Cell<bool>,
         #[template_child]
+        pub video_overlay: TemplateChild<gtk::Overlay>,
+        #[template_child]
         pub video: TemplateChild<MPVGLArea>,
         #[template_child]
         pub bottom_revealer: TemplateChild<gtk::Revealer>,
@@ -119,6 +189,8 @@ mod imp {
         #[template_child]
         pub duration_label: TemplateChild<gtk::Label>,
         #[template_child]
+        pub sleep_label: TemplateChild<gtk::Label>,
+        #[template_child]
         pub spinner: TemplateChild<adw::Spinner>,
         #[template_child]
         pub loading_box: TemplateChild<gtk::Box>,
@@ -140,9 +212,14 @@ mod imp {
         pub sub_listbox: TemplateChild<gtk::ListBox>,
         #[template_child]
         pub audio_listbox: TemplateChild<gtk::ListBox>,
+        #[template_child]
+        pub secondary_sub_listbox: TemplateChild<gtk::ListBox>,
         pub timeout: RefCell<Option<glib::source::SourceId>>,
         pub back_timeout: RefCell<Option<glib::source::SourceId>>,
         pub back: RefCell<Option<Back>>,
+        /// Set while a multi-part movie plays as one timeline.
+        pub parts: RefCell<Option<MultiPart>>,
+        pub part_index: Cell<usize>,
         pub x: RefCell<f64>,
         pub y: RefCell<f64>,
         pub last_motion_time: RefCell<i64>,
@@ -158,6 +235,20 @@ mod imp {
         pub current_episode_list: RefCell<Vec<TuItem>>,
 
         pub video_version_matcher: RefCell<Option<String>>,
+
+        pub track_choice: RefCell<TrackChoice>,
+        pub track_choice_changed: Cell<bool>,
+        pub track_choice_pending: Cell<bool>,
+
+        pub art_url: RefCell<Option<String>>,
+
+        pub pip_window: RefCell<Option<MPVPipWindow>>,
+
+        pub syncplay: SyncPlay,
+
+        pub sleep: SleepState,
+        /// The sleep timer reported playback as stopped.
+        pub sleep_stopped: Cell<bool>,
     }
 
     #[glib::object_subclass]
@@ -204,6 +295,49 @@ mod imp {
                     mpv.on_playlist_clicked();
                 },
             );
+            klass.install_action("mpv.screenshot", None, move |mpv, _action, _parameter| {
+                mpv.take_screenshot();
+            });
+            klass.install_action(
+                "mpv.show-screenshots",
+                None,
+                move |mpv, _action, _parameter| {
+                    mpv.show_screenshots();
+                },
+            );
+            klass.install_action("mpv.ab-loop", None, move |mpv, _action, _parameter| {
+                mpv.cycle_ab_loop();
+            });
+            klass.install_action("mpv.pip", None, move |mpv, _action, _parameter| {
+                mpv.enter_pip();
+            });
+            klass.install_action_async(
+                "mpv.export-clip",
+                Some(glib::VariantTy::STRING),
+                |mpv, _action, parameter| async move {
+                    let Some(extension) = parameter.and_then(|p| p.get::<String>()) else {
+                        return;
+                    };
+                    mpv.export_clip(&extension).await;
+                },
+            );
+            klass.install_action(
+                "mpv.sleep-timer",
+                Some(glib::VariantTy::INT32),
+                move |mpv, _action, parameter| {
+                    let Some(target) = parameter.and_then(|p| p.get::<i32>()) else {
+                        return;
+                    };
+                    mpv.start_sleep_timer(target);
+                },
+            );
+            klass.install_action(
+                "mpv.sleep-timer-cancel",
+                None,
+                move |mpv, _action, _parameter| {
+                    mpv.cancel_sleep_timer();
+                },
+            );
             klass.install_action_async(
                 "mpv.next-video",
                 None,
@@ -248,8 +382,12 @@ mod imp {
             self.video_scale.set_player(Some(&self.video.get()));
 
             let obj = self.obj();
+            self.syncplay.set_page(&obj);
 
             obj.set_popover();
+            obj.set_drop_target();
+            obj.action_set_enabled("mpv.export-clip", false);
+            obj.action_set_enabled("mpv.sleep-timer-cancel", false);
 
             obj.connect_root_notify(|obj| {
                 if let Some(window) = obj.root().and_downcast::<gtk::Window>() {
@@ -324,9 +462,13 @@ impl MPVPage {
     #[allow(clippy::too_many_arguments)]
     pub fn play(
         &self, url: &str, suburi: Option<&str>, item: TuItem, episode_list: Vec<TuItem>,
-        back: Option<Back>, percentage: f64, matcher: Option<String>,
+        back: Option<Back>, multipart: Option<MultiPart>, percentage: f64, matcher: Option<String>,
     ) {
-        let url = url.to_owned();
+        // A multi-part movie plays as one EDL timeline
+        let url = match &multipart {
+            Some(multipart) => multipart.edl(),
+            None => url.to_owned(),
+        };
         let suburi = suburi.map(|s| s.to_owned());
         let name = if let Some(series_name) = item.series_name() {
             format!(
@@ -346,15 +488,33 @@ impl MPVPage {
             .mpv
             .set_property("force-media-title", name.clone());
         self.imp().video_scale.reset_scale();
+        self.set_ab_loop(None, None);
         self.imp().video_version_matcher.replace(matcher);
+        self.imp()
+            .track_choice
+            .replace(TrackMemory::get(&item.id(), item.series_id().as_deref()).unwrap_or_default());
+        self.imp().track_choice_changed.set(false);
+        self.imp().track_choice_pending.set(true);
+        self.imp().art_url.replace(None);
+        self.update_art_url(item.id());
+        self.apply_default_shader_profile(item.id());
         self.imp().current_video.replace(Some(item));
         self.imp().current_episode_list.replace(episode_list);
         self.imp().back.replace(back);
+        let part_index = multipart.as_ref().map_or(0, |multipart| {
+            multipart
+                .locate(multipart.duration() * percentage / 100.0)
+                .0
+        });
+        self.imp().parts.replace(multipart);
+        self.imp().part_index.set(part_index);
+        mpris().set_player(Rc::new(self.clone()));
         spawn_g_timeout(glib::clone!(
             #[weak(rename_to = obj)]
             self,
             async move {
                 obj.load_config();
+                obj.apply_track_choice();
                 let imp = obj.imp();
                 imp.spinner.set_visible(true);
                 imp.loading_box.set_visible(true);
@@ -367,19 +527,80 @@ impl MPVPage {
         ));
     }
 
+    fn update_art_url(&self, id: String) {
+        spawn(glib::clone!(
+            #[weak(rename_to = obj)]
+            self,
+            async move {
+                let path = get_image_with_cache(id, "Primary".to_string(), None)
+                    .await
+                    .unwrap_or_default();
+                obj.imp().art_url.replace(mpris::art_url(&path));
+                mpris().changed(MPRIS_NAME, &["Metadata"]);
+            }
+        ));
+    }
+
+    /// Once any default is assigned, every video starts with the profile of
+    /// its library or collection type, or with none.
+    fn apply_default_shader_profile(&self, id: String) {
+        if ShaderProfiles::load().defaults.is_empty() {
+            return;
+        }
+        spawn(glib::clone!(
+            #[weak(rename_to = obj)]
+            self,
+            async move {
+                let library =
+                    match spawn_tokio(async move { EMBY_CLIENT.get_ancestors(&id).await }).await {
+                        Ok(ancestors) => ancestors
+                            .into_iter()
+                            .find(|item| item.item_type == "CollectionFolder"),
+                        Err(e) => {
+                            tracing::warn!("Failed to get library of item: {}", e);
+                            None
+                        }
+                    };
+                let profiles = ShaderProfiles::load();
+                let profile = profiles.default_for(
+                    library.as_ref().map(|library| library.id.as_str()),
+                    library
+                        .as_ref()
+                        .and_then(|library| library.collection_type.as_deref()),
+                );
+                obj.imp().video.apply_shader_profile(profile);
+            }
+        ));
+    }
+
     fn set_audio_and_video_tracks_dropdown(&self, value: MpvTracks) {
         let imp = self.imp();
-        self.bind_tracks::<true>(value.audio_tracks, &imp.audio_listbox.get());
-        self.bind_tracks::<false>(value.sub_tracks, &imp.sub_listbox.get());
+        if !value.sub_tracks.is_empty()
+            && imp.track_choice_pending.get()
+            && self.apply_track_choice_to_tracks(&value)
+        {
+            imp.track_choice_pending.set(false);
+        }
+        self.bind_tracks(
+            TrackType::Audio,
+            value.audio_tracks,
+            &imp.audio_listbox.get(),
+        );
+        self.bind_tracks(
+            TrackType::SecondarySub,
+            value.sub_tracks.clone(),
+            &imp.secondary_sub_listbox.get(),
+        );
+        self.bind_tracks(TrackType::Sub, value.sub_tracks, &imp.sub_listbox.get());
     }
 
     // TODO: Use GAction instead of listening to each button
-    fn bind_tracks<const A: bool>(&self, tracks: Vec<MpvTrack>, listbox: &gtk::ListBox) {
+    fn bind_tracks(&self, track_type: TrackType, tracks: Vec<MpvTrack>, listbox: &gtk::ListBox) {
         while let Some(row) = listbox.first_child() {
             listbox.remove(&row);
         }
 
-        let track_id = self.imp().video.get_track_id(if A { "aid" } else { "sid" });
+        let track_id = self.imp().video.get_track_id(track_type.property());
 
         let row = CheckRow::new();
         row.set_title("None");
@@ -391,7 +612,8 @@ impl MPVPage {
             #[weak(rename_to = obj)]
             self,
             move |_| {
-                obj.set_vsid::<A>(0);
+                obj.set_vsid(track_type, 0);
+                obj.remember_track(track_type, None);
             }
         ));
         listbox.append(&row);
@@ -410,41 +632,167 @@ impl MPVPage {
                 #[weak(rename_to = obj)]
                 self,
                 move |_| {
-                    obj.set_vsid::<A>(track.id);
+                    obj.set_vsid(track_type, track.id);
+                    obj.remember_track(track_type, Some(&track));
                 }
             ));
             listbox.append(&row);
         }
     }
 
-    fn set_vsid<const A: bool>(&self, track_id: i64) {
+    fn set_vsid(&self, track_type: TrackType, track_id: i64) {
         let track = if track_id == 0 {
             TrackSelection::None
         } else {
             TrackSelection::Track(track_id)
         };
 
-        if A {
-            self.imp().video.set_aid(track);
-        } else {
-            self.imp().video.set_sid(track);
+        let video = &self.imp().video;
+        match track_type {
+            TrackType::Audio => video.set_aid(track),
+            TrackType::Sub => video.set_sid(track),
+            TrackType::SecondarySub => video.set_secondary_sid(track),
+        }
+    }
+
+    fn remember_track(&self, track_type: TrackType, track: Option<&MpvTrack>) {
+        let imp = self.imp();
+        let known = |s: &String| (s != "unknown").then(|| s.to_owned());
+        {
+            let mut choice = imp.track_choice.borrow_mut();
+            match track_type {
+                TrackType::Audio => {
+                    choice.audio_lang = track.and_then(|t| known(&t.lang));
+                }
+                TrackType::Sub => {
+                    choice.sub_off = track.is_none();
+                    choice.sub_lang = track.and_then(|t| known(&t.lang));
+                    choice.sub_title = track.and_then(|t| known(&t.title));
+                }
+                TrackType::SecondarySub => {
+                    choice.secondary_sub_lang = track.and_then(|t| known(&t.lang));
+                    choice.secondary_sub_title = track.and_then(|t| known(&t.title));
+                }
+            }
+        }
+        imp.track_choice_changed.set(true);
+        imp.track_choice_pending.set(false);
+    }
+
+    fn apply_track_choice(&self) {
+        let imp = self.imp();
+        let choice = imp.track_choice.borrow().clone();
+        let video = &imp.video;
+
+        if let Some(lang) = choice.audio_lang.as_ref() {
+            let mut langs = SETTINGS.mpv_audio_preferred_langs();
+            langs.insert(0, lang.to_owned());
+            video.set_property("alang", mpv_language_list(&langs));
+        }
+        if let Some(lang) = choice.sub_lang.as_ref() {
+            let mut langs = SETTINGS.mpv_subtitle_preferred_langs();
+            langs.insert(0, lang.to_owned());
+            video.set_property("slang", mpv_language_list(&langs));
+        }
+        video.set_property("sid", if choice.sub_off { "no" } else { "auto" });
+        video.set_property("secondary-sid", "no");
+        video.set_property("sub-delay", choice.sub_delay.unwrap_or(0.0));
+        if let Some(speed) = choice.speed {
+            imp.speed_spin.set_value(speed);
+        }
+    }
+
+    // alang/slang only match languages, so pick the remembered titles once the track list is known.
+    // Returns false while a remembered track is missing, local files are added after the video.
+    fn apply_track_choice_to_tracks(&self, tracks: &MpvTracks) -> bool {
+        let imp = self.imp();
+        let choice = imp.track_choice.borrow().clone();
+
+        let find_track = |title: Option<&String>, lang: Option<&String>| {
+            if title.is_none() && lang.is_none() {
+                return None;
+            }
+            let matches = |wanted: Option<&String>, value: &String| match wanted {
+                Some(wanted) => wanted == value,
+                None => true,
+            };
+            tracks
+                .sub_tracks
+                .iter()
+                .find(|track| matches(title, &track.title) && matches(lang, &track.lang))
+        };
+
+        let mut settled = true;
+
+        if !choice.sub_off && imp.suburl.borrow().is_none() && choice.sub_title.is_some() {
+            match find_track(choice.sub_title.as_ref(), choice.sub_lang.as_ref()) {
+                Some(track) => imp.video.set_sid(TrackSelection::Track(track.id)),
+                None => settled = false,
+            }
         }
+
+        if choice.secondary_sub_title.is_some() || choice.secondary_sub_lang.is_some() {
+            match find_track(
+                choice.secondary_sub_title.as_ref(),
+                choice.secondary_sub_lang.as_ref(),
+            ) {
+                Some(track) => imp.video.set_secondary_sid(TrackSelection::Track(track.id)),
+                None => settled = false,
+            }
+        }
+
+        settled
+    }
+
+    fn save_track_choice(&self) {
+        let imp = self.imp();
+        let Some(item) = imp.current_video.borrow().clone() else {
+            return;
+        };
+
+        let mut choice = imp.track_choice.borrow().clone();
+        let sub_delay = imp.video.sub_delay();
+        let speed = imp.video.speed();
+
+        if choice.sub_delay.unwrap_or(0.0) != sub_delay {
+            choice.sub_delay = (sub_delay != 0.0).then_some(sub_delay);
+            imp.track_choice_changed.set(true);
+        }
+        if choice.speed.unwrap_or(1.0) != speed {
+            choice.speed = (speed != 1.0).then_some(speed);
+            imp.track_choice_changed.set(true);
+        }
+
+        if !imp.track_choice_changed.replace(false) {
+            return;
+        }
+
+        TrackMemory::set(&item.id(), item.series_id().as_deref(), choice);
     }
 
     async fn load_video(&self, offset: isize) {
         toast!(self, gettext("Loading Video..."));
 
+        self.save_track_choice();
+
         if self.paused() {
             self.imp().video.pause();
         }
 
-        let Some(current_video) = self.imp().current_video.borrow().clone() else {
+        let Some(next_item) = self.adjacent_video(offset) else {
+            toast!(self, gettext("No more videos found"));
+            self.on_stop_clicked();
             return;
         };
 
-        let video_list = self.imp().current_episode_list.borrow().clone();
+        self.in_play_item(next_item).await;
+    }
+
+    fn adjacent_video(&self, offset: isize) -> Option<TuItem> {
+        let current_video = self.imp().current_video.borrow().clone()?;
+        let video_list = self.imp().current_episode_list.borrow();
 
-        let next_item = video_list.iter().enumerate().find_map(|(i, item)| {
+        video_list.iter().enumerate().find_map(|(i, item)| {
             // Don't use id() here, because the same video maybe have different id
             if item.name() == current_video.name() {
                 let new_index = (i as isize + offset) as usize;
@@ -452,15 +800,7 @@ impl MPVPage {
             } else {
                 None
             }
-        });
-
-        let Some(next_item) = next_item else {
-            toast!(self, gettext("No more videos found"));
-            self.on_stop_clicked();
-            return;
-        };
-
-        self.in_play_item(next_item).await;
+        })
     }
 
     pub async fn in_play_item(&self, item: TuItem) {
@@ -507,19 +847,26 @@ impl MPVPage {
         let media_streams = &media_source.media_streams;
         let media_source_id = media_source.id.clone();
 
-        let mut lang_list = Vec::new();
+        let mut sub_streams = Vec::new();
         let mut indices = Vec::new();
 
         for (index, stream) in media_streams.iter().enumerate() {
-            if stream.stream_type == "Subtitle" {
-                if let Some(title) = stream.display_title.as_ref() {
-                    lang_list.push(title.clone());
-                    indices.push(index);
-                }
+            if stream.stream_type == "Subtitle" && stream.display_title.is_some() {
+                sub_streams.push(stream);
+                indices.push(index);
             }
         }
 
-        let suburi = if let Some(choice_index) = make_subtitle_version_choice(lang_list) {
+        let track_choice = TrackMemory::get(&item.id(), item.series_id().as_deref());
+
+        let sub_choice = match track_choice.as_ref() {
+            Some(choice) if choice.sub_off || choice.local_sub_selected() => None,
+            Some(choice) => make_subtitle_version_choice_from_memory(&sub_streams, choice)
+                .or_else(|| make_subtitle_version_choice(&sub_streams)),
+            None => make_subtitle_version_choice(&sub_streams),
+        };
+
+        let suburi = if let Some(choice_index) = sub_choice {
             if let Some(&index) = indices.get(choice_index) {
                 if let Some(stream) = media_streams.get(index) {
                     if stream.delivery_url.is_none() && stream.is_external {
@@ -569,6 +916,17 @@ impl MPVPage {
             mediasourceid: media_source_id.to_string(),
             tick: 0,
             start_tick: glib::DateTime::now_local().unwrap().to_unix() as u64,
+            play_method: None,
+            paused: false,
+        };
+
+        let matcher = self.imp().video_version_matcher.borrow().clone();
+        let multipart = match load_parts(&item, &url, Some(back.clone()), matcher).await {
+            Ok(multipart) => multipart,
+            Err(e) => {
+                toast!(self, e.to_user_facing());
+                None
+            }
         };
 
         self.play(
@@ -577,6 +935,7 @@ impl MPVPage {
             item.clone(),
             video_list,
             Some(back),
+            multipart,
             0.0,
             None,
         );
@@ -613,11 +972,18 @@ impl MPVPage {
                         ListenEvent::Duration(value) => {
                             obj.update_duration(value);
                         }
-                        ListenEvent::Seek | ListenEvent::PausedForCache(true) => {
+                        ListenEvent::Seek => {
                             obj.update_seeking(true);
+                            obj.imp().syncplay.on_seek();
+                        }
+                        ListenEvent::PausedForCache(value) => {
+                            obj.update_seeking(value);
+                            obj.imp().syncplay.on_buffering(value);
                         }
-                        ListenEvent::PausedForCache(false) | ListenEvent::PlaybackRestart => {
+                        ListenEvent::PlaybackRestart => {
                             obj.update_seeking(false);
+                            mpris().seeked(MPRIS_NAME, obj.position());
+                            obj.imp().syncplay.on_playback_restart();
                         }
                         ListenEvent::Eof(value) => {
                             obj.on_end_file(value);
@@ -627,6 +993,7 @@ impl MPVPage {
                         }
                         ListenEvent::Pause(value) => {
                             obj.on_pause_update(value);
+                            obj.imp().syncplay.on_pause(value);
                         }
                         ListenEvent::CacheSpeed(value) => {
                             obj.on_cache_speed_update(value);
@@ -649,6 +1016,9 @@ impl MPVPage {
                         ListenEvent::DemuxerCacheTime(value) => {
                             obj.on_cache_time_update(value);
                         }
+                        ListenEvent::AudioChannels(value) => {
+                            obj.on_audio_channels(value);
+                        }
                     }
                 }
             }
@@ -666,21 +1036,33 @@ impl MPVPage {
         self.imp().video_scale.set_cache_end_time(value);
     }
 
+    /// Dialogue boost only applies to tracks with a centre channel.
+    fn on_audio_channels(&self, value: i64) {
+        let filters = AudioFilters {
+            surround: value > 2,
+            ..AudioFilters::from_settings()
+        };
+        self.imp().video.set_property("af", filters.chain());
+    }
+
     fn update_duration(&self, value: f64) {
         let imp = self.imp();
         imp.video_scale.set_range(0.0, value);
         imp.duration_label.set_text(&format_duration(value as i64));
         imp.video_scale.update_timeout();
+        mpris().changed(MPRIS_NAME, &["Metadata"]);
     }
 
     fn speed_cb(&self, value: f64) {
         let imp = self.imp();
         imp.speed_spin.set_value(value);
+        mpris().changed(MPRIS_NAME, &["Rate"]);
     }
 
     fn volume_cb(&self, value: i64) {
         let imp = self.imp();
         imp.volume_spin.set_value(value as f64);
+        mpris().changed(MPRIS_NAME, &["Volume"]);
     }
 
     #[template_callback]
@@ -700,6 +1082,11 @@ impl MPVPage {
         if let Some(suburl) = imp.suburl.borrow().as_ref() {
             imp.video.add_sub(suburl);
         }
+        for path in &imp.track_choice.borrow().local_subs {
+            if std::path::Path::new(path).exists() {
+                imp.video.add_sub_file(path, &local_sub_title(path), false);
+            }
+        }
         self.update_timeout();
         self.handle_callback(BackType::Start);
     }
@@ -721,6 +1108,11 @@ impl MPVPage {
             #[weak(rename_to = obj)]
             self,
             async move {
+                if value == 0 && obj.sleeps_at_end_of_item() {
+                    obj.cancel_sleep_timer();
+                    obj.on_stop_clicked();
+                    return;
+                }
                 if value == 0 {
                     match SETTINGS.mpv_action_after_video_end() {
                         0 => obj.on_next_video().await,
@@ -738,12 +1130,16 @@ impl MPVPage {
 
     fn on_pause_update(&self, value: bool) {
         if !value {
+            if self.imp().sleep_stopped.replace(false) {
+                self.handle_callback(BackType::Start);
+            }
             self.update_timeout();
         } else {
             self.remove_timeout();
         }
 
         self.set_paused(value);
+        mpris().changed(MPRIS_NAME, &["PlaybackStatus"]);
     }
 
     fn on_cache_speed_update(&self, value: i64) {
@@ -917,7 +1313,16 @@ impl MPVPage {
 
     #[template_callback]
     fn on_stop_clicked(&self) {
+        if let Some(pip) = self.take_video_from_pip() {
+            pip.close();
+        }
+        let syncplay = self.imp().syncplay.clone();
+        if syncplay.in_group() {
+            spawn(async move { syncplay.leave().await });
+        }
+        self.cancel_sleep_timer();
         self.handle_callback(BackType::Stop);
+        self.save_track_choice();
         self.remove_timeout();
 
         self.imp().video_scale.remove_timeout();
@@ -932,6 +1337,7 @@ impl MPVPage {
             .unwrap();
         window.imp().stack.set_visible_child_name("main");
         window.allow_suspend();
+        mpris().unset_player(MPRIS_NAME);
 
         spawn_g_timeout(glib::clone!(
             #[weak(rename_to = obj)]
@@ -948,28 +1354,118 @@ impl MPVPage {
         ));
     }
 
+    fn start_sleep_timer(&self, target: i32) {
+        let Some(mode) = SleepMode::from_target(target) else {
+            return;
+        };
+        self.cancel_sleep_timer();
+
+        self.imp().sleep.start(
+            mode,
+            glib::clone!(
+                #[weak(rename_to = obj)]
+                self,
+                #[upgrade_or]
+                glib::ControlFlow::Break,
+                move || obj.on_sleep_tick()
+            ),
+        );
+        self.on_sleep_tick();
+    }
+
+    pub fn cancel_sleep_timer(&self) {
+        let imp = self.imp();
+        imp.sleep
+            .cancel(|volume| imp.video.set_volume(volume as i64));
+        self.show_sleep_remaining(None);
+    }
+
+    fn show_sleep_remaining(&self, remaining: Option<f64>) {
+        let imp = self.imp();
+        if let Some(remaining) = remaining {
+            imp.sleep_label
+                .set_text(&format_duration(remaining.ceil() as i64));
+        }
+        imp.sleep_label.set_visible(remaining.is_some());
+        self.action_set_enabled("mpv.sleep-timer-cancel", remaining.is_some());
+    }
+
+    fn sleeps_at_end_of_item(&self) -> bool {
+        self.imp().sleep.mode() == Some(SleepMode::EndOfItem)
+    }
+
+    fn on_sleep_tick(&self) -> glib::ControlFlow {
+        let imp = self.imp();
+        let remaining = imp.sleep.tick(
+            |_| imp.video_scale.adjustment().upper() - imp.video.position(),
+            || imp.volume_spin.value(),
+            |volume| imp.video.set_volume(volume as i64),
+            || {
+                imp.video.imp().mpv.pause(true);
+                // Ends the session on the server, resuming starts a new one
+                self.handle_callback(BackType::Stop);
+                self.remove_timeout();
+                imp.sleep_stopped.set(true);
+            },
+        );
+        self.show_sleep_remaining(remaining);
+        if remaining.is_some() {
+            glib::ControlFlow::Continue
+        } else {
+            glib::ControlFlow::Break
+        }
+    }
+
     pub fn update_position_callback(&self) -> glib::ControlFlow {
         self.handle_callback(BackType::Back);
         glib::ControlFlow::Continue
     }
 
     fn handle_callback(&self, backtype: BackType) {
-        let position = &self.imp().video.position();
-        let back = self.imp().back.borrow();
+        let imp = self.imp();
+        let mut position = imp.video.position();
 
         // close window when vo=gpu-next will set position to 0, so we need to ignore it
-        if position < &9.0 && (backtype != BackType::Start && backtype != BackType::Stop) {
+        if position < 9.0 && (backtype != BackType::Start && backtype != BackType::Stop) {
             return;
         }
 
-        if let Some(back) = back.as_ref() {
-            let duration = *position as u64 * 10000000;
-            let mut back = back.clone();
-            back.tick = duration;
-            crate::utils::spawn_tokio_without_await(async move {
-                let _ = EMBY_CLIENT.position_back(&back, backtype).await;
-            });
+        let Some(mut back) = imp.back.borrow().clone() else {
+            return;
+        };
+
+        // Multi-part movies report each part on its own, the position is global
+        if let Some(multipart) = imp.parts.borrow().as_ref() {
+            let (index, local) = multipart.locate(position);
+            let previous = imp.part_index.replace(index);
+            back = multipart.parts[index].back.clone();
+            position = local;
+            if previous != index {
+                let mut stopped = multipart.parts[previous].back.clone();
+                stopped.tick = if index > previous {
+                    ticks(multipart.parts[previous].duration) as u64
+                } else {
+                    0
+                };
+                // The part that is now playing starts its own session
+                let mut started = back;
+                started.tick = ticks(position) as u64;
+                let stop = backtype == BackType::Stop;
+                spawn_tokio_without_await(async move {
+                    let _ = EMBY_CLIENT.position_back(&stopped, BackType::Stop).await;
+                    let _ = EMBY_CLIENT.position_back(&started, BackType::Start).await;
+                    if stop {
+                        let _ = EMBY_CLIENT.position_back(&started, BackType::Stop).await;
+                    }
+                });
+                return;
+            }
         }
+
+        back.tick = ticks(position) as u64;
+        spawn_tokio_without_await(async move {
+            let _ = EMBY_CLIENT.position_back(&back, backtype).await;
+        });
     }
 
     pub fn update_timeout(&self) {
@@ -996,6 +1492,10 @@ impl MPVPage {
 
     #[template_callback]
     fn right_click_cb(&self, _n: i32, x: f64, y: f64) {
+        // The popover belongs to the page, not to the PiP window
+        if self.imp().pip_window.borrow().is_some() {
+            return;
+        }
         if let Some(popover) = self.imp().popover.borrow().as_ref() {
             popover.set_pointing_to(Some(&Rectangle::new(x as i32, y as i32, 0, 0)));
             popover.popup();
@@ -1035,6 +1535,11 @@ impl MPVPage {
             return;
         }
 
+        if let Some(action) = keybindings::player_action(key, state) {
+            self.activate_key_action(action.name);
+            return;
+        }
+
         self.imp().video.press_key(key, state)
     }
 
@@ -1048,9 +1553,354 @@ impl MPVPage {
             return;
         }
 
+        if keybindings::player_action(key, state).is_some() {
+            return;
+        }
+
         self.imp().video.release_key(key, state)
     }
 
+    fn activate_key_action(&self, name: &str) {
+        let video = &self.imp().video;
+        match name {
+            "toggle-pause" => video.pause(),
+            "seek-forward" => self.on_forward(),
+            "seek-backward" => self.on_backward(),
+            "seek-forward-long" => video.seek_forward(SEEK_LONG_STEP),
+            "seek-backward-long" => video.seek_backward(SEEK_LONG_STEP),
+            "volume-up" => video.add_property("volume", VOLUME_STEP),
+            "volume-down" => video.add_property("volume", -VOLUME_STEP),
+            "mute" => video.cycle_property("mute"),
+            "speed-up" => video.multiply_property("speed", SPEED_STEP),
+            "speed-down" => video.multiply_property("speed", 1.0 / SPEED_STEP),
+            "speed-reset" => video.set_speed(1.0),
+            "sub-delay-increase" => video.add_property("sub-delay", SUB_DELAY_STEP),
+            "sub-delay-decrease" => video.add_property("sub-delay", -SUB_DELAY_STEP),
+            "cycle-audio" => video.cycle_property("aid"),
+            "cycle-sub" => video.cycle_property("sid"),
+            "chapter-previous" => self.chapter_prev(),
+            "chapter-next" => self.chapter_next(),
+            "previous-item" => spawn(glib::clone!(
+                #[weak(rename_to = obj)]
+                self,
+                async move {
+                    obj.on_previous_video().await;
+                }
+            )),
+            "next-item" => spawn(glib::clone!(
+                #[weak(rename_to = obj)]
+                self,
+                async move {
+                    obj.on_next_video().await;
+                }
+            )),
+            "fullscreen" => {
+                // Not there while the page is out of the main window
+                if let Err(e) = self.activate_action("win.toggle-fullscreen", None) {
+                    tracing::warn!("Failed to toggle fullscreen: {}", e);
+                }
+            }
+            "screenshot" => self.take_screenshot(),
+            "ab-loop" => self.cycle_ab_loop(),
+            "pip" => self.enter_pip(),
+            "toggle-playlist" => self.on_playlist_clicked(),
+            "toggle-control-sidebar" => self.on_sidebar_clicked(),
+            _ => {}
+        }
+    }
+
+    #[template_callback]
+    pub fn take_screenshot(&self) {
+        let imp = self.imp();
+        let Some(item) = imp.current_video.borrow().clone() else {
+            return;
+        };
+
+        let path = screenshots::new_screenshot_path(&item.id(), &item.name(), imp.video.position());
+        if let Some(dir) = path.parent() {
+            if let Err(e) = std::fs::create_dir_all(dir) {
+                toast!(self, e.to_string());
+                return;
+            }
+        }
+
+        imp.video
+            .screenshot_to_file(&path.to_string_lossy(), SETTINGS.mpv_screenshot_subtitles());
+        toast!(self, gettext("Screenshot saved"));
+    }
+
+    /// First press sets A, the second B, the third clears the loop.
+    fn cycle_ab_loop(&self) {
+        let imp = self.imp();
+        let position = imp.video.position();
+        match imp.video_scale.ab_loop() {
+            (Some(_), Some(_)) => {
+                self.set_ab_loop(None, None);
+                toast!(self, gettext("A-B loop cleared"));
+            }
+            (Some(a), None) if position != a => {
+                self.set_ab_loop(Some(a.min(position)), Some(a.max(position)));
+                toast!(self, gettext("A-B loop set"));
+            }
+            _ => {
+                self.set_ab_loop(Some(position), None);
+                toast!(self, gettext("Loop start set"));
+            }
+        }
+    }
+
+    fn set_ab_loop(&self, a: Option<f64>, b: Option<f64>) {
+        let imp = self.imp();
+        imp.video.set_ab_loop(a, b);
+        imp.video_scale.set_ab_loop(a, b);
+        self.action_set_enabled("mpv.export-clip", a.is_some() && b.is_some());
+    }
+
+    async fn export_clip(&self, extension: &str) {
+        let imp = self.imp();
+        let (Some(start), Some(end)) = imp.video_scale.ab_loop() else {
+            return;
+        };
+        let Some(url) = imp.video.path() else {
+            return;
+        };
+        let Some(item) = imp.current_video.borrow().clone() else {
+            return;
+        };
+
+        let window = self.root().and_downcast::<gtk::Window>();
+        let dialog = gtk::FileDialog::builder()
+            .modal(true)
+            .title(gettext("Export Clip"))
+            .initial_name(format!(
+                "{}.{}",
+                screenshots::file_stem(&item.name(), start),
+                extension
+            ))
+            .build();
+        let Ok(file) = dialog.save_future(window.as_ref()).await else {
+            return;
+        };
+        let Some(output) = file.path() else {
+            return;
+        };
+
+        let title = gettext("Exporting clip…");
+        let progress_toast = adw::Toast::builder().title(&title).timeout(0).build();
+        crate::_add_toast!(self, progress_toast.clone());
+
+        let rx = ClipExport {
+            url,
+            format: ClipFormat::for_path(&output),
+            output,
+            start,
+            end,
+            http_proxy: proxy_url(),
+        }
+        .spawn();
+
+        while let Ok(event) = rx.recv_async().await {
+            match event {
+                ExportEvent::Progress(fraction) => {
+                    progress_toast.set_title(&format!("{} {}%", title, (fraction * 100.0) as u32));
+                }
+                ExportEvent::Done => {
+                    progress_toast.dismiss();
+                    toast!(self, gettext("Clip exported"));
+                    break;
+                }
+                ExportEvent::Error(e) => {
+                    progress_toast.dismiss();
+                    toast!(self, e);
+                    break;
+                }
+            }
+        }
+    }
+
+    /// Move the video into a small window, so the library can be browsed
+    /// while it keeps playing.
+    fn enter_pip(&self) {
+        let imp = self.imp();
+        if imp.pip_window.borrow().is_some() {
+            return;
+        }
+        let Some(window) = self.root().and_downcast::<Window>() else {
+            return;
+        };
+        if window.is_fullscreen() {
+            window.unfullscreen();
+        }
+
+        let video = imp.video.get();
+        imp.video_overlay.set_child(None::<&gtk::Widget>);
+        let pip = MPVPipWindow::new(self, &video);
+        pip.set_application(window.application().as_ref());
+        pip.present();
+        imp.pip_window.replace(Some(pip));
+
+        window.imp().stack.set_visible_child_name("main");
+    }
+
+    /// Back to the full view, playback goes on.
+    pub fn leave_pip(&self) {
+        let Some(pip) = self.take_video_from_pip() else {
+            return;
+        };
+        pip.close();
+
+        if let Some(window) = self.root().and_downcast::<Window>() {
+            window.imp().stack.set_visible_child_name("mpv");
+            window.present();
+        }
+    }
+
+    /// Closing the PiP window stops playback, like leaving the full view.
+    pub fn on_pip_closed(&self) {
+        if self.take_video_from_pip().is_some() {
+            self.on_stop_clicked();
+        }
+    }
+
+    fn take_video_from_pip(&self) -> Option<MPVPipWindow> {
+        let imp = self.imp();
+        let pip = imp.pip_window.take()?;
+        if let Some(video) = pip.take_video() {
+            imp.video_overlay.set_child(Some(&video));
+        }
+        Some(pip)
+    }
+
+    fn show_screenshots(&self) {
+        let Some(item) = self.imp().current_video.borrow().clone() else {
+            return;
+        };
+        let dialog = ScreenshotGallery::new(&item.id());
+        dialog.present(Some(self));
+    }
+
+    fn set_drop_target(&self) {
+        let drop_target =
+            gtk::DropTarget::new(gio::File::static_type(), gtk::gdk::DragAction::COPY);
+
+        drop_target.connect_drop(glib::clone!(
+            #[weak(rename_to = obj)]
+            self,
+            #[upgrade_or]
+            false,
+            move |_drop_target, value, _x, _y| {
+                let Ok(file) = value.get::<gio::File>() else {
+                    return false;
+                };
+                obj.add_sub_file(&file)
+            }
+        ));
+
+        self.add_controller(drop_target);
+    }
+
+    #[template_callback]
+    async fn on_add_sub_file_clicked(&self) {
+        self.imp().menu_popover.popdown();
+
+        let filter = gtk::FileFilter::new();
+        filter.set_name(Some(&gettext("Subtitles")));
+        for extension in SUB_FILE_EXTENSIONS {
+            filter.add_suffix(extension);
+        }
+        let model = gio::ListStore::new::<gtk::FileFilter>();
+        model.append(&filter);
+
+        let window = self.root().and_downcast::<gtk::Window>();
+        let filedialog = gtk::FileDialog::builder()
+            .modal(true)
+            .title(gettext("Select a subtitle file"))
+            .filters(&model)
+            .build();
+
+        match filedialog.open_future(window.as_ref()).await {
+            Ok(file) => {
+                self.add_sub_file(&file);
+            }
+            Err(_) => toast!(self, gettext("No file selected")),
+        }
+    }
+
+    #[template_callback]
+    fn on_search_subs_clicked(&self) {
+        let imp = self.imp();
+        imp.menu_popover.popdown();
+
+        let Some((id, media_source_id)) = imp
+            .back
+            .borrow()
+            .as_ref()
+            .map(|back| (back.id.clone(), back.mediasourceid.clone()))
+        else {
+            return;
+        };
+
+        let dialog = SubtitleSearchDialog::new(&id, &media_source_id);
+        dialog.connect_subtitle_downloaded(glib::clone!(
+            #[weak(rename_to = obj)]
+            self,
+            move |_, path| {
+                if path.is_empty() {
+                    toast!(
+                        obj,
+                        gettext("The subtitle will be available the next time this plays")
+                    );
+                    return;
+                }
+                obj.imp()
+                    .video
+                    .add_sub(&EMBY_CLIENT.get_streaming_url(&path));
+            }
+        ));
+        dialog.present(Some(self));
+    }
+
+    /// Load a local subtitle file and remember it for the current item.
+    fn add_sub_file(&self, file: &gio::File) -> bool {
+        let Some(path) = file.path() else {
+            toast!(self, gettext("Only local subtitle files are supported"));
+            return false;
+        };
+
+        let supported = path
+            .extension()
+            .and_then(|extension| extension.to_str())
+            .is_some_and(|extension| {
+                SUB_FILE_EXTENSIONS.contains(&extension.to_lowercase().as_str())
+            });
+        if !supported {
+            toast!(self, gettext("Unsupported subtitle file"));
+            return false;
+        }
+
+        let imp = self.imp();
+        let path = path.to_string_lossy().to_string();
+        let title = local_sub_title(&path);
+
+        imp.video.add_sub_file(&path, &title, true);
+
+        {
+            let mut choice = imp.track_choice.borrow_mut();
+            if !choice.local_subs.contains(&path) {
+                choice.local_subs.push(path);
+            }
+            choice.sub_off = false;
+            choice.sub_lang = None;
+            choice.sub_title = Some(title);
+        }
+        imp.track_choice_changed.set(true);
+        imp.track_choice_pending.set(false);
+        self.save_track_choice();
+
+        toast!(self, gettext("Subtitle file added"));
+        true
+    }
+
     pub fn set_popover(&self) {
         let imp = self.imp();
         let builder = Builder::from_resource("/moe/tsuna/tsukimi/ui/mpv_menu.ui");
@@ -1081,46 +1931,136 @@ impl MPVPage {
     }
 
     pub fn chapter_prev(&self) {
-        self.key_pressed_cb(PREV_CHAPTER_KEYVAL, gtk::gdk::ModifierType::empty());
+        self.imp().video.add_property("chapter", -1.0);
     }
 
     pub fn chapter_next(&self) {
-        self.key_pressed_cb(NEXT_CHAPTER_KEYVAL, gtk::gdk::ModifierType::empty());
+        self.imp().video.add_property("chapter", 1.0);
     }
 
     pub fn load_config(&self) {
         let imp = self.imp();
         let mpv = &imp.video.imp().mpv;
-        if let Some(uri) = crate::client::proxy::get_proxy_settings() {
-            let url =
-                Url::parse(&uri).map_or_else(|_| format!("http://{}", uri), |_| uri.to_string());
+        if let Some(url) = proxy_url() {
             mpv.set_property("http-proxy", url);
         }
-        match SETTINGS.mpv_audio_preferred_lang() {
-            0 => mpv.set_property("alang", ""), // clear alang
-            1 => mpv.set_property("alang", "eng"),
-            2 => mpv.set_property("alang", "chs"),
-            3 => mpv.set_property("alang", "jpn"),
-            4 => mpv.set_property("alang", "chi"),
-            5 => mpv.set_property("alang", "ara"),
-            6 => mpv.set_property("alang", "nob"),
-            7 => mpv.set_property("alang", "por"),
-            8 => mpv.set_property("alang", "fre"),
-            _ => unreachable!(),
-        }
-        match SETTINGS.mpv_subtitle_preferred_lang() {
-            0 => mpv.set_property("slang", ""), // clear slang
-            1 => mpv.set_property("slang", "eng"),
-            2 => mpv.set_property("slang", "chs"),
-            3 => mpv.set_property("slang", "jpn"),
-            4 => mpv.set_property("slang", "chi"),
-            5 => mpv.set_property("slang", "ara"),
-            6 => mpv.set_property("slang", "nob"),
-            7 => mpv.set_property("slang", "por"),
-            8 => mpv.set_property("alang", "fre"),
-            _ => unreachable!(),
+        mpv.set_property(
+            "alang",
+            mpv_language_list(&SETTINGS.mpv_audio_preferred_langs()),
+        );
+        mpv.set_property(
+            "slang",
+            mpv_language_list(&SETTINGS.mpv_subtitle_preferred_langs()),
+        );
+    }
+}
+
+fn proxy_url() -> Option<String> {
+    let uri = crate::client::proxy::get_proxy_settings()?;
+    Some(Url::parse(&uri).map_or_else(|_| format!("http://{}", uri), |_| uri.to_string()))
+}
+
+impl MprisPlayer for MPVPage {
+    fn name(&self) -> &'static str {
+        MPRIS_NAME
+    }
+
+    fn metadata(&self) -> Metadata {
+        let imp = self.imp();
+        let Some(item) = imp.current_video.borrow().clone() else {
+            return Metadata::default();
+        };
+        let duration = imp.video_scale.adjustment().upper();
+        Metadata {
+            id: item.id(),
+            title: item.name(),
+            series: item.series_name(),
+            length: (duration > 0.0).then_some((duration * 1_000_000.0) as i64),
+            art_url: imp.art_url.borrow().clone(),
+            ..Default::default()
+        }
+    }
+
+    fn playback_status(&self) -> PlaybackStatus {
+        if self.paused() {
+            PlaybackStatus::Paused
+        } else {
+            PlaybackStatus::Playing
         }
     }
+
+    fn position(&self) -> i64 {
+        (self.imp().video.position() * 1_000_000.0) as i64
+    }
+
+    fn volume(&self) -> f64 {
+        self.imp().volume_spin.value() / 100.0
+    }
+
+    fn set_volume(&self, volume: f64) {
+        self.imp().volume_spin.set_value(volume * 100.0);
+    }
+
+    fn rate(&self) -> f64 {
+        self.imp().speed_spin.value()
+    }
+
+    fn set_rate(&self, rate: f64) {
+        self.imp().speed_spin.set_value(rate);
+    }
+
+    fn rate_range(&self) -> (f64, f64) {
+        let adjustment = self.imp().speed_spin.adjustment();
+        (adjustment.lower(), adjustment.upper())
+    }
+
+    fn play(&self) {
+        self.imp().video.imp().mpv.pause(false);
+    }
+
+    fn pause(&self) {
+        self.imp().video.imp().mpv.pause(true);
+    }
+
+    fn play_pause(&self) {
+        self.on_play_pause_clicked();
+    }
+
+    fn stop(&self) {
+        self.on_stop_clicked();
+    }
+
+    fn next(&self) {
+        spawn(glib::clone!(
+            #[weak(rename_to = obj)]
+            self,
+            async move {
+                obj.on_next_video().await;
+            }
+        ));
+    }
+
+    fn previous(&self) {
+        spawn(glib::clone!(
+            #[weak(rename_to = obj)]
+            self,
+            async move {
+                obj.on_previous_video().await;
+            }
+        ));
+    }
+
+    fn set_position(&self, position: i64) {
+        self.imp().video.set_position(position as f64 / 1_000_000.0);
+    }
+
+    fn can_go_next(&self) -> bool {
+        self.adjacent_video(1).is_some()
+    }
+
+    fn can_go_previous(&self) -> bool {
+        self.adjacent_video(-1).is_some()
+    }
 }
 
 pub fn direct_stream_url(source: &MediaSource) -> Option<String> {
@@ -0,0 +1,131 @@
+use adw::{
+    prelude::*,
+    subclass::prelude::*,
+};
+use gtk::{
+    glib,
+    template_callbacks,
+};
+
+use super::{
+    mpvglarea::MPVGLArea,
+    page::MPVPage,
+};
+
+mod imp {
+    use glib::subclass::InitializingObject;
+    use gtk::CompositeTemplate;
+
+    use super::*;
+
+    #[derive(Debug, Default, CompositeTemplate)]
+    #[template(resource = "/moe/tsuna/tsukimi/ui/mpv_pip_window.ui")]
+    pub struct MPVPipWindow {
+        #[template_child]
+        pub overlay: TemplateChild<gtk::Overlay>,
+        #[template_child]
+        pub controls_revealer: TemplateChild<gtk::Revealer>,
+        #[template_child]
+        pub play_pause_button: TemplateChild<gtk::Button>,
+        pub page: glib::WeakRef<MPVPage>,
+    }
+
+    #[glib::object_subclass]
+    impl ObjectSubclass for MPVPipWindow {
+        const NAME: &'static str = "MPVPipWindow";
+        type Type = super::MPVPipWindow;
+        type ParentType = adw::Window;
+
+        fn class_init(klass: &mut Self::Class) {
+            klass.bind_template();
+            klass.bind_template_instance_callbacks();
+        }
+
+        fn instance_init(obj: &InitializingObject<Self>) {
+            obj.init_template();
+        }
+    }
+
+    impl ObjectImpl for MPVPipWindow {}
+
+    impl WidgetImpl for MPVPipWindow {}
+
+    impl WindowImpl for MPVPipWindow {
+        fn close_request(&self) -> glib::Propagation {
+            if let Some(page) = self.page.upgrade() {
+                page.on_pip_closed();
+            }
+            self.parent_close_request()
+        }
+    }
+
+    impl AdwWindowImpl for MPVPipWindow {}
+}
+
+glib::wrapper! {
+    /// Small window the video moves into while browsing the library.
+    ///
+    /// GTK can't keep a window above others by itself, the window menu of
+    /// the compositor (right click, or Alt+Space) offers "Always on Top".
+    pub struct MPVPipWindow(ObjectSubclass<imp::MPVPipWindow>)
+        @extends gtk::Widget, gtk::Window, adw::Window,
+        @implements gtk::Accessible, gtk::Buildable, gtk::ConstraintTarget, gtk::Native, gtk::Root, gtk::ShortcutManager;
+}
+
+#[template_callbacks]
+impl MPVPipWindow {
+    pub fn new(page: &MPVPage, video: &MPVGLArea) -> Self {
+        let obj: Self = glib::Object::new();
+        let imp = obj.imp();
+        imp.page.set(Some(page));
+        imp.overlay.set_child(Some(video));
+        page.bind_property("paused", &imp.play_pause_button.get(), "icon-name")
+            .transform_to(|_, paused: bool| {
+                Some(if paused {
+                    "media-playback-start-symbolic"
+                } else {
+                    "media-playback-pause-symbolic"
+                })
+            })
+            .sync_create()
+            .build();
+        obj
+    }
+
+    /// Hand the video back to the page.
+    pub fn take_video(&self) -> Option<MPVGLArea> {
+        let overlay = &self.imp().overlay;
+        let video = overlay.child().and_downcast::<MPVGLArea>();
+        overlay.set_child(None::<&gtk::Widget>);
+        video
+    }
+
+    #[template_callback]
+    fn on_enter(&self) {
+        self.imp().controls_revealer.set_reveal_child(true);
+    }
+
+    #[template_callback]
+    fn on_leave(&self) {
+        self.imp().controls_revealer.set_reveal_child(false);
+    }
+
+    #[template_callback]
+    fn on_play_pause_clicked(&self) {
+        if let Some(page) = self.imp().page.upgrade() {
+            page.imp().video.pause();
+        }
+    }
+
+    #[template_callback]
+    fn on_full_view_clicked(&self) {
+        if let Some(page) = self.imp().page.upgrade() {
+            page.leave_pip();
+        }
+    }
+
+    #[template_callback]
+    fn on_stop_clicked(&self) {
+        self.close();
+    }
+}
@@ -0,0 +1,738 @@
+use chrono::Utc;
+use gettextrs::gettext;
+use gtk::{
+    glib,
+    prelude::*,
+    subclass::prelude::*,
+};
+
+use super::page::MPVPage;
+use crate::{
+    client::{
+        emby_client::{
+            DEVICE_ID,
+            EMBY_CLIENT,
+        },
+        error::UserFacingError,
+        structs::{
+            ticks,
+            SessionInfo,
+            SyncPlayGroup,
+            TICKS_PER_SECOND,
+        },
+        syncplay::{
+            correction,
+            expected_position,
+            CommandType,
+            Correction,
+            GroupUpdate,
+            PlayQueueUpdate,
+            PlaystateRequest,
+            SendCommand,
+            TimeSync,
+        },
+        websocket::{
+            self,
+            ServerMessage,
+        },
+    },
+    toast,
+    ui::provider::tu_item::TuItem,
+    utils::{
+        spawn,
+        spawn_tokio,
+        spawn_tokio_without_await,
+    },
+};
+
+/// Seconds between drift checks while the group is playing.
+const DRIFT_INTERVAL: u32 = 1;
+
+/// A group, or on Emby another session, that can be joined.
+#[derive(Debug, Clone)]
+pub struct GroupEntry {
+    pub id: String,
+    pub name: String,
+    pub members: String,
+}
+
+mod imp {
+    use std::{
+        cell::{
+            Cell,
+            RefCell,
+        },
+        sync::OnceLock,
+    };
+
+    use glib::subclass::Signal;
+
+    use super::*;
+
+    #[derive(Default, glib::Properties)]
+    #[properties(wrapper_type = super::SyncPlay)]
+    pub struct SyncPlay {
+        /// Name of the joined group, empty when not in one.
+        #[property(get)]
+        pub group_name: RefCell<String>,
+        pub group_id: RefCell<Option<String>>,
+        pub participants: RefCell<Vec<String>>,
+        pub page: glib::WeakRef<MPVPage>,
+        pub socket: RefCell<Option<glib::JoinHandle<()>>>,
+        pub time_sync: Cell<TimeSync>,
+        pub playlist_item_id: RefCell<String>,
+        /// Last `Unpause`, drift is measured against it.
+        pub last_command: RefCell<Option<SendCommand>>,
+        pub command_source: RefCell<Option<glib::SourceId>>,
+        pub drift_source: RefCell<Option<glib::SourceId>>,
+        /// Speed before drift correction changed it.
+        pub base_speed: Cell<Option<f64>>,
+        /// Pause state and seek we caused ourselves, so they aren't sent
+        /// back to the group.
+        pub expected_pause: Cell<Option<bool>>,
+        pub expected_seek: Cell<bool>,
+        /// Position to seek to once the group's item has loaded.
+        pub pending_position: Cell<Option<f64>>,
+        /// The group waits for us to report ready after loading or seeking.
+        pub waiting_ready: Cell<bool>,
+        /// Emby sessions our playback is mirrored to.
+        pub peers: RefCell<Vec<SessionInfo>>,
+        pub sessions: RefCell<Vec<SessionInfo>>,
+    }
+
+    #[glib::object_subclass]
+    impl ObjectSubclass for SyncPlay {
+        const NAME: &'static str = "SyncPlay";
+        type Type = super::SyncPlay;
+    }
+
+    #[glib::derived_properties]
+    impl ObjectImpl for SyncPlay {
+        fn signals() -> &'static [Signal] {
+            static SIGNALS: OnceLock<Vec<Signal>> = OnceLock::new();
+            SIGNALS.get_or_init(|| vec![Signal::builder("participants-changed").build()])
+        }
+    }
+}
+
+glib::wrapper! {
+    /// Watch-together sessions. Jellyfin has SyncPlay groups, on Emby we
+    /// mirror playback to other sessions with remote control commands.
+    pub struct SyncPlay(ObjectSubclass<imp::SyncPlay>);
+}
+
+impl Default for SyncPlay {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl SyncPlay {
+    pub fn new() -> Self {
+        glib::Object::new()
+    }
+
+    pub fn set_page(&self, page: &MPVPage) {
+        self.imp().page.set(Some(page));
+    }
+
+    fn page(&self) -> Option<MPVPage> {
+        self.imp().page.upgrade()
+    }
+
+    fn toast(&self, message: &str) {
+        if let Some(page) = self.page() {
+            toast!(page, message);
+        }
+    }
+
+    fn current_item_id(&self) -> Option<String> {
+        let page = self.page()?;
+        let id = page
+            .imp()
+            .current_video
+            .borrow()
+            .as_ref()
+            .map(|item| item.id());
+        id
+    }
+
+    pub fn in_group(&self) -> bool {
+        let imp = self.imp();
+        imp.group_id.borrow().is_some() || !imp.peers.borrow().is_empty()
+    }
+
+    pub fn participants(&self) -> Vec<String> {
+        let imp = self.imp();
+        if imp.group_id.borrow().is_some() {
+            return imp.participants.borrow().clone();
+        }
+        imp.peers.borrow().iter().map(session_title).collect()
+    }
+
+    pub fn connect_participants_changed<F: Fn(&Self) + 'static>(
+        &self, f: F,
+    ) -> glib::SignalHandlerId {
+        self.connect_closure(
+            "participants-changed",
+            true,
+            glib::closure_local!(move |obj: Self| {
+                f(&obj);
+            }),
+        )
+    }
+
+    fn set_group(&self, name: String) {
+        self.imp().group_name.replace(name);
+        self.notify_group_name();
+        self.emit_by_name::<()>("participants-changed", &[]);
+    }
+
+    async fn connect_socket(&self) -> bool {
+        if self.imp().socket.borrow().is_some() {
+            return true;
+        }
+
+        let receiver = match spawn_tokio(async {
+            let url = EMBY_CLIENT.websocket_url()?;
+            websocket::connect(url).await
+        })
+        .await
+        {
+            Ok(receiver) => receiver,
+            Err(e) => {
+                self.toast(&e.to_user_facing());
+                return false;
+            }
+        };
+
+        if !EMBY_CLIENT.is_jellyfin() {
+            let _ = spawn_tokio(async { EMBY_CLIENT.post_capabilities().await }).await;
+        }
+
+        let handle = glib::spawn_future_local(glib::clone!(
+            #[weak(rename_to = obj)]
+            self,
+            async move {
+                while let Ok(message) = receiver.recv_async().await {
+                    obj.on_message(message);
+                }
+                obj.imp().socket.take();
+                if obj.in_group() {
+                    obj.toast(&gettext("Lost connection to the watch-together session"));
+                    obj.clear_group();
+                }
+            }
+        ));
+        self.imp().socket.replace(Some(handle));
+        true
+    }
+
+    fn disconnect_socket(&self) {
+        if let Some(handle) = self.imp().socket.take() {
+            handle.abort();
+        }
+    }
+
+    fn on_message(&self, message: ServerMessage) {
+        match message.message_type.as_str() {
+            "SyncPlayGroupUpdate" => {
+                if let Ok(update) = serde_json::from_value::<GroupUpdate>(message.data) {
+                    self.on_group_update(update);
+                }
+            }
+            "SyncPlayCommand" => {
+                if let Ok(command) = serde_json::from_value::<SendCommand>(message.data) {
+                    self.on_command(command);
+                }
+            }
+            "Playstate" if !EMBY_CLIENT.is_jellyfin() => {
+                if let Ok(request) = serde_json::from_value::<PlaystateRequest>(message.data) {
+                    self.on_playstate(request);
+                }
+            }
+            _ => {}
+        }
+    }
+
+    pub async fn groups(&self) -> anyhow::Result<Vec<GroupEntry>> {
+        if EMBY_CLIENT.is_jellyfin() {
+            let groups = spawn_tokio(async { EMBY_CLIENT.syncplay_list().await }).await?;
+            return Ok(groups
+                .into_iter()
+                .map(|group| GroupEntry {
+                    id: group.group_id,
+                    name: group.group_name,
+                    members: group.participants.join(", "),
+                })
+                .collect());
+        }
+
+        let current = self.current_item_id();
+        let sessions = spawn_tokio(async { EMBY_CLIENT.get_sessions().await }).await?;
+        let sessions: Vec<SessionInfo> = sessions
+            .into_iter()
+            .filter(|session| {
+                session.supports_remote_control
+                    && session.device_id.as_deref() != Some(DEVICE_ID.as_str())
+                    && session.now_playing_item.as_ref().is_some_and(|item| {
+                        current.as_ref().map_or(true, |current| &item.id == current)
+                    })
+            })
+            .collect();
+        self.imp().sessions.replace(sessions.clone());
+
+        Ok(sessions
+            .iter()
+            .map(|session| GroupEntry {
+                id: session.id.clone(),
+                name: session_title(session),
+                members: session
+                    .now_playing_item
+                    .as_ref()
+                    .map(|item| item.name.clone())
+                    .unwrap_or_default(),
+            })
+            .collect())
+    }
+
+    pub async fn create_group(&self, name: &str) {
+        if !self.connect_socket().await {
+            return;
+        }
+        let name = name.to_string();
+        if let Err(e) = spawn_tokio(async move { EMBY_CLIENT.syncplay_new(&name).await }).await {
+            self.toast(&e.to_user_facing());
+        }
+    }
+
+    pub async fn join(&self, id: &str) {
+        if !self.connect_socket().await {
+            return;
+        }
+
+        if EMBY_CLIENT.is_jellyfin() {
+            let id = id.to_string();
+            if let Err(e) = spawn_tokio(async move { EMBY_CLIENT.syncplay_join(&id).await }).await {
+                self.toast(&e.to_user_facing());
+            }
+            return;
+        }
+
+        let imp = self.imp();
+        let Some(session) = imp.sessions.borrow().iter().find(|s| s.id == id).cloned() else {
+            return;
+        };
+        if imp.peers.borrow().iter().any(|peer| peer.id == session.id) {
+            return;
+        }
+        imp.peers.borrow_mut().push(session);
+        let name = self.participants().join(", ");
+        self.set_group(name);
+    }
+
+    pub async fn leave(&self) {
+        if self.imp().group_id.borrow().is_some() {
+            let _ = spawn_tokio(async { EMBY_CLIENT.syncplay_leave().await }).await;
+        }
+        self.clear_group();
+        self.disconnect_socket();
+    }
+
+    fn clear_group(&self) {
+        let imp = self.imp();
+        self.stop_drift_correction();
+        if let Some(source) = imp.command_source.take() {
+            source.remove();
+        }
+        imp.group_id.replace(None);
+        imp.participants.replace(Vec::new());
+        imp.peers.replace(Vec::new());
+        imp.last_command.replace(None);
+        imp.pending_position.set(None);
+        imp.waiting_ready.set(false);
+        self.set_group(String::new());
+    }
+
+    async fn sync_time(&self) {
+        let sent = Utc::now();
+        let Ok(time) = spawn_tokio(async { EMBY_CLIENT.get_utc_time().await }).await else {
+            return;
+        };
+        self.imp()
+            .time_sync
+            .set(TimeSync::new(sent, Utc::now(), &time));
+    }
+
+    fn on_group_update(&self, update: GroupUpdate) {
+        let imp = self.imp();
+        match update.update_type.as_str() {
+            "GroupJoined" => {
+                let Ok(group) = serde_json::from_value::<SyncPlayGroup>(update.data) else {
+                    return;
+                };
+                imp.group_id.replace(Some(group.group_id));
+                imp.participants.replace(group.participants);
+                self.set_group(group.group_name);
+                let idle = group.state == "Idle";
+                spawn(glib::clone!(
+                    #[weak(rename_to = obj)]
+                    self,
+                    async move {
+                        obj.sync_time().await;
+                        // A new group has nothing queued, start with what we watch
+                        if idle {
+                            obj.share_current_item().await;
+                        }
+                    }
+                ));
+            }
+            "UserJoined" | "UserLeft" => {
+                let Some(user) = update.data.as_str() else {
+                    return;
+                };
+                if update.update_type == "UserJoined" {
+                    imp.participants.borrow_mut().push(user.to_string());
+                    self.toast(&gettext("{} joined the group").replace("{}", user));
+                } else {
+                    imp.participants.borrow_mut().retain(|name| name != user);
+                    self.toast(&gettext("{} left the group").replace("{}", user));
+                }
+                self.emit_by_name::<()>("participants-changed", &[]);
+            }
+            "GroupLeft" | "NotInGroup" => self.clear_group(),
+            "GroupDoesNotExist" => {
+                self.toast(&gettext("The group no longer exists"));
+                self.clear_group();
+            }
+            "LibraryAccessDenied" => {
+                self.toast(&gettext("Someone in the group can't access this item"));
+            }
+            "PlayQueue" => {
+                if let Ok(queue) = serde_json::from_value::<PlayQueueUpdate>(update.data) {
+                    self.on_play_queue(queue);
+                }
+            }
+            _ => {}
+        }
+    }
+
+    async fn share_current_item(&self) {
+        let Some(page) = self.page() else {
+            return;
+        };
+        if page.imp().video.path().is_none() {
+            return;
+        }
+        let Some(id) = self.current_item_id() else {
+            return;
+        };
+        let position = ticks(page.imp().video.position());
+        if let Err(e) =
+            spawn_tokio(async move { EMBY_CLIENT.syncplay_set_new_queue(&id, position).await })
+                .await
+        {
+            self.toast(&e.to_user_facing());
+        }
+    }
+
+    fn on_play_queue(&self, queue: PlayQueueUpdate) {
+        let Some(playing) = queue.playing_item().cloned() else {
+            return;
+        };
+        let Some(page) = self.page() else {
+            return;
+        };
+        let imp = self.imp();
+        imp.playlist_item_id.replace(playing.playlist_item_id);
+        imp.waiting_ready.set(true);
+        self.set_paused(true);
+
+        let position = queue.start_position_ticks as f64 / TICKS_PER_SECOND;
+        if self.current_item_id().as_deref() == Some(playing.item_id.as_str())
+            && page.imp().video.path().is_some()
+        {
+            self.seek_to(position);
+            return;
+        }
+
+        imp.pending_position.set(Some(position));
+        spawn(glib::clone!(
+            #[weak]
+            page,
+            async move {
+                let id = playing.item_id;
+                match spawn_tokio(async move { EMBY_CLIENT.get_item_info(&id).await }).await {
+                    Ok(item) => page.in_play_item(TuItem::from_simple(&item, None)).await,
+                    Err(e) => toast!(page, e.to_user_facing()),
+                }
+            }
+        ));
+    }
+
+    /// Tells the group whether we can play, it waits for everyone.
+    fn report_ready(&self, ready: bool) {
+        let imp = self.imp();
+        if imp.group_id.borrow().is_none() {
+            return;
+        }
+        let Some(page) = self.page() else {
+            return;
+        };
+        let mpv = &page.imp().video.imp().mpv;
+        let position = ticks(mpv.position());
+        let is_playing = !mpv.paused();
+        let when = imp.time_sync.get().server_now();
+        let playlist_item_id = imp.playlist_item_id.borrow().clone();
+        spawn_tokio_without_await(async move {
+            EMBY_CLIENT
+                .syncplay_ready(ready, when, position, is_playing, &playlist_item_id)
+                .await
+        });
+    }
+
+    fn on_command(&self, command: SendCommand) {
+        let imp = self.imp();
+        if imp.group_id.borrow().as_deref() != Some(command.group_id.as_str()) {
+            return;
+        }
+        self.stop_drift_correction();
+        if let Some(source) = imp.command_source.take() {
+            source.remove();
+        }
+
+        let position = command
+            .position_ticks
+            .map(|ticks| ticks as f64 / TICKS_PER_SECOND);
+        match command.command {
+            CommandType::Unpause => {
+                let time_sync = imp.time_sync.get();
+                let delay = (time_sync.server_to_local(command.when) - Utc::now())
+                    .to_std()
+                    .unwrap_or_default();
+                let source = glib::timeout_add_local_once(
+                    delay,
+                    glib::clone!(
+                        #[weak(rename_to = obj)]
+                        self,
+                        move || {
+                            obj.imp().command_source.take();
+                            let expected = expected_position(&command, time_sync.server_now());
+                            obj.line_up(expected);
+                            obj.set_paused(false);
+                            obj.imp().last_command.replace(Some(command));
+                            obj.start_drift_correction();
+                        }
+                    ),
+                );
+                imp.command_source.replace(Some(source));
+            }
+            CommandType::Pause | CommandType::Stop => {
+                self.set_paused(true);
+                if let Some(position) = position {
+                    self.line_up(position);
+                }
+            }
+            CommandType::Seek => {
+                self.set_paused(true);
+                if let Some(position) = position {
+                    imp.waiting_ready.set(true);
+                    self.seek_to(position);
+                }
+            }
+        }
+    }
+
+    fn on_playstate(&self, request: PlaystateRequest) {
+        let Some(page) = self.page() else {
+            return;
+        };
+        match request.command.as_str() {
+            "Pause" | "Stop" => self.set_paused(true),
+            "Unpause" => self.set_paused(false),
+            "PlayPause" => self.set_paused(!page.imp().video.paused()),
+            "Seek" => {
+                if let Some(ticks) = request.seek_position_ticks {
+                    self.seek_to(ticks as f64 / TICKS_PER_SECOND);
+                }
+            }
+            _ => {}
+        }
+    }
+
+    fn set_paused(&self, pause: bool) {
+        let Some(page) = self.page() else {
+            return;
+        };
+        let mpv = &page.imp().video.imp().mpv;
+        if mpv.paused() == pause {
+            return;
+        }
+        self.imp().expected_pause.set(Some(pause));
+        mpv.pause(pause);
+    }
+
+    fn seek_to(&self, position: f64) {
+        let Some(page) = self.page() else {
+            return;
+        };
+        self.imp().expected_seek.set(true);
+        page.imp().video.imp().mpv.set_position(position);
+    }
+
+    /// Seeks only when noticeably off, small drift is left to the speed.
+    fn line_up(&self, position: f64) {
+        let Some(page) = self.page() else {
+            return;
+        };
+        if correction(page.imp().video.position(), position) != Correction::None {
+            self.seek_to(position);
+        }
+    }
+
+    fn start_drift_correction(&self) {
+        let source = glib::timeout_add_seconds_local(
+            DRIFT_INTERVAL,
+            glib::clone!(
+                #[weak(rename_to = obj)]
+                self,
+                #[upgrade_or]
+                glib::ControlFlow::Break,
+                move || {
+                    obj.correct_drift();
+                    glib::ControlFlow::Continue
+                }
+            ),
+        );
+        if let Some(source) = self.imp().drift_source.replace(Some(source)) {
+            source.remove();
+        }
+    }
+
+    fn stop_drift_correction(&self) {
+        if let Some(source) = self.imp().drift_source.take() {
+            source.remove();
+        }
+        self.reset_speed();
+    }
+
+    fn correct_drift(&self) {
+        let imp = self.imp();
+        let Some(command) = imp.last_command.borrow().clone() else {
+            return;
+        };
+        let Some(page) = self.page() else {
+            return;
+        };
+        let mpv = &page.imp().video.imp().mpv;
+        if mpv.paused() {
+            return;
+        }
+
+        let expected = expected_position(&command, imp.time_sync.get().server_now());
+        match correction(mpv.position(), expected) {
+            Correction::None => self.reset_speed(),
+            Correction::Speed(factor) => {
+                let speed = imp.base_speed.get().unwrap_or_else(|| mpv.speed());
+                imp.base_speed.set(Some(speed));
+                mpv.set_speed(speed * factor);
+            }
+            Correction::Seek(position) => {
+                self.reset_speed();
+                self.seek_to(position);
+            }
+        }
+    }
+
+    fn reset_speed(&self) {
+        let Some(speed) = self.imp().base_speed.take() else {
+            return;
+        };
+        if let Some(page) = self.page() {
+            page.imp().video.imp().mpv.set_speed(speed);
+        }
+    }
+
+    fn send_to_peers(&self, command: &'static str, position_ticks: Option<i64>) {
+        let ids: Vec<String> = self
+            .imp()
+            .peers
+            .borrow()
+            .iter()
+            .map(|peer| peer.id.clone())
+            .collect();
+        spawn_tokio_without_await(async move {
+            for id in ids {
+                let _ = EMBY_CLIENT
+                    .send_playstate_command(&id, command, position_ticks)
+                    .await;
+            }
+        });
+    }
+
+    pub fn on_pause(&self, paused: bool) {
+        let imp = self.imp();
+        if imp.expected_pause.take() == Some(paused) || !self.in_group() {
+            return;
+        }
+        if paused {
+            self.stop_drift_correction();
+        }
+
+        if imp.group_id.borrow().is_some() {
+            spawn_tokio_without_await(async move {
+                if paused {
+                    EMBY_CLIENT.syncplay_pause().await
+                } else {
+                    EMBY_CLIENT.syncplay_unpause().await
+                }
+            });
+        } else {
+            self.send_to_peers(if paused { "Pause" } else { "Unpause" }, None);
+        }
+    }
+
+    pub fn on_seek(&self) {
+        let imp = self.imp();
+        if imp.expected_seek.replace(false) || imp.waiting_ready.get() || !self.in_group() {
+            return;
+        }
+        let Some(page) = self.page() else {
+            return;
+        };
+        let position = ticks(page.imp().video.position());
+
+        if imp.group_id.borrow().is_some() {
+            spawn_tokio_without_await(async move { EMBY_CLIENT.syncplay_seek(position).await });
+        } else {
+            self.send_to_peers("Seek", Some(position));
+        }
+    }
+
+    pub fn on_playback_restart(&self) {
+        let imp = self.imp();
+        if let Some(position) = imp.pending_position.take() {
+            self.set_paused(true);
+            self.seek_to(position);
+            return;
+        }
+        if imp.waiting_ready.replace(false) {
+            self.set_paused(true);
+            self.report_ready(true);
+        }
+    }
+
+    pub fn on_buffering(&self, buffering: bool) {
+        if !self.imp().waiting_ready.get() {
+            self.report_ready(!buffering);
+        }
+    }
+}
+
+fn session_title(session: &SessionInfo) -> String {
+    let user = session.user_name.clone().unwrap_or_default();
+    match session.device_name.as_deref() {
+        Some(device) => format!("{} · {}", user, device),
+        None => user,
+    }
+}
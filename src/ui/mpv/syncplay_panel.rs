@@ -0,0 +1,244 @@
+use adw::{
+    prelude::*,
+    subclass::prelude::*,
+};
+use gettextrs::gettext;
+use gtk::{
+    glib,
+    template_callbacks,
+    CompositeTemplate,
+};
+
+use super::syncplay::SyncPlay;
+use crate::{
+    client::{
+        emby_client::EMBY_CLIENT,
+        error::UserFacingError,
+    },
+    toast,
+    utils::spawn,
+};
+
+mod imp {
+    use std::cell::RefCell;
+
+    use glib::subclass::InitializingObject;
+
+    use super::*;
+
+    #[derive(Debug, Default, CompositeTemplate, glib::Properties)]
+    #[template(resource = "/moe/tsuna/tsukimi/ui/mpv_syncplay_panel.ui")]
+    #[properties(wrapper_type = super::MPVSyncPlayPanel)]
+    pub struct MPVSyncPlayPanel {
+        #[property(get, set = Self::set_syncplay, explicit_notify, nullable)]
+        pub syncplay: glib::WeakRef<SyncPlay>,
+
+        #[template_child]
+        pub current_group: TemplateChild<adw::PreferencesGroup>,
+        #[template_child]
+        pub create_group: TemplateChild<adw::PreferencesGroup>,
+        #[template_child]
+        pub group_name_entry: TemplateChild<adw::EntryRow>,
+        #[template_child]
+        pub groups_group: TemplateChild<adw::PreferencesGroup>,
+
+        /// Rows added in code, by the group holding them.
+        pub rows: RefCell<Vec<(adw::PreferencesGroup, gtk::Widget)>>,
+    }
+
+    #[glib::object_subclass]
+    impl ObjectSubclass for MPVSyncPlayPanel {
+        const NAME: &'static str = "MPVSyncPlayPanel";
+        type Type = super::MPVSyncPlayPanel;
+        type ParentType = adw::Bin;
+
+        fn class_init(klass: &mut Self::Class) {
+            klass.bind_template();
+            klass.bind_template_instance_callbacks();
+        }
+
+        fn instance_init(obj: &InitializingObject<Self>) {
+            obj.init_template();
+        }
+    }
+
+    #[glib::derived_properties]
+    impl ObjectImpl for MPVSyncPlayPanel {
+        fn constructed(&self) {
+            self.parent_constructed();
+            self.obj().connect_map(|obj| obj.refresh());
+        }
+    }
+
+    impl WidgetImpl for MPVSyncPlayPanel {}
+    impl BinImpl for MPVSyncPlayPanel {}
+
+    impl MPVSyncPlayPanel {
+        fn set_syncplay(&self, syncplay: Option<&SyncPlay>) {
+            self.syncplay.set(syncplay);
+            let obj = self.obj();
+            if let Some(syncplay) = syncplay {
+                syncplay.connect_participants_changed(glib::clone!(
+                    #[weak]
+                    obj,
+                    move |_| obj.refresh()
+                ));
+            }
+            obj.notify_syncplay();
+        }
+    }
+}
+
+glib::wrapper! {
+    /// Player side panel to create, join and leave watch-together groups.
+    pub struct MPVSyncPlayPanel(ObjectSubclass<imp::MPVSyncPlayPanel>)
+        @extends gtk::Widget, adw::Bin, @implements gtk::Accessible;
+}
+
+impl Default for MPVSyncPlayPanel {
+    fn default() -> Self {
+        glib::Object::new()
+    }
+}
+
+#[template_callbacks]
+impl MPVSyncPlayPanel {
+    fn add_row(&self, group: &adw::PreferencesGroup, row: &impl IsA<gtk::Widget>) {
+        group.add(row);
+        self.imp()
+            .rows
+            .borrow_mut()
+            .push((group.clone(), row.clone().upcast()));
+    }
+
+    fn refresh(&self) {
+        spawn(glib::clone!(
+            #[weak(rename_to = obj)]
+            self,
+            async move {
+                obj.reload().await;
+            }
+        ));
+    }
+
+    async fn reload(&self) {
+        let Some(syncplay) = self.syncplay() else {
+            return;
+        };
+        let groups = syncplay.groups().await;
+
+        let imp = self.imp();
+        for (group, row) in imp.rows.take() {
+            group.remove(&row);
+        }
+
+        let in_group = syncplay.in_group();
+        let jellyfin = EMBY_CLIENT.is_jellyfin();
+        imp.current_group.set_visible(in_group);
+        imp.create_group.set_visible(jellyfin && !in_group);
+        imp.groups_group.set_visible(!(jellyfin && in_group));
+
+        if in_group {
+            let current_group = imp.current_group.get();
+            current_group.set_description(Some(&syncplay.group_name()));
+            for participant in syncplay.participants() {
+                let row = adw::ActionRow::builder()
+                    .title(&participant)
+                    .use_markup(false)
+                    .build();
+                row.add_prefix(&gtk::Image::from_icon_name("avatar-default-symbolic"));
+                self.add_row(&current_group, &row);
+            }
+        }
+
+        let groups_group = imp.groups_group.get();
+        if jellyfin {
+            groups_group.set_title(&gettext("Groups"));
+            groups_group.set_description(None);
+        } else {
+            groups_group.set_title(&gettext("Sessions"));
+            groups_group.set_description(Some(&gettext(
+                "Your pause, play and seek are mirrored to the sessions you join",
+            )));
+        }
+
+        let groups = match groups {
+            Ok(groups) => groups,
+            Err(e) => {
+                toast!(self, e.to_user_facing());
+                return;
+            }
+        };
+
+        if groups.is_empty() {
+            let title = if jellyfin {
+                gettext("No groups yet")
+            } else {
+                gettext("Nobody else is watching this")
+            };
+            let row = adw::ActionRow::builder().title(title).build();
+            self.add_row(&groups_group, &row);
+            return;
+        }
+
+        for group in groups {
+            let row = adw::ActionRow::builder()
+                .title(&group.name)
+                .subtitle(&group.members)
+                .use_markup(false)
+                .build();
+
+            let join = gtk::Button::builder()
+                .label(gettext("Join"))
+                .valign(gtk::Align::Center)
+                .css_classes(["suggested-action", "pill"])
+                .build();
+            join.connect_clicked(glib::clone!(
+                #[weak]
+                syncplay,
+                move |_| {
+                    let id = group.id.clone();
+                    spawn(async move {
+                        syncplay.join(&id).await;
+                    });
+                }
+            ));
+            row.add_suffix(&join);
+
+            self.add_row(&groups_group, &row);
+        }
+    }
+
+    #[template_callback]
+    fn on_refresh_clicked(&self) {
+        self.refresh();
+    }
+
+    #[template_callback]
+    async fn on_create_group(&self) {
+        let Some(syncplay) = self.syncplay() else {
+            return;
+        };
+        let entry = self.imp().group_name_entry.get();
+        let name = entry.text();
+        let name = if name.trim().is_empty() {
+            format!(
+                "{} {}",
+                EMBY_CLIENT.user_name.lock().unwrap(),
+                gettext("Group")
+            )
+        } else {
+            name.trim().to_string()
+        };
+        entry.set_text("");
+        syncplay.create_group(&name).await;
+    }
+
+    #[template_callback]
+    async fn on_leave_clicked(&self) {
+        let Some(syncplay) = self.syncplay() else {
+            return;
+        };
+        syncplay.leave().await;
+    }
+}
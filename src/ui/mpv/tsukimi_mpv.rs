@@ -32,7 +32,7 @@ use tracing::{
     warn,
 };
 
-#[derive(Debug)]
+#[derive(Debug, Clone)]
 pub struct MpvTrack {
     pub id: i64,
     pub title: String,
@@ -121,6 +121,7 @@ impl Default for TsukimiMPV {
             init.set_property("sub-scale", SETTINGS.mpv_subtitle_scale())?;
             init.set_property("hwdec", match_hwdec_interop(SETTINGS.mpv_hwdec()))?;
             init.set_property("scale", match_video_upscale(SETTINGS.mpv_video_scale()))?;
+            init.set_property("ab-loop-count", ab_loop_count(SETTINGS.mpv_ab_loop_count()))?;
             if SETTINGS.mpv_action_after_video_end() == 1 {
                 init.set_property("loop", "inf")?;
             } else {
@@ -130,6 +131,11 @@ impl Default for TsukimiMPV {
                 "audio-channels",
                 match_audio_channels(SETTINGS.mpv_audio_channel()),
             )?;
+            init.set_property("af", AudioFilters::from_settings().chain())?;
+            init.set_property(
+                "audio-pitch-correction",
+                SETTINGS.mpv_audio_pitch_correction(),
+            )?;
             Ok(())
         })
         .expect("Failed to create mpv instance");
@@ -187,6 +193,7 @@ pub enum ListenEvent {
     PausedForCache(bool),
     Shutdown,
     DemuxerCacheTime(i64),
+    AudioChannels(i64),
 }
 
 pub static MPV_EVENT_CHANNEL: Lazy<MPVEventChannel> = Lazy::new(|| {
@@ -219,6 +226,12 @@ impl TsukimiMPV {
         self.ctx.replace(Some(ctx));
     }
 
+    /// Free the render context, the GL context it was made with has to be
+    /// current.
+    pub fn disconnect_render_update(&self) {
+        self.ctx.replace(None);
+    }
+
     pub fn set_position(&self, value: f64) {
         self.set_property("time-pos", value);
     }
@@ -247,6 +260,12 @@ impl TsukimiMPV {
         self.command("sub-add", &[url, "select"]);
     }
 
+    /// Add a local subtitle file, titled so it can be found again in the track list.
+    pub fn add_sub_file(&self, path: &str, title: &str, select: bool) {
+        let flag = if select { "select" } else { "auto" };
+        self.command("sub-add", &[path, flag, title]);
+    }
+
     pub fn load_video(&self, url: &str) {
         self.command("loadfile", &[url, "replace"]);
     }
@@ -263,6 +282,14 @@ impl TsukimiMPV {
         self.set_property("speed", speed);
     }
 
+    pub fn speed(&self) -> f64 {
+        self.get_property("speed").unwrap_or(1.0)
+    }
+
+    pub fn sub_delay(&self) -> f64 {
+        self.get_property("sub-delay").unwrap_or(0.0)
+    }
+
     pub fn set_aid(&self, aid: TrackSelection) {
         self.set_property("aid", aid.to_string());
     }
@@ -271,6 +298,14 @@ impl TsukimiMPV {
         self.set_property("sid", sid.to_string());
     }
 
+    pub fn set_secondary_sid(&self, sid: TrackSelection) {
+        self.set_property("secondary-sid", sid.to_string());
+    }
+
+    pub fn set_vid(&self, vid: TrackSelection) {
+        self.set_property("vid", vid.to_string());
+    }
+
     pub fn seek_forward(&self, value: i64) {
         self.command("seek", &[&value.to_string()]);
     }
@@ -279,6 +314,39 @@ impl TsukimiMPV {
         self.command("seek", &[&(-value).to_string()]);
     }
 
+    pub fn add_property(&self, property: &str, value: f64) {
+        self.command("add", &[property, &value.to_string()]);
+    }
+
+    pub fn multiply_property(&self, property: &str, value: f64) {
+        self.command("multiply", &[property, &value.to_string()]);
+    }
+
+    pub fn cycle_property(&self, property: &str) {
+        self.command("cycle", &[property]);
+    }
+
+    pub fn screenshot_to_file(&self, path: &str, subtitles: bool) {
+        let flag = if subtitles { "subtitles" } else { "video" };
+        self.command("screenshot-to-file", &[path, flag]);
+    }
+
+    /// Loop between `a` and `b`, `None` clears the point.
+    pub fn set_ab_loop(&self, a: Option<f64>, b: Option<f64>) {
+        let point = |p: Option<f64>| p.map_or_else(|| "no".to_string(), |p| p.to_string());
+        self.set_property("ab-loop-a", point(a));
+        self.set_property("ab-loop-b", point(b));
+    }
+
+    /// `0` loops forever.
+    pub fn set_ab_loop_count(&self, count: i32) {
+        self.set_property("ab-loop-count", ab_loop_count(count));
+    }
+
+    pub fn path(&self) -> Option<String> {
+        self.get_property("path")
+    }
+
     pub fn press_key(&self, key: u32, state: gtk::gdk::ModifierType) {
         let keystr = get_full_keystr(key, state);
         if let Some(keystr) = keystr {
@@ -344,6 +412,12 @@ impl TsukimiMPV {
         });
     }
 
+    /// Channels of the playing audio track, 0 without one.
+    pub fn audio_channel_count(&self) -> i64 {
+        self.get_property("current-tracks/audio/demux-channel-count")
+            .unwrap_or(0)
+    }
+
     pub fn get_track_id(&self, type_: &str) -> i64 {
         let Some(track) = self.get_property::<String>(type_) else {
             return 0;
@@ -387,6 +461,14 @@ impl TsukimiMPV {
         event_context
             .observe_property("demuxer-cache-time", libmpv2::Format::Int64, 5)
             .unwrap();
+        // Changes with aid as well as with the file
+        event_context
+            .observe_property(
+                "current-tracks/audio/demux-channel-count",
+                libmpv2::Format::Int64,
+                6,
+            )
+            .unwrap();
         let event_thread_alive = self.event_thread_alive.clone();
         std::thread::Builder::new()
             .name("mpv event loop".into())
@@ -442,6 +524,13 @@ impl TsukimiMPV {
                                         .send(ListenEvent::DemuxerCacheTime(time));
                                 }
                             }
+                            "current-tracks/audio/demux-channel-count" => {
+                                if let PropertyData::Int64(channels) = change {
+                                    let _ = MPV_EVENT_CHANNEL
+                                        .tx
+                                        .send(ListenEvent::AudioChannels(channels));
+                                }
+                            }
                             _ => {}
                         },
                         Event::Seek { .. } => {
@@ -520,6 +609,14 @@ fn node_to_tracks(node: MpvNode) -> MpvTracks {
     }
 }
 
+fn ab_loop_count(count: i32) -> String {
+    if count > 0 {
+        count.to_string()
+    } else {
+        "inf".to_string()
+    }
+}
+
 fn get_full_keystr(key: u32, state: gtk::gdk::ModifierType) -> Option<String> {
     let modstr = get_modstr(state);
     let keystr = keyval_to_keystr(key);
@@ -567,10 +664,13 @@ fn get_modstr(state: gtk::gdk::ModifierType) -> String {
 
 use gtk::glib::translate::FromGlib;
 
-use super::options_matcher::{
-    match_audio_channels,
-    match_hwdec_interop,
-    match_video_upscale,
+use super::{
+    audio_filters::AudioFilters,
+    options_matcher::{
+        match_audio_channels,
+        match_hwdec_interop,
+        match_video_upscale,
+    },
 };
 use crate::{
     client::error::UserFacingError,
@@ -5,7 +5,10 @@ use gtk::{
 };
 
 mod imp {
-    use std::cell::RefCell;
+    use std::cell::{
+        Cell,
+        RefCell,
+    };
 
     use gtk::{
         glib,
@@ -19,6 +22,7 @@ mod imp {
     #[properties(wrapper_type = super::VideoScale)]
     pub struct VideoScale {
         pub timeout: RefCell<Option<glib::source::SourceId>>,
+        pub ab_loop: Cell<(Option<f64>, Option<f64>)>,
         #[property(get, set = Self::set_player, explicit_notify, nullable)]
         pub player: glib::WeakRef<MPVGLArea>,
     }
@@ -67,7 +71,12 @@ mod imp {
             ));
         }
     }
-    impl WidgetImpl for VideoScale {}
+    impl WidgetImpl for VideoScale {
+        fn snapshot(&self, snapshot: &gtk::Snapshot) {
+            self.parent_snapshot(snapshot);
+            self.obj().snapshot_ab_loop(snapshot);
+        }
+    }
     impl RangeImpl for VideoScale {}
     impl ScaleImpl for VideoScale {}
 
@@ -156,5 +165,50 @@ impl VideoScale {
     pub fn reset_scale(&self) {
         self.set_value(0.0);
         self.set_fill_level(0.0);
+        self.set_ab_loop(None, None);
+    }
+
+    pub fn ab_loop(&self) -> (Option<f64>, Option<f64>) {
+        self.imp().ab_loop.get()
+    }
+
+    pub fn set_ab_loop(&self, a: Option<f64>, b: Option<f64>) {
+        self.imp().ab_loop.set((a, b));
+        self.queue_draw();
+    }
+
+    /// Highlight the A-B range on the trough, or just A while B is unset.
+    fn snapshot_ab_loop(&self, snapshot: &gtk::Snapshot) {
+        let (Some(a), b) = self.imp().ab_loop.get() else {
+            return;
+        };
+        let Some(trough) = std::iter::successors(self.first_child(), |child| child.next_sibling())
+            .find(|child| child.css_name() == "trough")
+        else {
+            return;
+        };
+        let Some(bounds) = trough.compute_bounds(self) else {
+            return;
+        };
+
+        let adjustment = self.adjustment();
+        let length = adjustment.upper() - adjustment.lower();
+        if length <= 0.0 {
+            return;
+        }
+        let x = |value: f64| {
+            let fraction = ((value - adjustment.lower()) / length).clamp(0.0, 1.0);
+            bounds.x() + fraction as f32 * bounds.width()
+        };
+
+        let start = x(a);
+        let width = b.map_or(0.0, |b| x(b) - start).max(2.0);
+        let color = adw::StyleManager::default()
+            .accent_color_rgba()
+            .with_alpha(0.5);
+        snapshot.append_color(
+            &color,
+            &gtk::graphene::Rect::new(start, bounds.y() - 2.0, width, bounds.height() + 4.0),
+        );
     }
 }
@@ -8,7 +8,13 @@ use gtk::{
     },
 };
 
-use crate::ui::widgets::song_widget::State;
+use crate::{
+    client::structs::TICKS_PER_SECOND,
+    ui::{
+        provider::tu_item::TuItem,
+        widgets::song_widget::State,
+    },
+};
 
 pub mod imp {
     use std::cell::Cell;
@@ -32,9 +38,14 @@ pub mod imp {
         #[property(get, set)]
         pub album_id: RefCell<String>,
         #[property(get, set)]
+        pub album: RefCell<String>,
+        #[property(get, set)]
         pub have_single_track_image: RefCell<bool>,
         #[property(get, set)]
         pub duration: RefCell<u64>,
+        /// ReplayGain fallback in dB, for songs without tags.
+        #[property(get, set)]
+        pub normalization_gain: RefCell<f64>,
     }
 
     #[glib::derived_properties]
@@ -68,6 +79,26 @@ impl CoreSong {
             .property("state", State::Unplayed)
             .build()
     }
+
+    pub fn from_item(item: &TuItem) -> CoreSong {
+        let core_song = CoreSong::new(&item.id());
+        if let Some(album_id) = item.album_id() {
+            core_song.set_album_id(album_id);
+        }
+        if let Some(album) = item.album() {
+            core_song.set_album(album);
+        }
+        core_song.set_artist(item.albumartist_name());
+        core_song.set_name(item.name());
+        if let Some(image_tags) = item.image_tags() {
+            if image_tags.primary().is_some() {
+                core_song.set_have_single_track_image(true);
+            }
+        }
+        core_song.set_duration((item.run_time_ticks() as f64 / TICKS_PER_SECOND) as u64);
+        core_song.set_normalization_gain(item.normalization_gain());
+        core_song
+    }
 }
 
 impl Default for CoreSong {
@@ -26,6 +26,7 @@ use crate::{
         mpv::page::extract_url,
         provider::core_song::CoreSong,
         widgets::{
+            artist_page::ArtistPage,
             item::ItemPage,
             list::ListPage,
             music_album::AlbumPage,
@@ -82,6 +83,8 @@ pub mod imp {
         #[property(get, set)]
         unplayed_item_count: RefCell<u32>,
         #[property(get, set)]
+        play_count: RefCell<u32>,
+        #[property(get, set)]
         is_favorite: RefCell<bool>,
         #[property(get, set)]
         is_resume: RefCell<bool>,
@@ -104,11 +107,17 @@ pub mod imp {
         #[property(get, set, nullable)]
         album_id: RefCell<Option<String>>,
         #[property(get, set, nullable)]
+        album: RefCell<Option<String>>,
+        #[property(get, set, nullable)]
         rating: RefCell<Option<String>>,
         #[property(get, set, nullable)]
         primary_image_item_id: RefCell<Option<String>>,
         #[property(get, set)]
         run_time_ticks: RefCell<u64>,
+        #[property(get, set)]
+        part_count: RefCell<u32>,
+        #[property(get, set)]
+        normalization_gain: RefCell<f64>,
         #[property(get, set, nullable)]
         collection_type: RefCell<Option<String>>,
         #[property(name = "albumartist-name", get, set, type = String, member = name)]
@@ -185,6 +194,7 @@ impl TuItem {
             tu_item.set_played(userdata.played);
             tu_item.set_played_percentage(userdata.played_percentage.unwrap_or_default());
             tu_item.set_unplayed_item_count(userdata.unplayed_item_count.unwrap_or_default());
+            tu_item.set_play_count(userdata.play_count.unwrap_or_default());
             tu_item
                 .set_playback_position_ticks(userdata.playback_position_ticks.unwrap_or_default());
             tu_item.set_is_favorite(userdata.is_favorite.unwrap_or(false));
@@ -221,7 +231,10 @@ impl TuItem {
         tu_item.set_role(item.role);
         tu_item.set_artists(item.artists.map(|artists| artists.join(" , ")));
         tu_item.set_album_id(item.album_id);
+        tu_item.set_album(item.album);
         tu_item.set_run_time_ticks(item.run_time_ticks.unwrap_or_default());
+        tu_item.set_part_count(item.part_count.unwrap_or_default());
+        tu_item.set_normalization_gain(item.normalization_gain.unwrap_or_default());
         tu_item.set_tagline(item.taglines.and_then(|taglines| taglines.first().cloned()));
         tu_item.set_primary_image_item_id(item.primary_image_item_id);
         tu_item.set_rating(item.community_rating.map(|rating| format!("{:.1}", rating)));
@@ -267,6 +280,10 @@ impl TuItem {
                 let page = AlbumPage::new(self.clone());
                 push_page_with_tag(window, page, self.id(), &self.name());
             }
+            "MusicArtist" => {
+                let page = ArtistPage::new(self);
+                push_page_with_tag(window, page, self.id(), &self.name());
+            }
             "CollectionFolder" => {
                 let page = ListPage::new(self.id(), self.collection_type().unwrap_or_default());
                 push_page_with_tag(window, page, self.id(), &self.name());
@@ -389,6 +406,8 @@ impl TuItem {
                             playsessionid: playback.play_session_id,
                             mediasourceid: playback.media_sources[0].id.clone(),
                             start_tick: glib::DateTime::now_local().unwrap().to_unix() as u64,
+                            play_method: None,
+                            paused: false,
                         };
                         window.play_media(
                             url.to_string(),
@@ -415,6 +434,13 @@ impl TuItem {
         bing_song_model!(obj, model, song_widget.coresong());
     }
 
+    pub fn play_audiobook(&self, obj: &impl IsA<gtk::Widget>) {
+        let Some(window) = obj.root().and_downcast::<Window>() else {
+            return;
+        };
+        window.play_audiobook(self.clone());
+    }
+
     pub async fn play_album(&self, obj: &impl IsA<gtk::Widget>) {
         let id = self.id();
 
@@ -482,6 +508,8 @@ impl TuItem {
             playsessionid: playback.play_session_id,
             mediasourceid: source.id.clone(),
             start_tick: glib::DateTime::now_local().unwrap().to_unix() as u64,
+            play_method: None,
+            paused: false,
         };
 
         if let Some(window) = obj.root().and_downcast_ref::<Window>() {
@@ -18,16 +18,29 @@ use gtk::{
 
 use crate::{
     client::emby_client::EMBY_CLIENT,
+    gstl::audio,
     toast,
     ui::{
         models::{
             emby_cache_path,
+            languages::language_name,
+            resume::{
+                ResumeBehavior,
+                ResumeDefaults,
+            },
+            shader_profiles::COLLECTION_TYPES,
             SETTINGS,
         },
         provider::descriptor::{
             Descriptor,
             DescriptorType,
         },
+        widgets::{
+            action_row::AActionRow,
+            equalizer_editor::EqualizerEditor,
+            keybindings_page::KeybindingsPage,
+            languages_page::LanguagesPage,
+        },
     },
     utils::{
         spawn,
@@ -38,6 +51,7 @@ use crate::{
 mod imp {
     use std::cell::{
         Cell,
+        OnceCell,
         RefCell,
     };
 
@@ -83,9 +97,9 @@ mod imp {
         pub post_spinrow: TemplateChild<adw::SpinRow>,
 
         #[template_child]
-        pub preferred_audio_language_comborow: TemplateChild<adw::ComboRow>,
+        pub audio_languages_row: TemplateChild<AActionRow>,
         #[template_child]
-        pub preferred_subtitle_language_comborow: TemplateChild<adw::ComboRow>,
+        pub subtitle_languages_row: TemplateChild<AActionRow>,
 
         #[template_child]
         pub preferred_version_subpage: TemplateChild<adw::NavigationPage>,
@@ -130,6 +144,28 @@ mod imp {
         #[template_child]
         pub folder_button_content: TemplateChild<adw::ButtonContent>,
 
+        #[template_child]
+        pub resume_group: TemplateChild<adw::PreferencesGroup>,
+        #[template_child]
+        pub resume_combo: TemplateChild<adw::ComboRow>,
+
+        #[template_child]
+        pub replaygain_combo: TemplateChild<adw::ComboRow>,
+        #[template_child]
+        pub replaygain_preamp_spinrow: TemplateChild<adw::SpinRow>,
+        #[template_child]
+        pub replaygain_clipping_switchrow: TemplateChild<adw::SwitchRow>,
+        #[template_child]
+        pub crossfade_spinrow: TemplateChild<adw::SpinRow>,
+        #[template_child]
+        pub audio_output_combo: TemplateChild<adw::ComboRow>,
+
+        #[template_child]
+        pub music_equalizer_row: TemplateChild<adw::ExpanderRow>,
+        #[template_child]
+        pub music_equalizer_preset_combo: TemplateChild<adw::ComboRow>,
+        pub music_equalizer: OnceCell<EqualizerEditor>,
+
         pub now_editing_descriptor: RefCell<Option<Descriptor>>,
 
         pub descriptor_grab_x: Cell<f64>,
@@ -202,6 +238,10 @@ mod imp {
             obj.set_color();
             obj.bind_settings();
             obj.refersh_descriptors();
+            obj.set_languages_subtitles();
+            obj.load_resume_defaults();
+            obj.load_audio_outputs();
+            obj.build_music_equalizer();
         }
     }
 
@@ -423,20 +463,6 @@ impl AccountSettings {
         SETTINGS
             .bind("mpv-config", &imp.config_switchrow.get(), "active")
             .build();
-        SETTINGS
-            .bind(
-                "mpv-audio-preferred-lang",
-                &imp.preferred_audio_language_comborow.get(),
-                "selected",
-            )
-            .build();
-        SETTINGS
-            .bind(
-                "mpv-subtitle-preferred-lang",
-                &imp.preferred_subtitle_language_comborow.get(),
-                "selected",
-            )
-            .build();
         SETTINGS
             .bind(
                 "is-auto-select-server",
@@ -456,6 +482,29 @@ impl AccountSettings {
         SETTINGS
             .bind("is-refresh", &imp.refresh_control.get(), "active")
             .build();
+        SETTINGS
+            .bind("mpv-resume-behavior", &imp.resume_combo.get(), "selected")
+            .build();
+        SETTINGS
+            .bind("music-replaygain", &imp.replaygain_combo.get(), "selected")
+            .build();
+        SETTINGS
+            .bind(
+                "music-replaygain-preamp",
+                &imp.replaygain_preamp_spinrow.get(),
+                "value",
+            )
+            .build();
+        SETTINGS
+            .bind(
+                "music-replaygain-prevent-clipping",
+                &imp.replaygain_clipping_switchrow.get(),
+                "active",
+            )
+            .build();
+        SETTINGS
+            .bind("music-crossfade", &imp.crossfade_spinrow.get(), "value")
+            .build();
 
         let action_group = gio::SimpleActionGroup::new();
 
@@ -500,6 +549,153 @@ impl AccountSettings {
         ));
     }
 
+    fn load_resume_defaults(&self) {
+        spawn(glib::clone!(
+            #[weak(rename_to = obj)]
+            self,
+            async move {
+                let views = match spawn_tokio(async move { EMBY_CLIENT.get_library().await }).await
+                {
+                    Ok(views) => views,
+                    Err(e) => {
+                        tracing::warn!("Failed to get libraries: {}", e);
+                        return;
+                    }
+                };
+
+                let group = obj.imp().resume_group.get();
+                let defaults = ResumeDefaults::load();
+                for view in views.items.into_iter().filter(|view| {
+                    view.collection_type
+                        .as_deref()
+                        .map_or(true, |kind| COLLECTION_TYPES.contains(&kind))
+                }) {
+                    let items = [
+                        gettext("Use Default"),
+                        gettext("Ask"),
+                        gettext("Resume"),
+                        gettext("Start Over"),
+                    ];
+                    let model =
+                        gtk::StringList::new(&items.iter().map(String::as_str).collect::<Vec<_>>());
+                    let row = adw::ComboRow::builder()
+                        .title(&view.name)
+                        .subtitle(gettext("Library"))
+                        .use_markup(false)
+                        .model(&model)
+                        .build();
+                    let selected = defaults
+                        .get(&view.id)
+                        .map_or(0, |behavior| behavior as u32 + 1);
+                    row.set_selected(selected);
+
+                    let id = view.id;
+                    row.connect_selected_notify(move |row| {
+                        let behavior = row
+                            .selected()
+                            .checked_sub(1)
+                            .map(|behavior| ResumeBehavior::from(behavior as i32));
+                        let mut defaults = ResumeDefaults::load();
+                        defaults.set(&id, behavior);
+                        defaults.save();
+                    });
+
+                    group.add(&row);
+                }
+            }
+        ));
+    }
+
+    fn load_audio_outputs(&self) {
+        let combo = self.imp().audio_output_combo.get();
+        let stored = SETTINGS.music_audio_output();
+
+        let mut outputs = audio::output_devices();
+        // Keep an unplugged device selected rather than forgetting it
+        if !stored.is_empty() && !outputs.iter().any(|(id, _)| *id == stored) {
+            outputs.push((stored.clone(), stored.clone()));
+        }
+
+        let mut items = vec![gettext("Default")];
+        items.extend(outputs.iter().map(|(_, name)| name.clone()));
+        let model = gtk::StringList::new(&items.iter().map(String::as_str).collect::<Vec<_>>());
+        combo.set_model(Some(&model));
+        let selected = outputs
+            .iter()
+            .position(|(id, _)| *id == stored)
+            .map_or(0, |index| index as u32 + 1);
+        combo.set_selected(selected);
+
+        combo.connect_selected_notify(move |combo| {
+            let id = combo
+                .selected()
+                .checked_sub(1)
+                .and_then(|index| outputs.get(index as usize))
+                .map(|(id, _)| id.as_str())
+                .unwrap_or_default();
+            let _ = SETTINGS.set_music_audio_output(id);
+        });
+    }
+
+    fn build_music_equalizer(&self) {
+        let imp = self.imp();
+        let editor = EqualizerEditor::new(
+            &imp.music_equalizer_row,
+            &imp.music_equalizer_preset_combo,
+            || SETTINGS.music_equalizer(),
+            |gains| {
+                let _ = SETTINGS.set_music_equalizer(gains);
+            },
+        );
+        let _ = imp.music_equalizer.set(editor);
+    }
+
+    fn set_languages_subtitles(&self) {
+        let imp = self.imp();
+        let names = |langs: Vec<String>| {
+            if langs.is_empty() {
+                return gettext("Default");
+            }
+            langs
+                .iter()
+                .map(|lang| language_name(lang))
+                .collect::<Vec<_>>()
+                .join(", ")
+        };
+
+        imp.audio_languages_row
+            .set_subtitle(&names(SETTINGS.mpv_audio_preferred_langs()));
+        imp.subtitle_languages_row
+            .set_subtitle(&names(SETTINGS.mpv_subtitle_preferred_langs()));
+    }
+
+    fn push_languages_page(&self, subtitle: bool) {
+        let page = LanguagesPage::new(subtitle);
+        page.connect_hidden(glib::clone!(
+            #[weak(rename_to = obj)]
+            self,
+            move |_| {
+                obj.set_languages_subtitles();
+            }
+        ));
+        self.push_subpage(&page);
+    }
+
+    #[template_callback]
+    fn audio_languages_activated_cb(&self) {
+        self.push_languages_page(false);
+    }
+
+    #[template_callback]
+    fn subtitle_languages_activated_cb(&self) {
+        self.push_languages_page(true);
+    }
+
+    #[template_callback]
+    fn keybindings_activated_cb(&self) {
+        self.push_subpage(&KeybindingsPage::new());
+    }
+
     #[template_callback]
     fn preferred_subpage_activated_cb(&self) {
         let subpage = self.imp().preferred_version_subpage.get();
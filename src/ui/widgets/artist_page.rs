@@ -0,0 +1,226 @@
+use glib::Object;
+use gtk::{
+    gio,
+    glib,
+    prelude::*,
+    subclass::prelude::*,
+    template_callbacks,
+};
+
+use super::{
+    hortu_scrolled::HortuScrolled,
+    picture_loader::PictureLoader,
+    window::Window,
+};
+use crate::{
+    client::{
+        emby_client::EMBY_CLIENT,
+        error::UserFacingError,
+        structs::*,
+    },
+    fraction,
+    fraction_reset,
+    toast,
+    ui::{
+        models::discography,
+        provider::tu_item::TuItem,
+    },
+    utils::{
+        fetch_with_cache,
+        CachePolicy,
+    },
+};
+
+pub(crate) mod imp {
+    use std::cell::OnceCell;
+
+    use adw::subclass::prelude::*;
+    use glib::subclass::InitializingObject;
+    use gtk::{
+        glib,
+        prelude::*,
+        CompositeTemplate,
+    };
+
+    use crate::{
+        ui::{
+            provider::tu_item::TuItem,
+            widgets::item_actionbox::ItemActionsBox,
+        },
+        utils::spawn_g_timeout,
+    };
+
+    // Object holding the state
+    #[derive(CompositeTemplate, Default, glib::Properties)]
+    #[template(resource = "/moe/tsuna/tsukimi/ui/artist_page.ui")]
+    #[properties(wrapper_type = super::ArtistPage)]
+    pub struct ArtistPage {
+        #[property(get, set, construct_only)]
+        pub item: OnceCell<TuItem>,
+        #[template_child]
+        pub picbox: TemplateChild<gtk::Box>,
+        #[template_child]
+        pub inscription: TemplateChild<gtk::Inscription>,
+        #[template_child]
+        pub inforevealer: TemplateChild<gtk::Revealer>,
+        #[template_child]
+        pub title: TemplateChild<gtk::Label>,
+        #[template_child]
+        pub actionbox: TemplateChild<ItemActionsBox>,
+        #[template_child]
+        pub discography_box: TemplateChild<gtk::Box>,
+    }
+
+    #[glib::object_subclass]
+    impl ObjectSubclass for ArtistPage {
+        const NAME: &'static str = "ArtistPage";
+        type Type = super::ArtistPage;
+        type ParentType = adw::NavigationPage;
+
+        fn class_init(klass: &mut Self::Class) {
+            ItemActionsBox::ensure_type();
+            klass.bind_template();
+            klass.bind_template_instance_callbacks();
+        }
+
+        fn instance_init(obj: &InitializingObject<Self>) {
+            obj.init_template();
+        }
+    }
+
+    #[glib::derived_properties]
+    impl ObjectImpl for ArtistPage {
+        fn constructed(&self) {
+            self.parent_constructed();
+            let obj = self.obj();
+
+            spawn_g_timeout(glib::clone!(
+                #[weak]
+                obj,
+                async move {
+                    obj.setup_pic();
+                    obj.get_item().await;
+                    obj.get_discography().await;
+                }
+            ));
+
+            self.actionbox.set_id(Some(obj.item().id()));
+        }
+    }
+
+    impl WidgetImpl for ArtistPage {}
+
+    impl WindowImpl for ArtistPage {}
+
+    impl ApplicationWindowImpl for ArtistPage {}
+
+    impl adw::subclass::navigation_page::NavigationPageImpl for ArtistPage {}
+}
+
+glib::wrapper! {
+    pub struct ArtistPage(ObjectSubclass<imp::ArtistPage>)
+        @extends gtk::ApplicationWindow, gtk::Window, gtk::Widget ,adw::NavigationPage,
+        @implements gio::ActionGroup, gio::ActionMap, gtk::Accessible, gtk::Buildable,
+                    gtk::ConstraintTarget, gtk::Native, gtk::Root, gtk::ShortcutManager;
+}
+
+#[template_callbacks]
+impl ArtistPage {
+    pub fn new(item: &TuItem) -> Self {
+        Object::builder().property("item", item).build()
+    }
+
+    pub fn setup_pic(&self) {
+        let id = self.item().id();
+        let pic = PictureLoader::new(&id, "Primary", None);
+        pic.set_size_request(218, 218);
+        pic.set_valign(gtk::Align::Start);
+        self.imp().picbox.append(&pic);
+    }
+
+    pub async fn get_item(&self) {
+        let id = self.item().id();
+
+        fraction_reset!(self);
+        let item = match fetch_with_cache(
+            &format!("list_{}", id),
+            CachePolicy::ReadCacheAndRefresh,
+            async move { EMBY_CLIENT.get_item_info(&id).await },
+        )
+        .await
+        {
+            Ok(item) => item,
+            Err(e) => {
+                toast!(self, e.to_user_facing());
+                return;
+            }
+        };
+
+        let imp = self.imp();
+        if let Some(overview) = item.overview {
+            imp.inscription.set_text(Some(&overview));
+        }
+        imp.title.set_text(&item.name);
+        if let Some(is_favorite) = item.user_data.and_then(|userdata| userdata.is_favorite) {
+            imp.actionbox.set_btn_active(is_favorite);
+        }
+        imp.inforevealer.set_reveal_child(true);
+        fraction!(self);
+    }
+
+    async fn get_discography(&self) {
+        let id = self.item().id();
+        let (albums, appearances) = match fetch_with_cache(
+            &format!("artist_discography_{}", id),
+            CachePolicy::ReadCacheAndRefresh,
+            async move {
+                let albums = EMBY_CLIENT.get_artist_albums(None, &id, None).await?;
+                let appearances = EMBY_CLIENT.get_artist_appearances(&id).await?;
+                Ok((albums, appearances))
+            },
+        )
+        .await
+        {
+            Ok(lists) => lists,
+            Err(e) => {
+                toast!(self, e.to_user_facing());
+                return;
+            }
+        };
+
+        self.set_discography(albums, appearances);
+    }
+
+    fn set_discography(&self, albums: List, appearances: List) {
+        let discography_box = self.imp().discography_box.get();
+        while let Some(child) = discography_box.first_child() {
+            discography_box.remove(&child);
+        }
+
+        for (kind, items) in discography::group(&self.item().id(), albums.items, appearances.items)
+        {
+            let hortu = HortuScrolled::new();
+            hortu.set_title(kind.title());
+            hortu.set_items(&items);
+            discography_box.append(&hortu);
+        }
+    }
+
+    fn play_instant_mix(&self, radio: bool) {
+        let Some(window) = self.root().and_downcast::<Window>() else {
+            return;
+        };
+        let item = self.item();
+        window.play_instant_mix(item.id(), item.item_type(), radio);
+    }
+
+    #[template_callback]
+    fn on_play_clicked(&self) {
+        self.play_instant_mix(false);
+    }
+
+    #[template_callback]
+    fn on_radio_clicked(&self) {
+        self.play_instant_mix(true);
+    }
+}
@@ -0,0 +1,154 @@
+use std::{
+    cell::Cell,
+    rc::{
+        Rc,
+        Weak,
+    },
+};
+
+use adw::prelude::*;
+use gettextrs::gettext;
+
+use crate::ui::models::equalizer::{
+    self,
+    band_label,
+    preset_index,
+    presets,
+    Gains,
+    BANDS,
+    MAX_GAIN,
+    MIN_GAIN,
+};
+
+/// Band rows and preset selection of the 10-band equalizer, filled into an
+/// expander row and a combo row of a template.
+#[derive(Clone)]
+pub struct EqualizerEditor(Rc<Inner>);
+
+impl std::fmt::Debug for EqualizerEditor {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("EqualizerEditor")
+            .field("gains", &self.gains())
+            .finish()
+    }
+}
+
+struct Inner {
+    row: adw::ExpanderRow,
+    preset_combo: adw::ComboRow,
+    adjs: Vec<gtk::Adjustment>,
+    /// Set while the bands or the preset are changed from here.
+    updating: Cell<bool>,
+    set_gains: Box<dyn Fn(&Gains)>,
+}
+
+impl EqualizerEditor {
+    /// Starts from `gains` and hands every change to `set_gains`.
+    pub fn new(
+        row: &adw::ExpanderRow, preset_combo: &adw::ComboRow, gains: impl Fn() -> Gains,
+        set_gains: impl Fn(&Gains) + 'static,
+    ) -> Self {
+        let inner = Rc::new_cyclic(|weak: &Weak<Inner>| {
+            let adjs = BANDS
+                .iter()
+                .zip(gains())
+                .map(|(frequency, gain)| {
+                    let adj = gtk::Adjustment::new(gain, MIN_GAIN, MAX_GAIN, 0.5, 1.0, 0.0);
+                    let weak = weak.clone();
+                    adj.connect_value_changed(move |_| {
+                        if let Some(inner) = weak.upgrade() {
+                            inner.on_band();
+                        }
+                    });
+                    let band_row = adw::SpinRow::builder()
+                        .title(band_label(*frequency))
+                        .subtitle(gettext("Unit: dB"))
+                        .adjustment(&adj)
+                        .digits(1)
+                        .build();
+                    row.add_row(&band_row);
+                    adj
+                })
+                .collect();
+
+            Inner {
+                row: row.clone(),
+                preset_combo: preset_combo.clone(),
+                adjs,
+                updating: Cell::new(false),
+                set_gains: Box::new(set_gains),
+            }
+        });
+
+        let mut items: Vec<String> = presets().into_iter().map(|(name, _)| name).collect();
+        items.push(gettext("Custom"));
+        let model = gtk::StringList::new(&items.iter().map(String::as_str).collect::<Vec<_>>());
+        inner.updating.set(true);
+        preset_combo.set_model(Some(&model));
+        inner.updating.set(false);
+
+        let weak = Rc::downgrade(&inner);
+        preset_combo.connect_selected_notify(move |combo| {
+            if let Some(inner) = weak.upgrade() {
+                inner.on_preset(combo.selected());
+            }
+        });
+
+        inner.sync_preset();
+        Self(inner)
+    }
+
+    pub fn gains(&self) -> Gains {
+        self.0.gains()
+    }
+}
+
+impl Inner {
+    fn gains(&self) -> Gains {
+        let values: Vec<f64> = self.adjs.iter().map(|adj| adj.value()).collect();
+        equalizer::gains(&values)
+    }
+
+    /// Select the preset the bands match, or "Custom".
+    fn sync_preset(&self) {
+        let index = preset_index(&self.gains()).unwrap_or(presets().len());
+        self.updating.set(true);
+        self.preset_combo.set_selected(index as u32);
+        self.updating.set(false);
+
+        let name = self
+            .preset_combo
+            .selected_item()
+            .and_downcast::<gtk::StringObject>()
+            .map(|item| item.string().to_string())
+            .unwrap_or_default();
+        self.row.set_subtitle(&name);
+    }
+
+    fn on_band(&self) {
+        if self.updating.get() {
+            return;
+        }
+        (self.set_gains)(&self.gains());
+        self.sync_preset();
+    }
+
+    fn on_preset(&self, selected: u32) {
+        if self.updating.get() {
+            return;
+        }
+        // The last entry is "Custom", which keeps the bands as they are
+        let Some((_, gains)) = presets().into_iter().nth(selected as usize) else {
+            return;
+        };
+
+        self.updating.set(true);
+        for (adj, gain) in self.adjs.iter().zip(gains) {
+            adj.set_value(gain);
+        }
+        self.updating.set(false);
+
+        (self.set_gains)(&gains);
+        self.sync_preset();
+    }
+}
@@ -23,6 +23,7 @@ use super::{
     hortu_scrolled::SHOW_BUTTON_ANIMATION_DURATION,
     item_utils::*,
     song_widget::format_duration,
+    subtitle_search_dialog::SubtitleSearchDialog,
     tu_overview_item::run_time_ticks_to_label,
     window::Window,
 };
@@ -32,8 +33,13 @@ use crate::{
         error::UserFacingError,
         structs::*,
     },
+    insert_editm_dialog,
     toast,
     ui::{
+        models::track_memory::{
+            TrackChoice,
+            TrackMemory,
+        },
         mpv::page::direct_stream_url,
         provider::{
             dropdown_factory::{
@@ -437,7 +443,8 @@ impl ItemPage {
                 }
             };
 
-        self.set_dropdown(&playback);
+        let track_choice = TrackMemory::get(&intro.id(), intro.series_id().as_deref());
+        self.set_dropdown(&playback, track_choice);
         self.set_play_session_id(playback.play_session_id.clone());
 
         self.set_current_item(Some(intro));
@@ -607,7 +614,7 @@ impl ItemPage {
         }
     }
 
-    pub fn set_dropdown(&self, playbackinfo: &Media) {
+    pub fn set_dropdown(&self, playbackinfo: &Media, track_choice: Option<TrackChoice>) {
         let playbackinfo = playbackinfo.clone();
         let imp = self.imp();
         let namedropdown = imp.namedropdown.get();
@@ -646,7 +653,7 @@ impl ItemPage {
                 }
                 for media in &media_sources {
                     if &Some(media.id.clone()) == selected {
-                        let mut lang_list = Vec::new();
+                        let mut sub_streams = Vec::new();
                         for stream in &media.media_streams {
                             if stream.stream_type == "Subtitle" {
                                 let Ok(dl) = DropdownListBuilder::default()
@@ -660,13 +667,25 @@ impl ItemPage {
                                     continue;
                                 };
 
-                                lang_list.push(dl.line1.clone().unwrap_or_default());
+                                sub_streams.push(stream);
                                 let object = glib::BoxedAnyObject::new(dl);
                                 sstore.append(&object);
                             }
                         }
 
-                        if let Some(usize) = make_subtitle_version_choice(lang_list) {
+                        let choice = match track_choice.as_ref() {
+                            Some(choice) if choice.sub_off || choice.local_sub_selected() => {
+                                subdropdown.set_selected(gtk::INVALID_LIST_POSITION);
+                                break;
+                            }
+                            Some(choice) => {
+                                make_subtitle_version_choice_from_memory(&sub_streams, choice)
+                                    .or_else(|| make_subtitle_version_choice(&sub_streams))
+                            }
+                            None => make_subtitle_version_choice(&sub_streams),
+                        };
+
+                        if let Some(usize) = choice {
                             subdropdown.set_selected(usize as u32);
                         }
                         break;
@@ -885,6 +904,7 @@ impl ItemPage {
                     obj.add_backdrops(image_tags, &item.id).await;
                 }
                 if let Some(part_count) = item.part_count {
+                    obj.item().set_part_count(part_count);
                     if part_count > 1 {
                         obj.sets("Additional Parts", &item.id).await;
                     }
@@ -1132,6 +1152,50 @@ impl ItemPage {
         carousel.scroll_to(&carousel.nth_page(0), true);
     }
 
+    #[template_callback]
+    fn on_search_subtitles_clicked(&self) {
+        let Some(item) = self.current_item() else {
+            return;
+        };
+
+        let Some(video_object) = self
+            .imp()
+            .namedropdown
+            .selected_item()
+            .and_downcast::<glib::BoxedAnyObject>()
+        else {
+            return;
+        };
+
+        let Some(media_source_id) = video_object.borrow::<DropdownList>().id.clone() else {
+            return;
+        };
+
+        let dialog = SubtitleSearchDialog::new(&item.id(), &media_source_id);
+        dialog.connect_subtitle_downloaded(glib::clone!(
+            #[weak(rename_to = obj)]
+            self,
+            #[weak]
+            item,
+            move |_, _| {
+                spawn(glib::clone!(
+                    #[weak]
+                    obj,
+                    #[weak]
+                    item,
+                    async move {
+                        if item.item_type() == "Episode" {
+                            obj.set_intro::<false>(&item).await;
+                        } else {
+                            obj.set_intro::<true>(&item).await;
+                        }
+                    }
+                ));
+            }
+        ));
+        insert_editm_dialog!(self, dialog);
+    }
+
     #[template_callback]
     async fn play_cb(&self) {
         let video_dropdown = self.imp().namedropdown.get();
@@ -1173,6 +1237,8 @@ impl ItemPage {
             mediasourceid: media_source_id.to_string(),
             tick: item.playback_position_ticks(),
             start_tick: glib::DateTime::now_local().unwrap().to_unix() as u64,
+            play_method: None,
+            paused: false,
         };
 
         let sub_url = if let Some(sub_object) = sub_dropdown
@@ -150,6 +150,23 @@ impl ItemActionsBox {
             ))
             .build()]);
         if self.is_playable() {
+            action_group.add_action_entries([gio::ActionEntry::builder("tracks")
+                .activate(glib::clone!(
+                    #[weak(rename_to = obj)]
+                    self,
+                    move |_, _, _| {
+                        use crate::{
+                            insert_editm_dialog,
+                            ui::widgets::track_memory_dialog::TrackMemoryDialog,
+                        };
+                        let id = obj.id();
+                        if let Some(id) = id {
+                            let dialog = TrackMemoryDialog::new(&id);
+                            insert_editm_dialog!(obj, dialog);
+                        }
+                    }
+                ))
+                .build()]);
             if self.played() {
                 action_group.add_action_entries([gio::ActionEntry::builder("unplayed")
                     .activate(glib::clone!(
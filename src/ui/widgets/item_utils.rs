@@ -1,8 +1,19 @@
 use strsim::jaro_winkler;
 
-use crate::ui::{
-    models::SETTINGS,
-    provider::descriptor::DescriptorType,
+use crate::{
+    client::structs::MediaStream,
+    ui::{
+        models::{
+            languages::{
+                chinese_script_hints,
+                language_matches,
+                language_name,
+            },
+            track_memory::TrackChoice,
+            SETTINGS,
+        },
+        provider::descriptor::DescriptorType,
+    },
 };
 
 pub fn make_video_version_choice_from_filter(dl_list: Vec<String>) -> Option<usize> {
@@ -46,23 +57,98 @@ pub fn make_video_version_choice_from_matcher(
     best_match_index
 }
 
-pub fn make_subtitle_version_choice(lang_list: Vec<String>) -> Option<usize> {
-    let lang = match SETTINGS.mpv_subtitle_preferred_lang() {
-        1 => "English",
-        2 => "Chinese Simplified",
-        3 => "Japanese",
-        4 => "Chinese Traditional",
-        5 => "Arabic",
-        6 => "Norwegian Bokmål",
-        7 => "Portuguese",
-        8 => "French",
-        _ => return None,
+pub fn make_subtitle_version_choice(streams: &[&MediaStream]) -> Option<usize> {
+    rank_subtitle_streams(
+        streams,
+        &SETTINGS.mpv_subtitle_preferred_langs(),
+        SETTINGS.mpv_subtitle_prefer_forced(),
+        SETTINGS.mpv_subtitle_prefer_sdh(),
+    )
+}
+
+fn stream_flag(stream: &MediaStream, flag: Option<bool>, hints: &[&str]) -> bool {
+    flag.unwrap_or_else(|| {
+        let title = stream
+            .title
+            .as_ref()
+            .or(stream.display_title.as_ref())
+            .map(|s| s.to_lowercase())
+            .unwrap_or_default();
+        hints.iter().any(|hint| title.contains(hint))
+    })
+}
+
+fn stream_matches_language(stream: &MediaStream, lang: &str) -> bool {
+    let title = stream
+        .display_title
+        .as_deref()
+        .unwrap_or_default()
+        .to_lowercase();
+    match stream.language.as_ref() {
+        // Only the title tells which script a Chinese stream is in
+        Some(language) if language_matches(language, "chi") => match chinese_script_hints(lang) {
+            Some(hints) => hints.iter().any(|hint| title.contains(hint)),
+            None => language_matches(language, lang),
+        },
+        Some(language) => language_matches(language, lang),
+        // External subtitles often come without a language tag
+        None => title.contains(&language_name(lang).to_lowercase()),
+    }
+}
+
+/// Streams are ordered by the position of their language in `langs`, then by
+/// how well their forced and SDH flags fit the preferences.
+fn rank_subtitle_streams(
+    streams: &[&MediaStream], langs: &[String], prefer_forced: bool, prefer_sdh: bool,
+) -> Option<usize> {
+    streams
+        .iter()
+        .enumerate()
+        .filter_map(|(index, stream)| {
+            let rank = langs
+                .iter()
+                .position(|lang| stream_matches_language(stream, lang))?;
+            let forced = stream_flag(stream, stream.is_forced, &["forced"]);
+            let sdh = stream_flag(
+                stream,
+                stream.is_hearing_impaired,
+                &["sdh", "hearing impaired"],
+            );
+            Some((rank, forced != prefer_forced, sdh != prefer_sdh, index))
+        })
+        .min()
+        .map(|(_, _, _, index)| index)
+}
+
+pub fn make_subtitle_version_choice_from_memory(
+    streams: &[&MediaStream], choice: &TrackChoice,
+) -> Option<usize> {
+    let candidates: Vec<_> = streams
+        .iter()
+        .enumerate()
+        .filter(|(_, stream)| match choice.sub_lang.as_ref() {
+            Some(lang) => stream_matches_language(stream, lang),
+            None => true,
+        })
+        .collect();
+
+    let Some(title) = choice.sub_title.as_ref() else {
+        if choice.sub_lang.is_none() {
+            return None;
+        }
+        return candidates.first().map(|(index, _)| *index);
     };
 
     let mut best_match_index = None;
     let mut highest_similarity = 0.0;
-    for (index, name) in lang_list.iter().enumerate() {
-        let similarity = jaro_winkler(name, lang);
+    for (index, stream) in candidates {
+        let name = stream
+            .title
+            .as_ref()
+            .or(stream.display_title.as_ref())
+            .map(|s| s.as_str())
+            .unwrap_or_default();
+        let similarity = jaro_winkler(name, title);
         if similarity > highest_similarity {
             highest_similarity = similarity;
             best_match_index = Some(index);
@@ -71,3 +157,77 @@ pub fn make_subtitle_version_choice(lang_list: Vec<String>) -> Option<usize> {
 
     best_match_index
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn stream(language: Option<&str>, title: &str, forced: Option<bool>) -> MediaStream {
+        serde_json::from_value(serde_json::json!({
+            "Type": "Subtitle",
+            "IsExternal": false,
+            "Index": 0,
+            "Language": language,
+            "DisplayTitle": title,
+            "IsForced": forced,
+        }))
+        .unwrap()
+    }
+
+    #[test]
+    fn test_rank_subtitle_streams() {
+        let streams = [
+            stream(Some("eng"), "English", Some(false)),
+            stream(Some("jpn"), "Japanese", Some(false)),
+            stream(Some("eng"), "English (Forced)", Some(true)),
+            stream(None, "French External", None),
+        ];
+        let streams: Vec<_> = streams.iter().collect();
+        let langs = |l: &[&str]| l.iter().map(|s| s.to_string()).collect::<Vec<_>>();
+
+        assert_eq!(
+            rank_subtitle_streams(&streams, &langs(&["jpn", "eng"]), false, false),
+            Some(1)
+        );
+        assert_eq!(
+            rank_subtitle_streams(&streams, &langs(&["en"]), false, false),
+            Some(0)
+        );
+        assert_eq!(
+            rank_subtitle_streams(&streams, &langs(&["eng"]), true, false),
+            Some(2)
+        );
+        assert_eq!(
+            rank_subtitle_streams(&streams, &langs(&["fra", "eng"]), false, false),
+            Some(3)
+        );
+        assert_eq!(
+            rank_subtitle_streams(&streams, &langs(&["ger"]), false, false),
+            None
+        );
+    }
+
+    #[test]
+    fn test_chinese_scripts() {
+        let streams = [
+            stream(Some("chi"), "Chinese Traditional - SUBRIP", Some(false)),
+            stream(Some("zho"), "Chinese Simplified - SUBRIP", Some(false)),
+            stream(None, "Chinese Simplified External", None),
+        ];
+        let streams: Vec<_> = streams.iter().collect();
+        let langs = |l: &[&str]| l.iter().map(|s| s.to_string()).collect::<Vec<_>>();
+
+        assert_eq!(
+            rank_subtitle_streams(&streams, &langs(&["chs"]), false, false),
+            Some(1)
+        );
+        assert_eq!(
+            rank_subtitle_streams(&streams, &langs(&["cht"]), false, false),
+            Some(0)
+        );
+        assert_eq!(
+            rank_subtitle_streams(&streams, &langs(&["chi"]), false, false),
+            Some(0)
+        );
+    }
+}
@@ -0,0 +1,352 @@
+use adw::{
+    prelude::*,
+    subclass::prelude::*,
+};
+use gettextrs::gettext;
+use gtk::{
+    gdk,
+    gio,
+    glib::{
+        self,
+        translate::IntoGlib,
+    },
+    template_callbacks,
+};
+
+use crate::{
+    toast,
+    ui::models::keybindings::{
+        self,
+        KeyAction,
+        KeyScope,
+        KEY_ACTIONS,
+    },
+};
+
+const EXPORT_FILE_NAME: &str = "tsukimi-shortcuts.json";
+
+mod imp {
+    use std::cell::{
+        Cell,
+        RefCell,
+    };
+
+    use glib::subclass::InitializingObject;
+    use gtk::CompositeTemplate;
+
+    use super::*;
+
+    #[derive(Debug, Default, CompositeTemplate)]
+    #[template(resource = "/moe/tsuna/tsukimi/ui/keybindings_page.ui")]
+    pub struct KeybindingsPage {
+        #[template_child]
+        pub player_group: TemplateChild<adw::PreferencesGroup>,
+        #[template_child]
+        pub app_group: TemplateChild<adw::PreferencesGroup>,
+        #[template_child]
+        pub capture_dialog: TemplateChild<adw::Dialog>,
+        #[template_child]
+        pub capture_status_page: TemplateChild<adw::StatusPage>,
+        pub rows: RefCell<Vec<(adw::PreferencesGroup, adw::ActionRow)>>,
+        /// Action waiting for a key, and whether the key replaces its bindings.
+        pub capture: Cell<Option<(&'static str, bool)>>,
+    }
+
+    #[glib::object_subclass]
+    impl ObjectSubclass for KeybindingsPage {
+        const NAME: &'static str = "KeybindingsPage";
+        type Type = super::KeybindingsPage;
+        type ParentType = adw::NavigationPage;
+
+        fn class_init(klass: &mut Self::Class) {
+            klass.bind_template();
+            klass.bind_template_instance_callbacks();
+        }
+
+        fn instance_init(obj: &InitializingObject<Self>) {
+            obj.init_template();
+        }
+    }
+
+    impl ObjectImpl for KeybindingsPage {
+        fn constructed(&self) {
+            self.parent_constructed();
+            let obj = self.obj();
+            obj.set_capture_controller();
+            obj.refresh();
+        }
+    }
+
+    impl WidgetImpl for KeybindingsPage {}
+    impl NavigationPageImpl for KeybindingsPage {}
+}
+
+glib::wrapper! {
+    pub struct KeybindingsPage(ObjectSubclass<imp::KeybindingsPage>)
+        @extends gtk::Widget, adw::NavigationPage, @implements gtk::Accessible;
+}
+
+impl Default for KeybindingsPage {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[template_callbacks]
+impl KeybindingsPage {
+    pub fn new() -> Self {
+        glib::Object::new()
+    }
+
+    fn refresh(&self) {
+        let imp = self.imp();
+
+        for (group, row) in imp.rows.take() {
+            group.remove(&row);
+        }
+
+        let mut rows = Vec::new();
+        for action in KEY_ACTIONS {
+            let group = if action.scope == KeyScope::Player {
+                imp.player_group.get()
+            } else {
+                imp.app_group.get()
+            };
+            let row = self.action_row(action);
+            group.add(&row);
+            rows.push((group, row));
+        }
+        imp.rows.replace(rows);
+    }
+
+    fn action_row(&self, action: &'static KeyAction) -> adw::ActionRow {
+        let accels = action.bindings();
+
+        let row = adw::ActionRow::builder()
+            .title(action.title())
+            .activatable(true)
+            .build();
+
+        let label = gtk::ShortcutLabel::builder()
+            .accelerator(accels.join(" "))
+            .disabled_text(gettext("Disabled"))
+            .valign(gtk::Align::Center)
+            .build();
+
+        let add_button = gtk::Button::builder()
+            .icon_name("list-add-symbolic")
+            .tooltip_text(gettext("Add Shortcut"))
+            .valign(gtk::Align::Center)
+            .css_classes(["flat"])
+            .build();
+
+        let clear_button = gtk::Button::builder()
+            .icon_name("edit-clear-symbolic")
+            .tooltip_text(gettext("Disable"))
+            .valign(gtk::Align::Center)
+            .sensitive(!accels.is_empty())
+            .css_classes(["flat"])
+            .build();
+
+        let reset_button = gtk::Button::builder()
+            .icon_name("edit-undo-symbolic")
+            .tooltip_text(gettext("Reset"))
+            .valign(gtk::Align::Center)
+            .visible(!action.is_default())
+            .css_classes(["flat"])
+            .build();
+
+        row.connect_activated(glib::clone!(
+            #[weak(rename_to = obj)]
+            self,
+            move |_| {
+                obj.capture(action, true);
+            }
+        ));
+
+        add_button.connect_clicked(glib::clone!(
+            #[weak(rename_to = obj)]
+            self,
+            move |_| {
+                obj.capture(action, false);
+            }
+        ));
+
+        clear_button.connect_clicked(glib::clone!(
+            #[weak(rename_to = obj)]
+            self,
+            move |_| {
+                keybindings::clear(action.name);
+                obj.refresh();
+            }
+        ));
+
+        reset_button.connect_clicked(glib::clone!(
+            #[weak(rename_to = obj)]
+            self,
+            move |_| {
+                keybindings::reset(action.name);
+                obj.refresh();
+            }
+        ));
+
+        row.add_suffix(&label);
+        row.add_suffix(&add_button);
+        row.add_suffix(&clear_button);
+        row.add_suffix(&reset_button);
+        row
+    }
+
+    fn set_capture_controller(&self) {
+        let controller = gtk::EventControllerKey::new();
+        controller.set_propagation_phase(gtk::PropagationPhase::Capture);
+        controller.connect_key_pressed(glib::clone!(
+            #[weak(rename_to = obj)]
+            self,
+            #[upgrade_or]
+            glib::Propagation::Proceed,
+            move |_, key, _, state| obj.on_capture_key(key, state)
+        ));
+        self.imp().capture_dialog.add_controller(controller);
+    }
+
+    fn capture(&self, action: &'static KeyAction, replace: bool) {
+        let imp = self.imp();
+        imp.capture.set(Some((action.name, replace)));
+        imp.capture_status_page.set_title(&action.title());
+        imp.capture_dialog.present(Some(self));
+    }
+
+    fn on_capture_key(&self, key: gdk::Key, state: gdk::ModifierType) -> glib::Propagation {
+        let imp = self.imp();
+        let Some((name, replace)) = imp.capture.get() else {
+            return glib::Propagation::Proceed;
+        };
+
+        let mods = state & gtk::accelerator_get_default_mod_mask();
+        if key == gdk::Key::Escape && mods.is_empty() {
+            imp.capture.set(None);
+            imp.capture_dialog.close();
+            return glib::Propagation::Stop;
+        }
+
+        // Wait for the actual key when only a modifier is held
+        let Some(accel) = keybindings::accelerator(key.into_glib(), state) else {
+            return glib::Propagation::Stop;
+        };
+
+        imp.capture.set(None);
+        imp.capture_dialog.close();
+        self.bind(name, accel, replace);
+        glib::Propagation::Stop
+    }
+
+    fn bind(&self, name: &'static str, accel: String, replace: bool) {
+        let Some(other) = keybindings::conflict(&accel, name) else {
+            keybindings::assign(name, &accel, replace);
+            self.refresh();
+            return;
+        };
+
+        let label = gtk::accelerator_parse(&accel)
+            .map(|(key, mods)| gtk::accelerator_get_label(key, mods).to_string())
+            .unwrap_or_else(|| accel.clone());
+
+        let alert_dialog = adw::AlertDialog::builder()
+            .heading(gettext("Shortcut Already in Use"))
+            .body(format!(
+                "{} {} “{}”",
+                label,
+                gettext("is currently used by"),
+                other.title()
+            ))
+            .build();
+
+        alert_dialog.add_response("cancel", &gettext("Cancel"));
+        alert_dialog.add_response("replace", &gettext("Replace"));
+        alert_dialog.set_response_appearance("replace", adw::ResponseAppearance::Destructive);
+
+        alert_dialog.connect_response(
+            Some("replace"),
+            glib::clone!(
+                #[weak(rename_to = obj)]
+                self,
+                move |_, _| {
+                    keybindings::assign(name, &accel, replace);
+                    obj.refresh();
+                }
+            ),
+        );
+
+        alert_dialog.present(Some(self));
+    }
+
+    #[template_callback]
+    fn on_reset_all_activated(&self) {
+        keybindings::reset_all();
+        self.refresh();
+        toast!(self, gettext("Shortcuts reset"));
+    }
+
+    fn json_filters() -> gio::ListStore {
+        let filter = gtk::FileFilter::new();
+        filter.set_name(Some(&gettext("JSON")));
+        filter.add_suffix("json");
+        let model = gio::ListStore::new::<gtk::FileFilter>();
+        model.append(&filter);
+        model
+    }
+
+    #[template_callback]
+    async fn on_export_clicked(&self) {
+        let window = self.root().and_downcast::<gtk::Window>();
+        let filedialog = gtk::FileDialog::builder()
+            .modal(true)
+            .title(gettext("Export Shortcuts"))
+            .initial_name(EXPORT_FILE_NAME)
+            .filters(&Self::json_filters())
+            .build();
+
+        let Ok(file) = filedialog.save_future(window.as_ref()).await else {
+            return;
+        };
+        let Some(path) = file.path() else {
+            return;
+        };
+
+        match std::fs::write(path, keybindings::export()) {
+            Ok(_) => toast!(self, gettext("Shortcuts exported")),
+            Err(e) => toast!(self, e.to_string()),
+        }
+    }
+
+    #[template_callback]
+    async fn on_import_clicked(&self) {
+        let window = self.root().and_downcast::<gtk::Window>();
+        let filedialog = gtk::FileDialog::builder()
+            .modal(true)
+            .title(gettext("Import Shortcuts"))
+            .filters(&Self::json_filters())
+            .build();
+
+        let Ok(file) = filedialog.open_future(window.as_ref()).await else {
+            return;
+        };
+        let Some(path) = file.path() else {
+            return;
+        };
+
+        let Ok(json) = std::fs::read_to_string(path) else {
+            toast!(self, gettext("Failed to read file"));
+            return;
+        };
+
+        match keybindings::import(&json) {
+            Ok(_) => {
+                self.refresh();
+                toast!(self, gettext("Shortcuts imported"));
+            }
+            Err(_) => toast!(self, gettext("Invalid shortcuts file")),
+        }
+    }
+}
@@ -0,0 +1,307 @@
+use adw::{
+    prelude::*,
+    subclass::prelude::*,
+};
+use gettextrs::gettext;
+use gtk::{
+    gio,
+    glib,
+    template_callbacks,
+};
+
+use crate::ui::models::{
+    languages::{
+        language_name,
+        LANGUAGES,
+    },
+    SETTINGS,
+};
+
+mod imp {
+    use std::cell::{
+        OnceCell,
+        RefCell,
+    };
+
+    use glib::subclass::InitializingObject;
+    use gtk::CompositeTemplate;
+
+    use super::*;
+
+    #[derive(Debug, Default, CompositeTemplate, glib::Properties)]
+    #[template(resource = "/moe/tsuna/tsukimi/ui/languages_page.ui")]
+    #[properties(wrapper_type = super::LanguagesPage)]
+    pub struct LanguagesPage {
+        #[property(get, set, construct_only)]
+        pub subtitle: OnceCell<bool>,
+        #[template_child]
+        pub list_stack: TemplateChild<gtk::Stack>,
+        #[template_child]
+        pub languages_listbox: TemplateChild<gtk::ListBox>,
+        #[template_child]
+        pub flags_group: TemplateChild<adw::PreferencesGroup>,
+        #[template_child]
+        pub forced_switchrow: TemplateChild<adw::SwitchRow>,
+        #[template_child]
+        pub sdh_switchrow: TemplateChild<adw::SwitchRow>,
+        #[template_child]
+        pub add_dialog: TemplateChild<adw::Dialog>,
+        #[template_child]
+        pub search_entry: TemplateChild<gtk::SearchEntry>,
+        #[template_child]
+        pub search_listbox: TemplateChild<gtk::ListBox>,
+        pub search_filter: RefCell<Option<gtk::CustomFilter>>,
+    }
+
+    #[glib::object_subclass]
+    impl ObjectSubclass for LanguagesPage {
+        const NAME: &'static str = "LanguagesPage";
+        type Type = super::LanguagesPage;
+        type ParentType = adw::NavigationPage;
+
+        fn class_init(klass: &mut Self::Class) {
+            klass.bind_template();
+            klass.bind_template_instance_callbacks();
+        }
+
+        fn instance_init(obj: &InitializingObject<Self>) {
+            obj.init_template();
+        }
+    }
+
+    #[glib::derived_properties]
+    impl ObjectImpl for LanguagesPage {
+        fn constructed(&self) {
+            self.parent_constructed();
+            let obj = self.obj();
+
+            if obj.subtitle() {
+                obj.set_title(&gettext("Preferred Subtitle Languages"));
+                self.flags_group.set_visible(true);
+                SETTINGS
+                    .bind(
+                        "mpv-subtitle-prefer-forced",
+                        &self.forced_switchrow.get(),
+                        "active",
+                    )
+                    .build();
+                SETTINGS
+                    .bind(
+                        "mpv-subtitle-prefer-sdh",
+                        &self.sdh_switchrow.get(),
+                        "active",
+                    )
+                    .build();
+            } else {
+                obj.set_title(&gettext("Preferred Audio Languages"));
+            }
+
+            obj.set_search_model();
+            obj.refresh_languages();
+        }
+    }
+
+    impl WidgetImpl for LanguagesPage {}
+    impl NavigationPageImpl for LanguagesPage {}
+}
+
+glib::wrapper! {
+    pub struct LanguagesPage(ObjectSubclass<imp::LanguagesPage>)
+        @extends gtk::Widget, adw::NavigationPage, @implements gtk::Accessible;
+}
+
+#[template_callbacks]
+impl LanguagesPage {
+    pub fn new(subtitle: bool) -> Self {
+        glib::Object::builder()
+            .property("subtitle", subtitle)
+            .build()
+    }
+
+    fn langs(&self) -> Vec<String> {
+        if self.subtitle() {
+            SETTINGS.mpv_subtitle_preferred_langs()
+        } else {
+            SETTINGS.mpv_audio_preferred_langs()
+        }
+    }
+
+    fn set_langs(&self, langs: Vec<String>) {
+        let result = if self.subtitle() {
+            SETTINGS.set_mpv_subtitle_preferred_langs(langs)
+        } else {
+            SETTINGS.set_mpv_audio_preferred_langs(langs)
+        };
+        result.expect("Failed to set preferred languages");
+        self.refresh_languages();
+    }
+
+    fn refresh_languages(&self) {
+        let imp = self.imp();
+        let listbox = imp.languages_listbox.get();
+        let langs = self.langs();
+
+        if langs.is_empty() {
+            imp.list_stack.set_visible_child_name("empty");
+            return;
+        }
+        imp.list_stack.set_visible_child_name("list");
+
+        listbox.remove_all();
+
+        let len = langs.len();
+        for (index, lang) in langs.into_iter().enumerate() {
+            let row = adw::ActionRow::builder()
+                .title(language_name(&lang))
+                .subtitle(&lang)
+                .build();
+
+            let up_button = gtk::Button::builder()
+                .icon_name("go-up-symbolic")
+                .tooltip_text(gettext("Move Up"))
+                .valign(gtk::Align::Center)
+                .sensitive(index > 0)
+                .css_classes(["flat"])
+                .build();
+
+            up_button.connect_clicked(glib::clone!(
+                #[weak(rename_to = obj)]
+                self,
+                move |_| {
+                    let mut langs = obj.langs();
+                    langs.swap(index, index - 1);
+                    obj.set_langs(langs);
+                }
+            ));
+
+            let down_button = gtk::Button::builder()
+                .icon_name("go-down-symbolic")
+                .tooltip_text(gettext("Move Down"))
+                .valign(gtk::Align::Center)
+                .sensitive(index + 1 < len)
+                .css_classes(["flat"])
+                .build();
+
+            down_button.connect_clicked(glib::clone!(
+                #[weak(rename_to = obj)]
+                self,
+                move |_| {
+                    let mut langs = obj.langs();
+                    langs.swap(index, index + 1);
+                    obj.set_langs(langs);
+                }
+            ));
+
+            let delete_button = gtk::Button::builder()
+                .icon_name("user-trash-symbolic")
+                .tooltip_text(gettext("Remove"))
+                .valign(gtk::Align::Center)
+                .css_classes(["flat"])
+                .build();
+
+            delete_button.connect_clicked(glib::clone!(
+                #[weak(rename_to = obj)]
+                self,
+                move |_| {
+                    let mut langs = obj.langs();
+                    langs.remove(index);
+                    obj.set_langs(langs);
+                }
+            ));
+
+            row.add_suffix(&up_button);
+            row.add_suffix(&down_button);
+            row.add_suffix(&delete_button);
+
+            listbox.append(&row);
+        }
+    }
+
+    fn set_search_model(&self) {
+        let imp = self.imp();
+
+        let store = gio::ListStore::new::<glib::BoxedAnyObject>();
+        for (_, code, _, name) in LANGUAGES {
+            store.append(&glib::BoxedAnyObject::new((*code, *name)));
+        }
+
+        let filter = gtk::CustomFilter::new(glib::clone!(
+            #[weak(rename_to = obj)]
+            self,
+            #[upgrade_or]
+            true,
+            move |item| {
+                let Some(entry) = item.downcast_ref::<glib::BoxedAnyObject>() else {
+                    return false;
+                };
+                let (code, name) = *entry.borrow::<(&'static str, &'static str)>();
+                let search = obj.imp().search_entry.text().to_lowercase();
+                search.is_empty()
+                    || name.to_lowercase().contains(&search)
+                    || LANGUAGES
+                        .iter()
+                        .find(|(_, b, _, _)| *b == code)
+                        .is_some_and(|(a, b, t, _)| *a == search || *b == search || *t == search)
+            }
+        ));
+
+        let model = gtk::FilterListModel::new(Some(store), Some(filter.clone()));
+
+        imp.search_listbox.bind_model(
+            Some(&model),
+            glib::clone!(
+                #[weak(rename_to = obj)]
+                self,
+                #[upgrade_or_panic]
+                move |item| {
+                    let entry = item
+                        .downcast_ref::<glib::BoxedAnyObject>()
+                        .expect("Expected a language entry");
+                    let (code, name) = *entry.borrow::<(&'static str, &'static str)>();
+
+                    let row = adw::ActionRow::builder()
+                        .title(name)
+                        .subtitle(code)
+                        .activatable(true)
+                        .build();
+
+                    row.connect_activated(glib::clone!(
+                        #[weak]
+                        obj,
+                        move |_| {
+                            obj.add_language(code);
+                        }
+                    ));
+
+                    row.upcast()
+                }
+            ),
+        );
+
+        imp.search_filter.replace(Some(filter));
+    }
+
+    fn add_language(&self, code: &str) {
+        let mut langs = self.langs();
+        if !langs.iter().any(|lang| lang == code) {
+            langs.push(code.to_string());
+            self.set_langs(langs);
+        }
+        self.imp().add_dialog.close();
+    }
+
+    #[template_callback]
+    fn on_add_clicked(&self) {
+        let imp = self.imp();
+        imp.search_entry.set_text("");
+        imp.add_dialog.present(Some(self));
+        imp.search_entry.grab_focus();
+    }
+
+    #[template_callback]
+    fn on_search_changed(&self) {
+        if let Some(filter) = self.imp().search_filter.borrow().as_ref() {
+            filter.changed(gtk::FilterChange::Different);
+        }
+    }
+}
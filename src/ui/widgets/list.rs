@@ -6,9 +6,12 @@ use gtk::{
     subclass::prelude::*,
 };
 
-use super::single_grid::{
-    imp::ListType,
-    SingleGrid,
+use super::{
+    single_grid::{
+        imp::ListType,
+        SingleGrid,
+    },
+    songs_page::SongsPage,
 };
 use crate::client::emby_client::EMBY_CLIENT;
 mod imp {
@@ -110,10 +113,34 @@ impl ListPage {
             return;
         }
 
-        let include_item_types = get_include_item_types(collection_type);
+        let include_item_types = get_include_item_types(collection_type.clone());
+
+        if &collection_type == "music" {
+            let pages = [
+                ("all", &gettext("Albums"), ListType::All),
+                (
+                    "albumartists",
+                    &gettext("Album Artists"),
+                    ListType::AlbumArtists,
+                ),
+                ("artists", &gettext("Artists"), ListType::Artists),
+            ];
+            for (name, title, list_type) in pages {
+                self.add_grid(&id, &include_item_types, name, title, list_type);
+            }
+
+            stack.add_titled(&SongsPage::new(&id), Some("songs"), &gettext("Songs"));
+        } else {
+            self.add_grid(
+                &id,
+                &include_item_types,
+                "all",
+                &gettext("All"),
+                ListType::All,
+            );
+        }
 
         let pages = [
-            ("all", &gettext("All"), ListType::All),
             ("resume", &gettext("Resume"), ListType::Resume),
             ("boxset", &gettext("BoxSet"), ListType::BoxSet),
             ("tags", &gettext("Tags"), ListType::Tags),
@@ -123,79 +150,79 @@ impl ListPage {
         ];
 
         for (name, title, list_type) in pages {
-            let page = SingleGrid::new();
-            page.set_list_type(list_type);
-            let id_clone1 = id.clone();
-            let include_item_types_clone1 = include_item_types.clone();
-            page.connect_sort_changed_tokio(
-                list_type == ListType::Resume,
-                move |sort_by, sort_order, filters_list| {
-                    let id_clone1 = id_clone1.clone();
-                    let include_item_types_clone1 = include_item_types_clone1.clone();
-                    async move {
-                        if list_type == ListType::Folder {
-                            EMBY_CLIENT
-                                .get_folder_include(
-                                    &id_clone1,
-                                    &sort_by,
-                                    &sort_order,
-                                    0,
-                                    &filters_list,
-                                )
-                                .await
-                        } else {
-                            EMBY_CLIENT
-                                .get_list(
-                                    &id_clone1,
-                                    0,
-                                    &include_item_types_clone1,
-                                    list_type,
-                                    &sort_order,
-                                    &sort_by,
-                                    &filters_list,
-                                )
-                                .await
-                        }
+            self.add_grid(&id, &include_item_types, name, title, list_type);
+        }
+    }
+
+    fn add_grid(
+        &self, id: &str, include_item_types: &str, name: &str, title: &str, list_type: ListType,
+    ) {
+        let page = SingleGrid::new();
+        page.set_list_type(list_type);
+        let id_clone1 = id.to_string();
+        let include_item_types_clone1 = include_item_types.to_string();
+        page.connect_sort_changed_tokio(
+            list_type == ListType::Resume,
+            move |sort_by, sort_order, filters_list| {
+                let id_clone1 = id_clone1.clone();
+                let include_item_types_clone1 = include_item_types_clone1.clone();
+                async move {
+                    if list_type == ListType::Folder {
+                        EMBY_CLIENT
+                            .get_folder_include(&id_clone1, &sort_by, &sort_order, 0, &filters_list)
+                            .await
+                    } else {
+                        EMBY_CLIENT
+                            .get_list(
+                                &id_clone1,
+                                0,
+                                &include_item_types_clone1,
+                                list_type,
+                                &sort_order,
+                                &sort_by,
+                                &filters_list,
+                            )
+                            .await
                     }
-                },
-            );
-            let id_clone2 = id.clone();
-            let include_item_types_clone2 = include_item_types.clone();
-            page.connect_end_edge_overshot_tokio(
-                list_type == ListType::Resume,
-                move |sort_by, sort_order, n_items, filters_list| {
-                    let id_clone2 = id_clone2.clone();
-                    let include_item_types_clone2 = include_item_types_clone2.clone();
-                    async move {
-                        if list_type == ListType::Folder {
-                            EMBY_CLIENT
-                                .get_folder_include(
-                                    &id_clone2,
-                                    &sort_by,
-                                    &sort_order,
-                                    n_items,
-                                    &filters_list,
-                                )
-                                .await
-                        } else {
-                            EMBY_CLIENT
-                                .get_list(
-                                    &id_clone2,
-                                    n_items,
-                                    &include_item_types_clone2,
-                                    list_type,
-                                    &sort_order,
-                                    &sort_by,
-                                    &filters_list,
-                                )
-                                .await
-                        }
+                }
+            },
+        );
+        let id_clone2 = id.to_string();
+        let include_item_types_clone2 = include_item_types.to_string();
+        page.connect_end_edge_overshot_tokio(
+            list_type == ListType::Resume,
+            move |sort_by, sort_order, n_items, filters_list| {
+                let id_clone2 = id_clone2.clone();
+                let include_item_types_clone2 = include_item_types_clone2.clone();
+                async move {
+                    if list_type == ListType::Folder {
+                        EMBY_CLIENT
+                            .get_folder_include(
+                                &id_clone2,
+                                &sort_by,
+                                &sort_order,
+                                n_items,
+                                &filters_list,
+                            )
+                            .await
+                    } else {
+                        EMBY_CLIENT
+                            .get_list(
+                                &id_clone2,
+                                n_items,
+                                &include_item_types_clone2,
+                                list_type,
+                                &sort_order,
+                                &sort_by,
+                                &filters_list,
+                            )
+                            .await
                     }
-                },
-            );
+                }
+            },
+        );
 
-            stack.add_titled(&page, Some(name), title);
-        }
+        self.imp().stack.add_titled(&page, Some(name), title);
     }
 }
 
@@ -204,6 +231,7 @@ pub fn get_include_item_types(c: String) -> String {
         "movies" => "Movie",
         "tvshows" => "Series",
         "music" => "MusicAlbum",
+        "audiobooks" => "AudioBook",
         _ => "Movie, Series",
     };
     item_type.to_string()
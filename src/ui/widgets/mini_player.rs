@@ -0,0 +1,190 @@
+use adw::{
+    prelude::*,
+    subclass::prelude::*,
+};
+use gtk::{
+    gio,
+    glib,
+    template_callbacks,
+};
+
+use super::{
+    player_toolbar::PlayerToolbarBox,
+    smooth_scale::SmoothScale,
+};
+use crate::ui::{
+    models::SETTINGS,
+    mpris::MprisPlayer,
+};
+
+mod imp {
+    use glib::subclass::InitializingObject;
+    use gtk::CompositeTemplate;
+
+    use super::*;
+
+    #[derive(Debug, Default, CompositeTemplate)]
+    #[template(resource = "/moe/tsuna/tsukimi/ui/mini_player.ui")]
+    pub struct MiniPlayer {
+        #[template_child]
+        pub cover_picture: TemplateChild<gtk::Picture>,
+        #[template_child]
+        pub title_label: TemplateChild<gtk::Label>,
+        #[template_child]
+        pub artist_label: TemplateChild<gtk::Label>,
+        #[template_child]
+        pub progress_scale: TemplateChild<SmoothScale>,
+        #[template_child]
+        pub play_pause_image: TemplateChild<gtk::Image>,
+        pub toolbar: glib::WeakRef<PlayerToolbarBox>,
+    }
+
+    #[glib::object_subclass]
+    impl ObjectSubclass for MiniPlayer {
+        const NAME: &'static str = "MiniPlayer";
+        type Type = super::MiniPlayer;
+        type ParentType = adw::Window;
+
+        fn class_init(klass: &mut Self::Class) {
+            SmoothScale::ensure_type();
+            klass.bind_template();
+            klass.bind_template_instance_callbacks();
+        }
+
+        fn instance_init(obj: &InitializingObject<Self>) {
+            obj.init_template();
+        }
+    }
+
+    impl ObjectImpl for MiniPlayer {
+        fn constructed(&self) {
+            self.parent_constructed();
+            self.obj()
+                .set_default_size(SETTINGS.mini_player_width(), SETTINGS.mini_player_height());
+        }
+    }
+
+    impl WidgetImpl for MiniPlayer {
+        fn map(&self) {
+            self.parent_map();
+            if let Some(toolbar) = self.toolbar.upgrade() {
+                self.progress_scale
+                    .set_value(toolbar.imp().progress_scale.value());
+            }
+            self.progress_scale.update_timeout();
+        }
+
+        fn unmap(&self) {
+            self.progress_scale.remove_timeout();
+            self.parent_unmap();
+        }
+    }
+
+    impl WindowImpl for MiniPlayer {
+        fn close_request(&self) -> glib::Propagation {
+            self.obj()
+                .save_window_size()
+                .expect("Failed to save mini player state");
+            if let Some(toolbar) = self.toolbar.upgrade() {
+                toolbar.on_mini_player_closed();
+            }
+            self.parent_close_request()
+        }
+    }
+
+    impl AdwWindowImpl for MiniPlayer {}
+}
+
+glib::wrapper! {
+    /// Compact window with the music controls, kept next to the main one.
+    ///
+    /// GTK leaves placing windows to the compositor, so only the size is
+    /// remembered.
+    pub struct MiniPlayer(ObjectSubclass<imp::MiniPlayer>)
+        @extends gtk::Widget, gtk::Window, adw::Window,
+        @implements gtk::Accessible, gtk::Buildable, gtk::ConstraintTarget, gtk::Native, gtk::Root, gtk::ShortcutManager;
+}
+
+#[template_callbacks]
+impl MiniPlayer {
+    pub fn new(toolbar: &PlayerToolbarBox) -> Self {
+        let obj: Self = glib::Object::new();
+        let imp = obj.imp();
+        imp.toolbar.set(Some(toolbar));
+
+        let toolbar_imp = toolbar.imp();
+        imp.progress_scale.set_player(Some(&toolbar_imp.player));
+        toolbar_imp
+            .progress_scale
+            .adjustment()
+            .bind_property("upper", &imp.progress_scale.adjustment(), "upper")
+            .sync_create()
+            .build();
+        toolbar_imp
+            .title_label
+            .bind_property("label", &imp.title_label.get(), "label")
+            .sync_create()
+            .build();
+        toolbar_imp
+            .artist_label
+            .bind_property("label", &imp.artist_label.get(), "label")
+            .sync_create()
+            .build();
+        toolbar_imp
+            .play_pause_image
+            .bind_property("icon-name", &imp.play_pause_image.get(), "icon-name")
+            .sync_create()
+            .build();
+        // The toolbar loads the cover from the image cache
+        toolbar_imp
+            .cover_image
+            .bind_property("file", &imp.cover_picture.get(), "file")
+            .transform_to(|_, path: Option<String>| path.map(gio::File::for_path))
+            .sync_create()
+            .build();
+        obj
+    }
+
+    pub fn save_window_size(&self) -> Result<(), glib::BoolError> {
+        let (width, height) = self.default_size();
+        SETTINGS.set_mini_player_width(width)?;
+        SETTINGS.set_mini_player_height(height)?;
+        Ok(())
+    }
+
+    #[template_callback]
+    fn on_play_clicked(&self) {
+        if let Some(toolbar) = self.imp().toolbar.upgrade() {
+            toolbar.play_pause();
+        }
+    }
+
+    #[template_callback]
+    fn on_prev_clicked(&self) {
+        if let Some(toolbar) = self.imp().toolbar.upgrade() {
+            toolbar.previous();
+        }
+    }
+
+    #[template_callback]
+    fn on_next_clicked(&self) {
+        if let Some(toolbar) = self.imp().toolbar.upgrade() {
+            toolbar.next();
+        }
+    }
+
+    /// Back to the main window, playback goes on.
+    #[template_callback]
+    fn on_expand_clicked(&self) {
+        if let Some(window) = self
+            .imp()
+            .toolbar
+            .upgrade()
+            .and_then(|toolbar| toolbar.root())
+            .and_downcast::<gtk::Window>()
+        {
+            window.present();
+        }
+        self.close();
+    }
+}
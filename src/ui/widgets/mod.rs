@@ -1,9 +1,11 @@
 pub mod account_add;
 pub mod account_settings;
 pub mod action_row;
+pub mod artist_page;
 pub mod check_row;
 pub mod content_viewer;
 pub mod disc_box;
+pub mod equalizer_editor;
 pub mod eu_item;
 pub mod filter_panel;
 pub mod fix;
@@ -17,6 +19,8 @@ pub mod item;
 pub mod item_actionbox;
 pub mod item_carousel;
 pub mod item_utils;
+pub mod keybindings_page;
+pub mod languages_page;
 pub mod liked;
 pub mod list;
 pub mod list_dropdown;
@@ -24,22 +28,31 @@ pub mod listexpand_row;
 pub mod logo;
 pub mod media_viewer;
 pub mod metadata_dialog;
+pub mod mini_player;
 pub mod missing_episodes_dialog;
 pub mod music_album;
 pub mod other;
 pub mod picture_loader;
 pub mod player_toolbar;
 pub mod refresh_dialog;
+pub mod resume_dialog;
 pub mod scale_revealer;
+pub mod screenshot_gallery;
 pub mod search;
 pub mod server_action_row;
 pub mod server_panel;
 pub mod server_row;
+pub mod shader_profile_editor;
+pub mod shader_profiles_dialog;
 pub mod single_grid;
 pub mod smooth_scale;
 pub mod song_widget;
+pub mod songs_page;
 pub mod star_toggle;
+pub mod subtitle_search_dialog;
+pub mod subtitle_upload_dialog;
 pub mod theme_switcher;
+pub mod track_memory_dialog;
 pub mod tu_item;
 pub mod tu_list_item;
 pub mod tu_overview_item;
@@ -308,7 +308,11 @@ impl AlbumPage {
             async move {
                 match types.as_str() {
                     "Recommend" => EMBY_CLIENT.get_similar(&id).await,
-                    "More From" => EMBY_CLIENT.get_artist_albums(&id, &artist_id).await,
+                    "More From" => {
+                        EMBY_CLIENT
+                            .get_artist_albums(Some(&id), &artist_id, Some(12))
+                            .await
+                    }
                     _ => Ok(List::default()),
                 }
             },
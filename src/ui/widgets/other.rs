@@ -264,6 +264,16 @@ impl OtherPage {
                     }
                 ));
             }
+            "AudioBook" => {
+                self.imp().play_button.set_visible(true);
+                self.imp().play_button.connect_clicked(glib::clone!(
+                    #[weak(rename_to = obj)]
+                    self,
+                    move |_| {
+                        obj.item().play_audiobook(&obj);
+                    }
+                ));
+            }
             "Season" => {
                 self.hortu_set_season_episode_list().await;
             }
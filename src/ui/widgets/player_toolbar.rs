@@ -1,28 +1,85 @@
+use std::{
+    collections::HashSet,
+    rc::Rc,
+};
+
+use adw::prelude::*;
+use gettextrs::gettext;
 use gst::ClockTime;
 use gtk::{
     glib,
-    prelude::*,
     subclass::prelude::*,
     template_callbacks,
 };
 
 use super::{
+    mini_player::MiniPlayer,
     smooth_scale::SmoothScale,
-    song_widget::format_duration,
+    song_widget::{
+        format_duration,
+        State,
+    },
 };
 use crate::{
+    client::{
+        emby_client::EMBY_CLIENT,
+        error::UserFacingError,
+    },
     gstl::player::imp::ListRepeatMode,
+    toast,
     ui::{
-        models::SETTINGS,
-        provider::core_song::CoreSong,
+        models::{
+            audiobook::{
+                Audiobook,
+                BookFile,
+            },
+            lyrics::{
+                self,
+                Lyrics,
+            },
+            music_queue::{
+                MusicQueue,
+                QueuedSong,
+            },
+            sleep_timer::{
+                SleepMode,
+                SleepState,
+            },
+            SETTINGS,
+        },
+        mpris::{
+            self,
+            mpris,
+            LoopStatus,
+            Metadata,
+            MprisPlayer,
+            PlaybackStatus,
+        },
+        provider::{
+            core_song::CoreSong,
+            tu_item::TuItem,
+        },
     },
     utils::{
         get_image_with_cache,
         spawn,
+        spawn_tokio,
     },
 };
 
+const MPRIS_NAME: &str = "music";
+
+/// Songs left in the queue when a radio asks for more.
+const RADIO_LOOKAHEAD: usize = 3;
+
+const SKIP_BACK_SECONDS: f64 = 15.0;
+const SKIP_FORWARD_SECONDS: f64 = 30.0;
+
 mod imp {
+    use std::cell::{
+        Cell,
+        RefCell,
+    };
 
     use adw::subclass::bin::BinImpl;
     use glib::closure_local;
@@ -62,6 +119,48 @@ mod imp {
         pub duration_label: TemplateChild<gtk::Label>,
         #[template_child]
         pub repeat_image: TemplateChild<gtk::Image>,
+        #[template_child]
+        pub sleep_label: TemplateChild<gtk::Label>,
+        #[template_child]
+        pub shuffle_button: TemplateChild<gtk::ToggleButton>,
+        #[template_child]
+        pub repeat_menu_button: TemplateChild<gtk::MenuButton>,
+        #[template_child]
+        pub lyrics_button: TemplateChild<gtk::MenuButton>,
+        #[template_child]
+        pub skip_back_button: TemplateChild<gtk::Button>,
+        #[template_child]
+        pub skip_forward_button: TemplateChild<gtk::Button>,
+        #[template_child]
+        pub speed_button: TemplateChild<gtk::MenuButton>,
+        #[template_child]
+        pub chapters_button: TemplateChild<gtk::MenuButton>,
+        #[template_child]
+        pub chapters_list: TemplateChild<gtk::ListBox>,
+        #[template_child]
+        pub queue_list: TemplateChild<gtk::ListBox>,
+        #[template_child]
+        pub lyrics_stack: TemplateChild<gtk::Stack>,
+        #[template_child]
+        pub lyrics_scrolled: TemplateChild<gtk::ScrolledWindow>,
+        #[template_child]
+        pub lyrics_list: TemplateChild<gtk::ListBox>,
+        pub lyrics: RefCell<Lyrics>,
+        /// Index of the highlighted line.
+        pub lyrics_line: Cell<Option<usize>>,
+        /// Whether instant mixes of the playing song keep the queue going.
+        pub radio: Cell<bool>,
+        pub radio_loading: Cell<bool>,
+        pub art_url: RefCell<Option<String>>,
+
+        pub sleep: SleepState,
+
+        /// Book playing, whose files make the queue.
+        pub audiobook: RefCell<Option<Audiobook>>,
+        /// Index of the highlighted chapter.
+        pub chapter: Cell<Option<usize>>,
+
+        pub mini_player: RefCell<Option<MiniPlayer>>,
     }
 
     #[glib::object_subclass]
@@ -98,6 +197,37 @@ mod imp {
                     player_toolbar_box.set_repeat_mode(ListRepeatMode::Repeat);
                 },
             );
+
+            klass.install_action(
+                "sleep.start",
+                Some(glib::VariantTy::INT32),
+                move |player_toolbar_box, _action, target| {
+                    let Some(target) = target.and_then(|t| t.get::<i32>()) else {
+                        return;
+                    };
+                    player_toolbar_box.start_sleep_timer(target);
+                },
+            );
+
+            klass.install_action(
+                "sleep.cancel",
+                None,
+                move |player_toolbar_box, _action, _target| {
+                    player_toolbar_box.cancel_sleep_timer();
+                },
+            );
+
+            klass.install_action(
+                "audiobook.speed",
+                Some(glib::VariantTy::DOUBLE),
+                move |player_toolbar_box, _action, target| {
+                    let Some(speed) = target.and_then(|t| t.get::<f64>()) else {
+                        return;
+                    };
+                    let _ = SETTINGS.set_audiobook_speed(speed);
+                    player_toolbar_box.set_speed(speed);
+                },
+            );
         }
 
         fn instance_init(obj: &InitializingObject<Self>) {
@@ -119,6 +249,28 @@ mod imp {
             );
             self.obj()
                 .set_repeat_mode(ListRepeatMode::from_string(&SETTINGS.music_repeat_mode()));
+            self.obj().action_set_enabled("sleep.cancel", false);
+
+            self.player.set_shuffle(SETTINGS.music_shuffle());
+            self.player
+                .bind_property("shuffle", &self.shuffle_button.get(), "active")
+                .sync_create()
+                .bidirectional()
+                .build();
+            self.player.connect_shuffle_notify(|player| {
+                // Books always play in order, which is not the user's choice
+                if !player.imp().audiobook.get() {
+                    let _ = SETTINGS.set_music_shuffle(player.shuffle());
+                }
+                mpris().changed(MPRIS_NAME, &["Shuffle", "CanGoNext", "CanGoPrevious"]);
+            });
+
+            let obj = self.obj();
+            self.player.connect_active_model_notify(glib::clone!(
+                #[weak]
+                obj,
+                move |_| obj.bind_queue()
+            ));
         }
     }
 
@@ -138,6 +290,17 @@ impl Default for PlayerToolbarBox {
     }
 }
 
+async fn instant_mix(id: String, item_type: String) -> anyhow::Result<Vec<CoreSong>> {
+    let list =
+        spawn_tokio(async move { EMBY_CLIENT.get_instant_mix(&id, &item_type).await }).await?;
+    Ok(list
+        .items
+        .iter()
+        .filter(|item| item.item_type == "Audio")
+        .map(|item| CoreSong::from_item(&TuItem::from_simple(item, None)))
+        .collect())
+}
+
 #[template_callbacks]
 impl PlayerToolbarBox {
     pub fn new() -> Self {
@@ -164,6 +327,7 @@ impl PlayerToolbarBox {
                 i.set_icon_name(Some("media-playlist-repeat-symbolic"));
             }
         }
+        mpris().changed(MPRIS_NAME, &["LoopStatus", "CanGoNext", "CanGoPrevious"]);
     }
 
     pub fn update_play_state(&self) {
@@ -184,22 +348,28 @@ impl PlayerToolbarBox {
         imp.duration_label
             .set_text(&format_duration(duration as i64));
         imp.progress_scale.set_range(0.0, duration as f64);
+        imp.art_url.replace(None);
+        self.load_lyrics(&core_song);
+        self.extend_radio();
+        mpris().changed(
+            MPRIS_NAME,
+            &["Metadata", "PlaybackStatus", "CanGoNext", "CanGoPrevious"],
+        );
         spawn(glib::clone!(
             #[weak]
             imp,
             async move {
-                if core_song.have_single_track_image() {
-                    let path = get_image_with_cache(core_song.id(), "Primary".to_string(), None)
-                        .await
-                        .unwrap_or_default();
-                    imp.cover_image.set_from_file(Some(&path));
+                let id = if core_song.have_single_track_image() {
+                    core_song.id()
                 } else {
-                    let path =
-                        get_image_with_cache(core_song.album_id(), "Primary".to_string(), None)
-                            .await
-                            .unwrap_or_default();
-                    imp.cover_image.set_from_file(Some(&path));
-                }
+                    core_song.album_id()
+                };
+                let path = get_image_with_cache(id, "Primary".to_string(), None)
+                    .await
+                    .unwrap_or_default();
+                imp.cover_image.set_from_file(Some(&path));
+                imp.art_url.replace(mpris::art_url(&path));
+                mpris().changed(MPRIS_NAME, &["Metadata"]);
             }
         ));
     }
@@ -209,44 +379,897 @@ impl PlayerToolbarBox {
         let label = &self.imp().progress_time_label.get();
         let position = progress_scale.value();
         label.set_text(&format_duration(position as i64));
+        self.sync_lyrics(position);
+        self.sync_chapter(position);
+    }
+
+    fn load_lyrics(&self, core_song: &CoreSong) {
+        self.set_lyrics(Lyrics::default());
+        let id = core_song.id();
+        let artist = core_song.artist();
+        let name = core_song.name();
+        spawn(glib::clone!(
+            #[weak(rename_to = obj)]
+            self,
+            async move {
+                let song_id = id.clone();
+                let lyrics =
+                    spawn_tokio(async move { lyrics::fetch(&id, &artist, &name).await }).await;
+                // The song may have changed while fetching
+                if obj
+                    .imp()
+                    .player
+                    .active_core_song()
+                    .is_some_and(|core_song| core_song.id() == song_id)
+                {
+                    obj.set_lyrics(lyrics.unwrap_or_default());
+                }
+            }
+        ));
+    }
+
+    fn set_lyrics(&self, lyrics: Lyrics) {
+        let imp = self.imp();
+        imp.lyrics_list.remove_all();
+        let synced = lyrics.is_synced();
+        for line in &lyrics.lines {
+            let label = gtk::Label::builder()
+                .label(&line.text)
+                .wrap(true)
+                .justify(gtk::Justification::Center)
+                .margin_top(6)
+                .margin_bottom(6)
+                .build();
+            let row = gtk::ListBoxRow::builder()
+                .child(&label)
+                .activatable(line.start.is_some())
+                .build();
+            if synced {
+                row.add_css_class("dim-label");
+            }
+            imp.lyrics_list.append(&row);
+        }
+        imp.lyrics_stack
+            .set_visible_child_name(if lyrics.is_empty() { "empty" } else { "lyrics" });
+        imp.lyrics.replace(lyrics);
+        imp.lyrics_line.set(None);
+        self.sync_lyrics(imp.progress_scale.value());
+    }
+
+    /// Highlights the line sung at `position`.
+    fn sync_lyrics(&self, position: f64) {
+        let imp = self.imp();
+        let line = imp.lyrics.borrow().line_at(position);
+        let previous = imp.lyrics_line.replace(line);
+        if line == previous {
+            return;
+        }
+        if let Some(row) = previous.and_then(|index| imp.lyrics_list.row_at_index(index as i32)) {
+            row.remove_css_class("heading");
+            row.add_css_class("dim-label");
+        }
+        if let Some(row) = line.and_then(|index| imp.lyrics_list.row_at_index(index as i32)) {
+            row.remove_css_class("dim-label");
+            row.add_css_class("heading");
+        }
+        self.scroll_to_lyric();
+    }
+
+    /// Centres the highlighted line.
+    fn scroll_to_lyric(&self) {
+        let imp = self.imp();
+        let Some(row) = imp
+            .lyrics_line
+            .get()
+            .and_then(|index| imp.lyrics_list.row_at_index(index as i32))
+        else {
+            return;
+        };
+        let Some(bounds) = row.compute_bounds(&imp.lyrics_list.get()) else {
+            return;
+        };
+        let adj = imp.lyrics_scrolled.vadjustment();
+        let value = bounds.y() as f64 + bounds.height() as f64 / 2.0 - adj.page_size() / 2.0;
+        adj.set_value(value.min(adj.upper() - adj.page_size()).max(adj.lower()));
+    }
+
+    #[template_callback]
+    fn on_lyrics_mapped(&self) {
+        // Rows have no size until the popover is laid out
+        glib::idle_add_local_once(glib::clone!(
+            #[weak(rename_to = obj)]
+            self,
+            move || obj.scroll_to_lyric()
+        ));
+    }
+
+    #[template_callback]
+    fn on_lyrics_row_activated(&self, row: &gtk::ListBoxRow) {
+        let imp = self.imp();
+        let Some(start) = imp
+            .lyrics
+            .borrow()
+            .lines
+            .get(row.index() as usize)
+            .and_then(|line| line.start)
+        else {
+            return;
+        };
+        imp.player.imp().set_position(start);
+        imp.progress_scale.set_value(start);
     }
 
     #[template_callback]
     pub fn on_stop_button_clicked(&self) {
+        self.cancel_sleep_timer();
         let imp = self.imp();
+        imp.radio.set(false);
+        imp.player.imp().finish_session(false);
         imp.player.imp().stop();
         imp.progress_scale.remove_timeout();
         imp.toolbar.set_revealed(false);
+        mpris().unset_player(MPRIS_NAME);
+        MusicQueue::clear();
+        self.set_audiobook(None);
+    }
+
+    /// Keeps the queue in the server's cache for the next launch.
+    pub fn save_queue(&self) {
+        let imp = self.imp();
+        if !imp.toolbar.is_revealed() {
+            return;
+        }
+        // The server keeps where a book was left off
+        if imp.audiobook.borrow().is_some() {
+            MusicQueue::clear();
+            return;
+        }
+        let player = &imp.player;
+        let Some(index) = player.imp().core_song_position() else {
+            return;
+        };
+        MusicQueue {
+            songs: player.imp().songs().iter().map(QueuedSong::from).collect(),
+            index: index as usize,
+            position: player.imp().resume_position(),
+            repeat_mode: player.repeat_mode().to_string().to_owned(),
+            shuffle: player.shuffle(),
+            volume: imp
+                .sleep
+                .saved_volume()
+                .unwrap_or_else(|| player.imp().volume()),
+        }
+        .save();
+    }
+
+    /// Shows the queue saved by the last launch, paused, unless something
+    /// is already playing.
+    pub fn restore_queue(&self) {
+        let imp = self.imp();
+        let player = &imp.player;
+        if player.active_model().is_some() {
+            return;
+        }
+        let Some(queue) = MusicQueue::load() else {
+            return;
+        };
+
+        let model = gtk::gio::ListStore::new::<CoreSong>();
+        for song in &queue.songs {
+            model.append(&song.core_song());
+        }
+        let Some(core_song) = model.item(queue.index as u32).and_downcast::<CoreSong>() else {
+            return;
+        };
+
+        self.set_repeat_mode(ListRepeatMode::from_string(&queue.repeat_mode));
+        player.set_shuffle(queue.shuffle);
+        player
+            .imp()
+            .cue_model(model, core_song.clone(), queue.position);
+        player.imp().set_volume(queue.volume);
+
+        self.change_view(ClockTime::from_seconds(core_song.duration()));
+        imp.progress_scale.set_value(queue.position);
+        imp.progress_scale.update_timeout();
+        imp.play_pause_image
+            .set_icon_name(Some("media-playback-start-symbolic"));
+        imp.toolbar.set_revealed(true);
+        mpris().set_player(Rc::new(self.clone()));
     }
 
     #[template_callback]
     fn on_play_button_clicked(&self) {
+        let playing = self.imp().player.imp().state() == gst::State::Playing;
+        self.set_playing(!playing);
+    }
+
+    fn set_playing(&self, playing: bool) {
         let player = &self.imp().player;
         let play_pause_image = &self.imp().play_pause_image.get();
-        if player.imp().state() == gst::State::Playing {
+        if playing {
+            player.imp().unpause();
+            play_pause_image.set_icon_name(Some("media-playback-pause-symbolic"));
+        } else {
             player.imp().pause();
             play_pause_image.set_icon_name(Some("media-playback-start-symbolic"));
+        }
+        mpris().changed(MPRIS_NAME, &["PlaybackStatus"]);
+    }
+
+    fn start_sleep_timer(&self, target: i32) {
+        let Some(mode) = SleepMode::from_target(target) else {
+            return;
+        };
+        self.cancel_sleep_timer();
+
+        self.imp().sleep.start(
+            mode,
+            glib::clone!(
+                #[weak(rename_to = obj)]
+                self,
+                #[upgrade_or]
+                glib::ControlFlow::Break,
+                move || obj.on_sleep_tick()
+            ),
+        );
+        self.on_sleep_tick();
+    }
+
+    pub fn cancel_sleep_timer(&self) {
+        let imp = self.imp();
+        imp.sleep
+            .cancel(|volume| imp.player.imp().set_volume(volume));
+        self.show_sleep_remaining(None);
+    }
+
+    fn show_sleep_remaining(&self, remaining: Option<f64>) {
+        let imp = self.imp();
+        if let Some(remaining) = remaining {
+            imp.sleep_label
+                .set_text(&format_duration(remaining.ceil() as i64));
+        }
+        imp.sleep_label.set_visible(remaining.is_some());
+        self.action_set_enabled("sleep.cancel", remaining.is_some());
+    }
+
+    /// Seconds left of the active song and the songs of its album queued
+    /// right after it.
+    fn until_end(&self, mode: SleepMode) -> f64 {
+        let player = &self.imp().player;
+        let Some(core_song) = player.active_core_song() else {
+            return 0.0;
+        };
+        let until_song_end = core_song.duration() as f64 - player.imp().position();
+        if mode != SleepMode::EndOfAlbum {
+            return until_song_end;
+        }
+
+        let (Some(model), Some(position)) =
+            (player.active_model(), player.imp().core_song_position())
+        else {
+            return until_song_end;
+        };
+        let album_id = core_song.album_id();
+        let rest: f64 = (position + 1..model.n_items())
+            .map_while(|index| model.item(index).and_downcast::<CoreSong>())
+            .take_while(|song| song.album_id() == album_id)
+            .map(|song| song.duration() as f64)
+            .sum();
+        until_song_end + rest
+    }
+
+    fn on_sleep_tick(&self) -> glib::ControlFlow {
+        let player = self.imp().player.imp();
+        let remaining = self.imp().sleep.tick(
+            |mode| self.until_end(mode),
+            || player.volume(),
+            |volume| player.set_volume(volume),
+            || self.set_playing(false),
+        );
+        self.show_sleep_remaining(remaining);
+        if remaining.is_some() {
+            glib::ControlFlow::Continue
         } else {
-            player.imp().unpause();
-            play_pause_image.set_icon_name(Some("media-playback-pause-symbolic"));
+            glib::ControlFlow::Break
         }
     }
 
     pub fn bind_song_model(&self, active_model: gtk::gio::ListStore, active_core_song: CoreSong) {
+        self.imp().radio.set(false);
+        self.set_audiobook(None);
         self.imp()
             .player
             .imp()
             .load_model(active_model, active_core_song);
         self.update_play_state();
+        mpris().set_player(Rc::new(self.clone()));
+    }
+
+    /// Queues `core_song` after the active song when `next` is set, at the
+    /// end otherwise, or plays it when nothing is queued.
+    pub fn queue_song(&self, core_song: CoreSong, next: bool) {
+        let imp = self.imp();
+        let player = &imp.player;
+        if player.active_model().is_none() || imp.audiobook.borrow().is_some() {
+            let model = gtk::gio::ListStore::new::<CoreSong>();
+            model.append(&core_song);
+            self.bind_song_model(model, core_song);
+            return;
+        }
+        player.imp().queue(core_song, next);
+        mpris().changed(MPRIS_NAME, &["CanGoNext", "CanGoPrevious"]);
+    }
+
+    /// Plays songs similar to the item `id`, topped up with more as they
+    /// run out when `radio` is set.
+    pub fn play_instant_mix(&self, id: String, item_type: String, radio: bool) {
+        spawn(glib::clone!(
+            #[weak(rename_to = obj)]
+            self,
+            async move {
+                let songs = match instant_mix(id, item_type).await {
+                    Ok(songs) => songs,
+                    Err(e) => {
+                        toast!(obj, e.to_user_facing());
+                        return;
+                    }
+                };
+                let Some(first) = songs.first().cloned() else {
+                    toast!(obj, gettext("No similar songs found"));
+                    return;
+                };
+                let model = gtk::gio::ListStore::new::<CoreSong>();
+                model.extend_from_slice(&songs);
+                obj.bind_song_model(model, first);
+                obj.imp().radio.set(radio);
+            }
+        ));
+    }
+
+    /// Appends an instant mix of the playing song once the radio's queue
+    /// is about to run out.
+    fn extend_radio(&self) {
+        let imp = self.imp();
+        if !imp.radio.get() || imp.radio_loading.get() {
+            return;
+        }
+        if imp.player.imp().remaining() > RADIO_LOOKAHEAD {
+            return;
+        }
+        let Some(core_song) = imp.player.active_core_song() else {
+            return;
+        };
+
+        imp.radio_loading.set(true);
+        spawn(glib::clone!(
+            #[weak(rename_to = obj)]
+            self,
+            async move {
+                let result = instant_mix(core_song.id(), "Audio".to_string()).await;
+                let imp = obj.imp();
+                imp.radio_loading.set(false);
+                // Something else may have started playing meanwhile
+                if !imp.radio.get() {
+                    return;
+                }
+                let songs = match result {
+                    Ok(songs) => songs,
+                    Err(e) => {
+                        tracing::warn!("Failed to extend the radio: {}", e);
+                        return;
+                    }
+                };
+                let queued: HashSet<String> = imp
+                    .player
+                    .imp()
+                    .songs()
+                    .iter()
+                    .map(|song| song.id())
+                    .collect();
+                for core_song in songs
+                    .into_iter()
+                    .filter(|core_song| !queued.contains(&core_song.id()))
+                {
+                    imp.player.imp().queue(core_song, false);
+                }
+                mpris().changed(MPRIS_NAME, &["CanGoNext", "CanGoPrevious"]);
+            }
+        ));
+    }
+
+    fn bind_queue(&self) {
+        let model = self.imp().player.active_model();
+        self.imp().queue_list.bind_model(
+            model.as_ref(),
+            glib::clone!(
+                #[weak(rename_to = obj)]
+                self,
+                #[upgrade_or_panic]
+                move |item| {
+                    let core_song = item
+                        .downcast_ref::<CoreSong>()
+                        .expect("Queue items must be songs");
+                    obj.queue_row(core_song).upcast()
+                }
+            ),
+        );
+    }
+
+    fn queue_row(&self, core_song: &CoreSong) -> adw::ActionRow {
+        let row = adw::ActionRow::builder()
+            .title(core_song.name())
+            .subtitle(core_song.artist())
+            .use_markup(false)
+            .activatable(true)
+            .build();
+
+        let playing_image = gtk::Image::from_icon_name("media-playback-start-symbolic");
+        core_song
+            .bind_property("state", &playing_image, "visible")
+            .transform_to(|_, state: State| Some(state == State::Playing))
+            .sync_create()
+            .build();
+
+        let remove_button = gtk::Button::builder()
+            .icon_name("list-remove-symbolic")
+            .tooltip_text(gettext("Remove From Queue"))
+            .valign(gtk::Align::Center)
+            .css_classes(["flat", "circular"])
+            .build();
+        core_song
+            .bind_property("state", &remove_button, "visible")
+            .transform_to(|_, state: State| Some(state != State::Playing))
+            .sync_create()
+            .build();
+        remove_button.connect_clicked(glib::clone!(
+            #[weak(rename_to = obj)]
+            self,
+            #[weak]
+            core_song,
+            move |_| {
+                let player = &obj.imp().player;
+                let Some(position) = player
+                    .active_model()
+                    .and_then(|model| model.find(&core_song))
+                else {
+                    return;
+                };
+                player.imp().unqueue(position);
+                mpris().changed(MPRIS_NAME, &["CanGoNext", "CanGoPrevious"]);
+            }
+        ));
+
+        row.add_prefix(&gtk::Image::from_icon_name("list-drag-handle-symbolic"));
+        row.add_suffix(&playing_image);
+        row.add_suffix(&remove_button);
+
+        let drag_source = gtk::DragSource::builder()
+            .name("queue-drag-format")
+            .actions(gtk::gdk::DragAction::MOVE)
+            .build();
+
+        drag_source.connect_prepare(glib::clone!(
+            #[weak(rename_to = obj)]
+            self,
+            #[weak(rename_to = widget)]
+            row,
+            #[weak]
+            core_song,
+            #[upgrade_or]
+            None,
+            move |drag_context, _x, _y| {
+                obj.imp().queue_list.drag_highlight_row(&widget);
+                let icon = gtk::WidgetPaintable::new(Some(&widget));
+                drag_context.set_icon(Some(&icon), 0, 0);
+                Some(gtk::gdk::ContentProvider::for_value(&core_song.to_value()))
+            }
+        ));
+
+        drag_source.connect_drag_end(glib::clone!(
+            #[weak(rename_to = obj)]
+            self,
+            move |_, _, _| {
+                obj.imp().queue_list.drag_unhighlight_row();
+            }
+        ));
+
+        let drop_target = gtk::DropTarget::builder()
+            .name("queue-drag-format")
+            .propagation_phase(gtk::PropagationPhase::Capture)
+            .actions(gtk::gdk::DragAction::MOVE)
+            .build();
+
+        drop_target.set_types(&[CoreSong::static_type()]);
+
+        drop_target.connect_drop(glib::clone!(
+            #[weak(rename_to = obj)]
+            self,
+            #[weak]
+            core_song,
+            #[upgrade_or]
+            false,
+            move |_drop_target, value, _x, _y| {
+                let Ok(dragged) = value.get::<CoreSong>() else {
+                    return false;
+                };
+                let player = &obj.imp().player;
+                let Some(model) = player.active_model() else {
+                    return false;
+                };
+                let (Some(from), Some(to)) = (model.find(&dragged), model.find(&core_song)) else {
+                    return false;
+                };
+                if from == to {
+                    return false;
+                }
+                player.imp().move_queued(from, to);
+                mpris().changed(MPRIS_NAME, &["CanGoNext", "CanGoPrevious"]);
+                true
+            }
+        ));
+
+        row.add_controller(drag_source);
+        row.add_controller(drop_target);
+
+        row
+    }
+
+    #[template_callback]
+    fn on_queue_row_activated(&self, row: &gtk::ListBoxRow) {
+        let player = &self.imp().player;
+        let Some(core_song) = player
+            .active_model()
+            .and_then(|model| model.item(row.index() as u32))
+            .and_downcast::<CoreSong>()
+        else {
+            return;
+        };
+        player.imp().skip_to(core_song);
+        self.update_play_state();
+    }
+
+    /// Shows the music controls in a compact window, or closes it.
+    #[template_callback]
+    fn on_mini_player_clicked(&self) {
+        let mini_player = self.imp().mini_player.borrow().clone();
+        if let Some(mini_player) = mini_player {
+            mini_player.close();
+            return;
+        }
+        let mini_player = MiniPlayer::new(self);
+        if let Some(window) = self.root().and_downcast::<gtk::Window>() {
+            mini_player.set_application(window.application().as_ref());
+        }
+        mini_player.present();
+        self.imp().mini_player.replace(Some(mini_player));
+    }
+
+    pub fn on_mini_player_closed(&self) {
+        self.imp().mini_player.take();
+    }
+
+    /// Plays `item`, a book or one of its files, from where it was left
+    /// off on any device.
+    pub fn play_audiobook(&self, item: TuItem) {
+        let id = item.id();
+        let folder_id = match item.item_type().as_str() {
+            "AudioBook" | "Audio" => item.album_id(),
+            _ => Some(item.id()),
+        };
+        spawn(glib::clone!(
+            #[weak(rename_to = obj)]
+            self,
+            async move {
+                let list = match spawn_tokio(async move {
+                    EMBY_CLIENT
+                        .get_audiobook_files(&id, folder_id.as_deref())
+                        .await
+                })
+                .await
+                {
+                    Ok(list) => list,
+                    Err(e) => {
+                        toast!(obj, e.to_user_facing());
+                        return;
+                    }
+                };
+
+                let book = Audiobook::new(list.items.iter().map(BookFile::from_item).collect());
+                let (file, position) = book.resume_point();
+                let model = gtk::gio::ListStore::new::<CoreSong>();
+                for item in &list.items {
+                    model.append(&CoreSong::from_item(&TuItem::from_simple(item, None)));
+                }
+                let Some(core_song) = model.item(file as u32).and_downcast::<CoreSong>() else {
+                    toast!(obj, gettext("No audio files found"));
+                    return;
+                };
+
+                let imp = obj.imp();
+                imp.radio.set(false);
+                obj.set_audiobook(Some(book));
+                imp.player.imp().cue_model(model, core_song, position);
+                obj.set_playing(true);
+                obj.update_play_state();
+                mpris().set_player(Rc::new(obj.clone()));
+            }
+        ));
+    }
+
+    /// Switches between playing a book and music.
+    fn set_audiobook(&self, book: Option<Audiobook>) {
+        let imp = self.imp();
+        let player = &imp.player;
+        let is_book = book.is_some();
+        let was_book = player.imp().audiobook.replace(is_book);
+
+        imp.chapters_list.remove_all();
+        for chapter in book.iter().flat_map(|book| &book.chapters) {
+            let row = adw::ActionRow::builder()
+                .title(&chapter.name)
+                .use_markup(false)
+                .activatable(true)
+                .build();
+            imp.chapters_list.append(&row);
+        }
+        imp.audiobook.replace(book);
+        imp.chapter.set(None);
+
+        imp.skip_back_button.set_visible(is_book);
+        imp.skip_forward_button.set_visible(is_book);
+        imp.speed_button.set_visible(is_book);
+        imp.chapters_button.set_visible(is_book);
+        imp.shuffle_button.set_visible(!is_book);
+        imp.repeat_menu_button.set_visible(!is_book);
+        imp.lyrics_button.set_visible(!is_book);
+
+        if is_book {
+            player.set_shuffle(false);
+            player.set_repeat_mode(ListRepeatMode::None);
+            self.set_speed(SETTINGS.audiobook_speed());
+        } else if was_book {
+            player.set_shuffle(SETTINGS.music_shuffle());
+            player.set_repeat_mode(ListRepeatMode::from_string(&SETTINGS.music_repeat_mode()));
+            player.imp().set_rate(1.0);
+        }
+    }
+
+    fn set_speed(&self, speed: f64) {
+        let imp = self.imp();
+        imp.player.imp().set_rate(speed);
+        imp.speed_button.set_label(&format!("{}×", speed));
+    }
+
+    /// Goes to `position` seconds into the book's file at index `file`.
+    fn seek_book(&self, file: usize, position: f64) {
+        let imp = self.imp();
+        let player = &imp.player;
+        if player.imp().core_song_position() == Some(file as u32) {
+            player.imp().set_position(position);
+            imp.progress_scale.set_value(position);
+            return;
+        }
+        let Some(core_song) = player
+            .active_model()
+            .and_then(|model| model.item(file as u32))
+            .and_downcast::<CoreSong>()
+        else {
+            return;
+        };
+        player.imp().skip_to_position(core_song, position);
+        self.update_play_state();
+    }
+
+    fn skip(&self, delta: f64) {
+        let player = self.imp().player.imp();
+        let Some(file) = player.core_song_position() else {
+            return;
+        };
+        let position = player.resume_position();
+        let Some((file, position)) = self
+            .imp()
+            .audiobook
+            .borrow()
+            .as_ref()
+            .map(|book| book.skip(file as usize, position, delta))
+        else {
+            return;
+        };
+        self.seek_book(file, position);
+    }
+
+    /// Where the previous or next chapter of the book starts, `None` out
+    /// of a book.
+    fn chapter_target(&self, next: bool) -> Option<(usize, f64)> {
+        let player = self.imp().player.imp();
+        let file = player.core_song_position()? as usize;
+        let position = player.resume_position();
+        let book = self.imp().audiobook.borrow();
+        let book = book.as_ref()?;
+        let chapter = if next {
+            book.next_chapter(file, position)
+        } else {
+            book.previous_chapter(file, position)
+        };
+        chapter.map(|chapter| (chapter.file, chapter.start))
+    }
+
+    fn sync_chapter(&self, position: f64) {
+        let imp = self.imp();
+        let Some(file) = imp.player.imp().core_song_position() else {
+            return;
+        };
+        let chapter = imp
+            .audiobook
+            .borrow()
+            .as_ref()
+            .and_then(|book| book.chapter_at(file as usize, position));
+        if imp.chapter.replace(chapter) == chapter {
+            return;
+        }
+        let row = chapter.and_then(|index| imp.chapters_list.row_at_index(index as i32));
+        imp.chapters_list.select_row(row.as_ref());
+    }
+
+    #[template_callback]
+    fn on_chapter_row_activated(&self, row: &gtk::ListBoxRow) {
+        let Some((file, start)) = self
+            .imp()
+            .audiobook
+            .borrow()
+            .as_ref()
+            .and_then(|book| book.chapters.get(row.index() as usize))
+            .map(|chapter| (chapter.file, chapter.start))
+        else {
+            return;
+        };
+        self.seek_book(file, start);
+    }
+
+    #[template_callback]
+    fn on_skip_back_clicked(&self) {
+        self.skip(-SKIP_BACK_SECONDS);
+    }
+
+    #[template_callback]
+    fn on_skip_forward_clicked(&self) {
+        self.skip(SKIP_FORWARD_SECONDS);
     }
 
     #[template_callback]
     fn on_next_button_clicked(&self) {
+        if self.imp().audiobook.borrow().is_some() {
+            if let Some((file, start)) = self.chapter_target(true) {
+                self.seek_book(file, start);
+            }
+            return;
+        }
         self.imp().player.imp().next();
     }
 
     #[template_callback]
     fn on_prev_button_clicked(&self) {
+        if self.imp().audiobook.borrow().is_some() {
+            if let Some((file, start)) = self.chapter_target(false) {
+                self.seek_book(file, start);
+            }
+            return;
+        }
         self.imp().player.imp().prev();
     }
 }
+
+impl MprisPlayer for PlayerToolbarBox {
+    fn name(&self) -> &'static str {
+        MPRIS_NAME
+    }
+
+    fn metadata(&self) -> Metadata {
+        let Some(core_song) = self.imp().player.active_core_song() else {
+            return Metadata::default();
+        };
+        let artist = core_song.artist();
+        let album = core_song.album();
+        Metadata {
+            id: core_song.id(),
+            title: core_song.name(),
+            artists: (!artist.is_empty()).then_some(artist).into_iter().collect(),
+            album: (!album.is_empty()).then_some(album),
+            length: Some(core_song.duration() as i64 * 1_000_000),
+            art_url: self.imp().art_url.borrow().clone(),
+            ..Default::default()
+        }
+    }
+
+    fn playback_status(&self) -> PlaybackStatus {
+        match self.imp().player.imp().state() {
+            gst::State::Playing => PlaybackStatus::Playing,
+            gst::State::Paused => PlaybackStatus::Paused,
+            _ => PlaybackStatus::Stopped,
+        }
+    }
+
+    fn position(&self) -> i64 {
+        (self.imp().player.imp().position() * 1_000_000.0) as i64
+    }
+
+    fn volume(&self) -> f64 {
+        self.imp().player.imp().volume()
+    }
+
+    fn set_volume(&self, volume: f64) {
+        self.imp().player.imp().set_volume(volume);
+        mpris().changed(MPRIS_NAME, &["Volume"]);
+    }
+
+    fn play(&self) {
+        self.set_playing(true);
+    }
+
+    fn pause(&self) {
+        self.set_playing(false);
+    }
+
+    fn play_pause(&self) {
+        self.on_play_button_clicked();
+    }
+
+    fn stop(&self) {
+        self.on_stop_button_clicked();
+    }
+
+    fn next(&self) {
+        self.on_next_button_clicked();
+    }
+
+    fn previous(&self) {
+        self.on_prev_button_clicked();
+    }
+
+    fn set_position(&self, position: i64) {
+        self.imp()
+            .player
+            .imp()
+            .set_position(position as f64 / 1_000_000.0);
+        mpris().seeked(MPRIS_NAME, position);
+    }
+
+    fn loop_status(&self) -> LoopStatus {
+        match self.imp().player.repeat_mode() {
+            ListRepeatMode::None => LoopStatus::None,
+            ListRepeatMode::RepeatOne => LoopStatus::Track,
+            ListRepeatMode::Repeat => LoopStatus::Playlist,
+        }
+    }
+
+    fn shuffle(&self) -> bool {
+        self.imp().player.shuffle()
+    }
+
+    fn set_shuffle(&self, shuffle: bool) {
+        self.imp().player.set_shuffle(shuffle);
+    }
+
+    fn set_loop_status(&self, status: LoopStatus) {
+        self.set_repeat_mode(match status {
+            LoopStatus::None => ListRepeatMode::None,
+            LoopStatus::Track => ListRepeatMode::RepeatOne,
+            LoopStatus::Playlist => ListRepeatMode::Repeat,
+        });
+    }
+
+    fn can_go_next(&self) -> bool {
+        if self.imp().audiobook.borrow().is_some() {
+            return self.chapter_target(true).is_some();
+        }
+        self.imp().player.imp().next_song().is_some()
+    }
+
+    fn can_go_previous(&self) -> bool {
+        if self.imp().audiobook.borrow().is_some() {
+            return self.chapter_target(false).is_some();
+        }
+        self.imp().player.imp().prev_song().is_some()
+    }
+}
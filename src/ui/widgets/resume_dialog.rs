@@ -0,0 +1,74 @@
+use adw::prelude::*;
+use gettextrs::gettext;
+
+use super::tu_overview_item::run_time_ticks_to_label;
+use crate::{
+    client::{
+        emby_client::EMBY_CLIENT,
+        structs::ticks,
+    },
+    ui::{
+        models::resume::{
+            ResumeBehavior,
+            ResumeDefaults,
+        },
+        provider::tu_item::TuItem,
+    },
+    utils::spawn_tokio,
+};
+
+/// Whether a partially watched `item` should resume at `position` seconds,
+/// `None` when the user cancelled. Asks unless the item's library, or the
+/// global setting, says otherwise.
+pub async fn choose_resume(
+    parent: &impl IsA<gtk::Widget>, item: &TuItem, position: f64,
+) -> Option<bool> {
+    let defaults = ResumeDefaults::load();
+    let library = if defaults.libraries.is_empty() {
+        None
+    } else {
+        let id = item.id();
+        match spawn_tokio(async move { EMBY_CLIENT.get_ancestors(&id).await }).await {
+            Ok(ancestors) => ancestors
+                .into_iter()
+                .find(|item| item.item_type == "CollectionFolder")
+                .map(|library| library.id),
+            Err(e) => {
+                tracing::warn!("Failed to get library of item: {}", e);
+                None
+            }
+        }
+    };
+
+    match defaults.behavior_for(library.as_deref()) {
+        ResumeBehavior::Resume => return Some(true),
+        ResumeBehavior::StartOver => return Some(false),
+        ResumeBehavior::Ask => {}
+    }
+
+    let dialog = adw::AlertDialog::builder()
+        .heading(item.name())
+        .body(gettext("You have watched part of this video"))
+        .close_response("cancel")
+        .default_response("resume")
+        .build();
+    dialog.add_response("cancel", &gettext("Cancel"));
+    dialog.add_response("start-over", &gettext("Start Over"));
+    dialog.add_response(
+        "resume",
+        &gettext("Resume at {}").replace("{}", &run_time_ticks_to_label(ticks(position) as u64)),
+    );
+    dialog.set_response_appearance("resume", adw::ResponseAppearance::Suggested);
+
+    let (sender, receiver) = flume::bounded(1);
+    dialog.connect_response(None, move |_, response| {
+        let _ = sender.send(response.to_string());
+    });
+    dialog.present(Some(parent));
+
+    match receiver.recv_async().await.ok()?.as_str() {
+        "resume" => Some(true),
+        "start-over" => Some(false),
+        _ => None,
+    }
+}
@@ -0,0 +1,241 @@
+use std::path::PathBuf;
+
+use adw::{
+    prelude::*,
+    subclass::prelude::*,
+};
+use gettextrs::gettext;
+use gtk::{
+    gio,
+    glib,
+    template_callbacks,
+};
+
+use crate::{
+    client::{
+        emby_client::EMBY_CLIENT,
+        error::UserFacingError,
+    },
+    toast,
+    ui::{
+        models::screenshots,
+        provider::IS_ADMIN,
+    },
+    utils::{
+        spawn,
+        spawn_tokio,
+    },
+};
+
+mod imp {
+    use std::cell::{
+        OnceCell,
+        RefCell,
+    };
+
+    use glib::subclass::InitializingObject;
+    use gtk::CompositeTemplate;
+
+    use super::*;
+
+    #[derive(Debug, Default, CompositeTemplate, glib::Properties)]
+    #[template(resource = "/moe/tsuna/tsukimi/ui/screenshot_gallery.ui")]
+    #[properties(wrapper_type = super::ScreenshotGallery)]
+    pub struct ScreenshotGallery {
+        #[property(get, set, construct_only)]
+        pub id: OnceCell<String>,
+        #[template_child]
+        pub stack: TemplateChild<gtk::Stack>,
+        #[template_child]
+        pub flowbox: TemplateChild<gtk::FlowBox>,
+        #[template_child]
+        pub action_bar: TemplateChild<gtk::ActionBar>,
+        #[template_child]
+        pub set_image_button: TemplateChild<gtk::MenuButton>,
+        /// Screenshot shown by each flowbox child, in the same order.
+        pub paths: RefCell<Vec<PathBuf>>,
+    }
+
+    #[glib::object_subclass]
+    impl ObjectSubclass for ScreenshotGallery {
+        const NAME: &'static str = "ScreenshotGallery";
+        type Type = super::ScreenshotGallery;
+        type ParentType = adw::Dialog;
+
+        fn class_init(klass: &mut Self::Class) {
+            klass.bind_template();
+            klass.bind_template_instance_callbacks();
+            klass.install_action_async(
+                "gallery.set-image",
+                Some(glib::VariantTy::STRING),
+                |gallery, _action, parameter| async move {
+                    let Some(image_type) = parameter.and_then(|p| p.get::<String>()) else {
+                        return;
+                    };
+                    gallery.set_image(&image_type).await;
+                },
+            );
+        }
+
+        fn instance_init(obj: &InitializingObject<Self>) {
+            obj.init_template();
+        }
+    }
+
+    #[glib::derived_properties]
+    impl ObjectImpl for ScreenshotGallery {
+        fn constructed(&self) {
+            self.parent_constructed();
+            self.set_image_button
+                .set_visible(IS_ADMIN.load(std::sync::atomic::Ordering::Relaxed));
+            self.obj().load();
+        }
+    }
+
+    impl WidgetImpl for ScreenshotGallery {}
+    impl AdwDialogImpl for ScreenshotGallery {}
+}
+
+glib::wrapper! {
+    pub struct ScreenshotGallery(ObjectSubclass<imp::ScreenshotGallery>)
+        @extends gtk::Widget, adw::Dialog, @implements gtk::Accessible, gtk::Root;
+}
+
+#[template_callbacks]
+impl ScreenshotGallery {
+    pub fn new(id: &str) -> Self {
+        glib::Object::builder().property("id", id).build()
+    }
+
+    fn load(&self) {
+        let imp = self.imp();
+        imp.flowbox.remove_all();
+
+        let paths = screenshots::list(&self.id());
+        for path in &paths {
+            let picture = gtk::Picture::builder()
+                .content_fit(gtk::ContentFit::Cover)
+                .height_request(135)
+                .width_request(180)
+                .build();
+            picture.set_filename(Some(path));
+            if let Some(name) = path.file_stem() {
+                picture.set_tooltip_text(Some(&name.to_string_lossy()));
+            }
+            imp.flowbox.append(&picture);
+        }
+
+        imp.stack
+            .set_visible_child_name(if paths.is_empty() { "empty" } else { "gallery" });
+        imp.paths.replace(paths);
+        self.on_selection_changed();
+    }
+
+    fn selected_path(&self) -> Option<PathBuf> {
+        let imp = self.imp();
+        let child = imp.flowbox.selected_children().into_iter().next()?;
+        imp.paths.borrow().get(child.index() as usize).cloned()
+    }
+
+    fn launch(&self, path: PathBuf) {
+        spawn(glib::clone!(
+            #[weak(rename_to = obj)]
+            self,
+            async move {
+                let window = obj.root().and_downcast::<gtk::Window>();
+                if gtk::FileLauncher::new(Some(&gio::File::for_path(path)))
+                    .launch_future(window.as_ref())
+                    .await
+                    .is_err()
+                {
+                    toast!(obj.imp().stack, gettext("Failed to open file"));
+                }
+            }
+        ));
+    }
+
+    #[template_callback]
+    fn on_selection_changed(&self) {
+        let imp = self.imp();
+        imp.action_bar
+            .set_revealed(!imp.flowbox.selected_children().is_empty());
+    }
+
+    #[template_callback]
+    fn on_child_activated(&self, child: &gtk::FlowBoxChild) {
+        let Some(path) = self
+            .imp()
+            .paths
+            .borrow()
+            .get(child.index() as usize)
+            .cloned()
+        else {
+            return;
+        };
+        self.launch(path);
+    }
+
+    #[template_callback]
+    fn on_open_folder_clicked(&self) {
+        let dir = screenshots::item_dir(&self.id());
+        if !dir.exists() {
+            toast!(self.imp().stack, gettext("No screenshots yet"));
+            return;
+        }
+        self.launch(dir);
+    }
+
+    #[template_callback]
+    fn on_delete_clicked(&self) {
+        let Some(path) = self.selected_path() else {
+            return;
+        };
+        if std::fs::remove_file(&path).is_err() {
+            toast!(self.imp().stack, gettext("Failed to delete screenshot"));
+            return;
+        }
+        self.load();
+    }
+
+    async fn set_image(&self, image_type: &str) {
+        let imp = self.imp();
+        let Some(path) = self.selected_path() else {
+            return;
+        };
+        let Some(content_type) = screenshots::content_type(&path) else {
+            return;
+        };
+
+        let bytes = match gio::File::for_path(&path).load_bytes_future().await {
+            Ok((bytes, _)) => bytes.to_vec(),
+            Err(_) => {
+                toast!(imp.stack, gettext("Failed to read file"));
+                return;
+            }
+        };
+
+        use base64::{
+            engine::general_purpose::STANDARD,
+            Engine as _,
+        };
+        let data = STANDARD.encode(bytes);
+
+        let id = self.id();
+        let image_type = image_type.to_string();
+
+        imp.set_image_button.set_sensitive(false);
+
+        match spawn_tokio(async move {
+            EMBY_CLIENT
+                .post_image(&id, &image_type, data, content_type)
+                .await
+        })
+        .await
+        {
+            Ok(_) => toast!(imp.stack, gettext("Image updated")),
+            Err(e) => toast!(imp.stack, e.to_user_facing()),
+        }
+
+        imp.set_image_button.set_sensitive(true);
+    }
+}
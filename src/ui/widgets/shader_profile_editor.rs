@@ -0,0 +1,254 @@
+use adw::{
+    prelude::*,
+    subclass::prelude::*,
+};
+use gettextrs::gettext;
+use gtk::{
+    glib,
+    template_callbacks,
+};
+
+use crate::{
+    toast,
+    ui::models::shader_profiles::{
+        installed_shaders,
+        Deband,
+        ShaderProfile,
+        ShaderProfiles,
+    },
+};
+
+mod imp {
+    use std::{
+        cell::RefCell,
+        sync::OnceLock,
+    };
+
+    use glib::subclass::{
+        InitializingObject,
+        Signal,
+    };
+    use gtk::CompositeTemplate;
+
+    use super::*;
+
+    #[derive(Debug, Default, CompositeTemplate)]
+    #[template(resource = "/moe/tsuna/tsukimi/ui/shader_profile_editor.ui")]
+    pub struct ShaderProfileEditor {
+        #[template_child]
+        pub page: TemplateChild<adw::PreferencesPage>,
+        #[template_child]
+        pub name_entry: TemplateChild<adw::EntryRow>,
+        #[template_child]
+        pub chain_group: TemplateChild<adw::PreferencesGroup>,
+        #[template_child]
+        pub shader_combo: TemplateChild<adw::ComboRow>,
+        #[template_child]
+        pub scale_combo: TemplateChild<adw::ComboRow>,
+        #[template_child]
+        pub deband_row: TemplateChild<adw::ExpanderRow>,
+        #[template_child]
+        pub deband_iterations_adj: TemplateChild<gtk::Adjustment>,
+        #[template_child]
+        pub deband_threshold_adj: TemplateChild<gtk::Adjustment>,
+        #[template_child]
+        pub deband_range_adj: TemplateChild<gtk::Adjustment>,
+        #[template_child]
+        pub deband_grain_adj: TemplateChild<gtk::Adjustment>,
+        /// Name the profile had when the editor opened, `None` for a new one.
+        pub old_name: RefCell<Option<String>>,
+        pub installed: RefCell<Vec<String>>,
+        pub shaders: RefCell<Vec<String>>,
+        pub chain_rows: RefCell<Vec<adw::ActionRow>>,
+    }
+
+    #[glib::object_subclass]
+    impl ObjectSubclass for ShaderProfileEditor {
+        const NAME: &'static str = "ShaderProfileEditor";
+        type Type = super::ShaderProfileEditor;
+        type ParentType = adw::NavigationPage;
+
+        fn class_init(klass: &mut Self::Class) {
+            klass.bind_template();
+            klass.bind_template_instance_callbacks();
+        }
+
+        fn instance_init(obj: &InitializingObject<Self>) {
+            obj.init_template();
+        }
+    }
+
+    impl ObjectImpl for ShaderProfileEditor {
+        fn signals() -> &'static [Signal] {
+            static SIGNALS: OnceLock<Vec<Signal>> = OnceLock::new();
+            SIGNALS.get_or_init(|| vec![Signal::builder("saved").build()])
+        }
+    }
+
+    impl WidgetImpl for ShaderProfileEditor {}
+    impl NavigationPageImpl for ShaderProfileEditor {}
+}
+
+glib::wrapper! {
+    pub struct ShaderProfileEditor(ObjectSubclass<imp::ShaderProfileEditor>)
+        @extends gtk::Widget, adw::NavigationPage, @implements gtk::Accessible;
+}
+
+#[template_callbacks]
+impl ShaderProfileEditor {
+    pub fn new(profile: Option<&ShaderProfile>) -> Self {
+        let obj: Self = glib::Object::new();
+        obj.load(profile);
+        obj
+    }
+
+    fn load(&self, profile: Option<&ShaderProfile>) {
+        let imp = self.imp();
+
+        let installed = installed_shaders();
+        let model = gtk::StringList::new(&installed.iter().map(String::as_str).collect::<Vec<_>>());
+        imp.shader_combo
+            .set_expression(Some(gtk::PropertyExpression::new(
+                gtk::StringObject::static_type(),
+                None::<gtk::Expression>,
+                "string",
+            )));
+        imp.shader_combo.set_model(Some(&model));
+        imp.shader_combo.set_sensitive(!installed.is_empty());
+        imp.installed.replace(installed);
+
+        let Some(profile) = profile else {
+            imp.scale_combo
+                .set_selected(crate::ui::models::SETTINGS.mpv_video_scale() as u32);
+            return;
+        };
+
+        imp.old_name.replace(Some(profile.name.clone()));
+        imp.name_entry.set_text(&profile.name);
+        imp.scale_combo.set_selected(profile.scale as u32);
+        imp.deband_row
+            .set_enable_expansion(profile.deband.is_some());
+        let deband = profile.deband.unwrap_or_default();
+        imp.deband_iterations_adj
+            .set_value(deband.iterations as f64);
+        imp.deband_threshold_adj.set_value(deband.threshold as f64);
+        imp.deband_range_adj.set_value(deband.range as f64);
+        imp.deband_grain_adj.set_value(deband.grain as f64);
+        imp.shaders.replace(profile.shaders.clone());
+        self.update_chain();
+    }
+
+    fn update_chain(&self) {
+        let imp = self.imp();
+        for row in imp.chain_rows.take() {
+            imp.chain_group.remove(&row);
+        }
+
+        let shaders = imp.shaders.borrow().clone();
+        let mut rows = Vec::new();
+        for (index, shader) in shaders.iter().enumerate() {
+            let (pack, file_name) = shader.rsplit_once('/').unwrap_or(("", shader));
+            let row = adw::ActionRow::builder()
+                .title(file_name)
+                .subtitle(pack)
+                .use_markup(false)
+                .build();
+
+            let up = self.chain_button("go-up-symbolic", &gettext("Move Up"), index, -1);
+            up.set_sensitive(index > 0);
+            row.add_suffix(&up);
+            let down = self.chain_button("go-down-symbolic", &gettext("Move Down"), index, 1);
+            down.set_sensitive(index + 1 < shaders.len());
+            row.add_suffix(&down);
+            row.add_suffix(&self.chain_button("user-trash-symbolic", &gettext("Remove"), index, 0));
+
+            imp.chain_group.add(&row);
+            rows.push(row);
+        }
+        imp.chain_rows.replace(rows);
+    }
+
+    /// Button moving shader `index` by `offset`, removing it when `offset`
+    /// is 0.
+    fn chain_button(&self, icon: &str, tooltip: &str, index: usize, offset: isize) -> gtk::Button {
+        let button = gtk::Button::builder()
+            .icon_name(icon)
+            .tooltip_text(tooltip)
+            .valign(gtk::Align::Center)
+            .css_classes(["flat"])
+            .build();
+        button.connect_clicked(glib::clone!(
+            #[weak(rename_to = obj)]
+            self,
+            move |_| {
+                {
+                    let mut shaders = obj.imp().shaders.borrow_mut();
+                    if offset == 0 {
+                        shaders.remove(index);
+                    } else if let Some(target) = index.checked_add_signed(offset) {
+                        if target < shaders.len() {
+                            shaders.swap(index, target);
+                        }
+                    }
+                }
+                obj.update_chain();
+            }
+        ));
+        button
+    }
+
+    #[template_callback]
+    fn on_add_shader_clicked(&self) {
+        let imp = self.imp();
+        let selected = imp.shader_combo.selected() as usize;
+        let Some(shader) = imp.installed.borrow().get(selected).cloned() else {
+            return;
+        };
+        imp.shaders.borrow_mut().push(shader);
+        self.update_chain();
+    }
+
+    #[template_callback]
+    fn on_save_clicked(&self) {
+        let imp = self.imp();
+        let name = imp.name_entry.text().trim().to_string();
+        if name.is_empty() {
+            toast!(imp.page, gettext("Please enter a name"));
+            return;
+        }
+
+        let old_name = imp.old_name.borrow().clone();
+        let mut profiles = ShaderProfiles::load();
+        if old_name.as_deref() != Some(name.as_str()) && profiles.get(&name).is_some() {
+            toast!(imp.page, gettext("A profile with this name already exists"));
+            return;
+        }
+
+        let deband = imp.deband_row.enables_expansion().then(|| Deband {
+            iterations: imp.deband_iterations_adj.value() as i64,
+            threshold: imp.deband_threshold_adj.value() as i64,
+            range: imp.deband_range_adj.value() as i64,
+            grain: imp.deband_grain_adj.value() as i64,
+        });
+        let profile = ShaderProfile {
+            name,
+            shaders: imp.shaders.borrow().clone(),
+            scale: imp.scale_combo.selected() as i32,
+            deband,
+        };
+
+        profiles.upsert(old_name.as_deref(), profile);
+        profiles.save();
+        self.emit_by_name::<()>("saved", &[]);
+    }
+
+    pub fn connect_saved<F: Fn(&Self) + 'static>(&self, f: F) -> glib::SignalHandlerId {
+        self.connect_closure(
+            "saved",
+            true,
+            glib::closure_local!(move |obj: Self| {
+                f(&obj);
+            }),
+        )
+    }
+}
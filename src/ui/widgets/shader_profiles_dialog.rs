@@ -0,0 +1,329 @@
+use adw::{
+    prelude::*,
+    subclass::prelude::*,
+};
+use gettextrs::gettext;
+use gtk::{
+    gio,
+    glib,
+    template_callbacks,
+};
+
+use super::shader_profile_editor::ShaderProfileEditor;
+use crate::{
+    client::emby_client::EMBY_CLIENT,
+    toast,
+    ui::{
+        models::shader_profiles::{
+            import_pack,
+            installed_shaders,
+            shaders_dir,
+            ShaderProfiles,
+            COLLECTION_TYPES,
+        },
+        mpv::options_matcher::match_video_upscale,
+    },
+    utils::{
+        spawn,
+        spawn_tokio,
+    },
+};
+
+mod imp {
+    use std::cell::RefCell;
+
+    use glib::subclass::InitializingObject;
+    use gtk::CompositeTemplate;
+
+    use super::*;
+
+    #[derive(Debug, Default, CompositeTemplate)]
+    #[template(resource = "/moe/tsuna/tsukimi/ui/shader_profiles_dialog.ui")]
+    pub struct ShaderProfilesDialog {
+        #[template_child]
+        pub navigation_view: TemplateChild<adw::NavigationView>,
+        #[template_child]
+        pub page: TemplateChild<adw::PreferencesPage>,
+        #[template_child]
+        pub profiles_group: TemplateChild<adw::PreferencesGroup>,
+        #[template_child]
+        pub defaults_group: TemplateChild<adw::PreferencesGroup>,
+        #[template_child]
+        pub shaders_group: TemplateChild<adw::PreferencesGroup>,
+        /// Rows added in code, by the group holding them.
+        pub rows: RefCell<Vec<(adw::PreferencesGroup, gtk::Widget)>>,
+        /// Id and name of the video libraries of the server.
+        pub libraries: RefCell<Vec<(String, String)>>,
+    }
+
+    #[glib::object_subclass]
+    impl ObjectSubclass for ShaderProfilesDialog {
+        const NAME: &'static str = "ShaderProfilesDialog";
+        type Type = super::ShaderProfilesDialog;
+        type ParentType = adw::Dialog;
+
+        fn class_init(klass: &mut Self::Class) {
+            klass.bind_template();
+            klass.bind_template_instance_callbacks();
+        }
+
+        fn instance_init(obj: &InitializingObject<Self>) {
+            obj.init_template();
+        }
+    }
+
+    impl ObjectImpl for ShaderProfilesDialog {
+        fn constructed(&self) {
+            self.parent_constructed();
+            let obj = self.obj();
+            obj.reload();
+            spawn(glib::clone!(
+                #[weak]
+                obj,
+                async move {
+                    obj.load_libraries().await;
+                }
+            ));
+        }
+    }
+
+    impl WidgetImpl for ShaderProfilesDialog {}
+    impl AdwDialogImpl for ShaderProfilesDialog {}
+}
+
+glib::wrapper! {
+    pub struct ShaderProfilesDialog(ObjectSubclass<imp::ShaderProfilesDialog>)
+        @extends gtk::Widget, adw::Dialog, @implements gtk::Accessible, gtk::Root;
+}
+
+impl Default for ShaderProfilesDialog {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[template_callbacks]
+impl ShaderProfilesDialog {
+    pub fn new() -> Self {
+        glib::Object::new()
+    }
+
+    async fn load_libraries(&self) {
+        let Ok(views) = spawn_tokio(async move { EMBY_CLIENT.get_library().await }).await else {
+            return;
+        };
+        let libraries = views
+            .items
+            .into_iter()
+            .filter(|view| {
+                view.collection_type
+                    .as_deref()
+                    .map_or(true, |kind| COLLECTION_TYPES.contains(&kind))
+            })
+            .map(|view| (view.id, view.name))
+            .collect();
+        self.imp().libraries.replace(libraries);
+        self.reload();
+    }
+
+    fn add_row(&self, group: &adw::PreferencesGroup, row: &impl IsA<gtk::Widget>) {
+        group.add(row);
+        self.imp()
+            .rows
+            .borrow_mut()
+            .push((group.clone(), row.clone().upcast()));
+    }
+
+    fn reload(&self) {
+        let imp = self.imp();
+        for (group, row) in imp.rows.take() {
+            group.remove(&row);
+        }
+
+        let profiles = ShaderProfiles::load();
+        self.load_profiles(&profiles);
+        self.load_defaults(&profiles);
+        self.load_shaders();
+    }
+
+    fn load_profiles(&self, profiles: &ShaderProfiles) {
+        let group = self.imp().profiles_group.get();
+        for profile in &profiles.profiles {
+            let row = adw::ActionRow::builder()
+                .title(&profile.name)
+                .subtitle(format!(
+                    "{} · {}",
+                    profile.shaders.len(),
+                    match_video_upscale(profile.scale)
+                ))
+                .use_markup(false)
+                .activatable(true)
+                .build();
+            row.add_suffix(&gtk::Image::from_icon_name("go-next-symbolic"));
+
+            let name = profile.name.clone();
+            row.connect_activated(glib::clone!(
+                #[weak(rename_to = obj)]
+                self,
+                #[strong]
+                name,
+                move |_| obj.edit(Some(&name))
+            ));
+
+            let delete = gtk::Button::builder()
+                .icon_name("user-trash-symbolic")
+                .tooltip_text(gettext("Delete"))
+                .valign(gtk::Align::Center)
+                .css_classes(["flat"])
+                .build();
+            delete.connect_clicked(glib::clone!(
+                #[weak(rename_to = obj)]
+                self,
+                move |_| {
+                    let mut profiles = ShaderProfiles::load();
+                    profiles.remove(&name);
+                    profiles.save();
+                    obj.reload();
+                }
+            ));
+            row.add_prefix(&delete);
+
+            self.add_row(&group, &row);
+        }
+    }
+
+    fn load_defaults(&self, profiles: &ShaderProfiles) {
+        let imp = self.imp();
+        let group = imp.defaults_group.get();
+        group.set_visible(!profiles.profiles.is_empty());
+        if profiles.profiles.is_empty() {
+            return;
+        }
+
+        let names = profiles.names();
+        let kinds = COLLECTION_TYPES
+            .iter()
+            .map(|kind| (kind.to_string(), collection_type_title(kind), None));
+        let libraries = imp
+            .libraries
+            .borrow()
+            .clone()
+            .into_iter()
+            .map(|(id, name)| (id, name, Some(gettext("Library"))));
+
+        for (key, title, subtitle) in kinds.chain(libraries) {
+            let mut items = vec![gettext("None")];
+            items.extend(names.iter().cloned());
+            let model = gtk::StringList::new(&items.iter().map(String::as_str).collect::<Vec<_>>());
+
+            let row = adw::ComboRow::builder()
+                .title(&title)
+                .use_markup(false)
+                .model(&model)
+                .build();
+            if let Some(subtitle) = subtitle {
+                row.set_subtitle(&subtitle);
+            }
+            let selected = profiles
+                .defaults
+                .get(&key)
+                .and_then(|name| names.iter().position(|n| n == name))
+                .map_or(0, |position| position + 1);
+            row.set_selected(selected as u32);
+
+            let names = names.clone();
+            row.connect_selected_notify(move |row| {
+                let name = (row.selected() as usize)
+                    .checked_sub(1)
+                    .and_then(|index| names.get(index));
+                let mut profiles = ShaderProfiles::load();
+                profiles.set_default(&key, name.map(String::as_str));
+                profiles.save();
+            });
+
+            self.add_row(&group, &row);
+        }
+    }
+
+    fn load_shaders(&self) {
+        let group = self.imp().shaders_group.get();
+        group.set_description(Some(&shaders_dir().to_string_lossy()));
+
+        let shaders = installed_shaders();
+        if shaders.is_empty() {
+            let row = adw::ActionRow::builder()
+                .title(gettext("No shaders installed"))
+                .subtitle(gettext(
+                    "Import a folder of GLSL shaders, e.g. Anime4K or FSRCNNX",
+                ))
+                .build();
+            self.add_row(&group, &row);
+            return;
+        }
+
+        for shader in shaders {
+            let (pack, file_name) = shader.rsplit_once('/').unwrap_or(("", &shader));
+            let row = adw::ActionRow::builder()
+                .title(file_name)
+                .subtitle(pack)
+                .use_markup(false)
+                .build();
+            self.add_row(&group, &row);
+        }
+    }
+
+    fn edit(&self, name: Option<&str>) {
+        let profiles = ShaderProfiles::load();
+        let editor = ShaderProfileEditor::new(name.and_then(|name| profiles.get(name)));
+        editor.connect_saved(glib::clone!(
+            #[weak(rename_to = obj)]
+            self,
+            move |_| {
+                obj.imp().navigation_view.pop();
+                obj.reload();
+            }
+        ));
+        self.imp().navigation_view.push(&editor);
+    }
+
+    #[template_callback]
+    fn on_new_profile_clicked(&self) {
+        self.edit(None);
+    }
+
+    #[template_callback]
+    async fn on_import_clicked(&self) {
+        let window = self.root().and_downcast::<gtk::Window>();
+        let filedialog = gtk::FileDialog::builder()
+            .modal(true)
+            .title(gettext("Select a Shader Pack Folder"))
+            .build();
+
+        let Ok(folder) = filedialog.select_folder_future(window.as_ref()).await else {
+            return;
+        };
+        let Some(path) = folder.path() else {
+            return;
+        };
+
+        let page = self.imp().page.get();
+        match gio::spawn_blocking(move || import_pack(&path)).await {
+            Ok(Ok(0)) => toast!(page, gettext("No shaders found in this folder")),
+            Ok(Ok(_)) => toast!(page, gettext("Shader pack imported")),
+            Ok(Err(e)) => toast!(page, e.to_string()),
+            Err(_) => toast!(page, gettext("Failed to import shader pack")),
+        }
+        self.reload();
+    }
+}
+
+fn collection_type_title(kind: &str) -> String {
+    match kind {
+        "movies" => gettext("Movies"),
+        "tvshows" => gettext("TV Shows"),
+        "homevideos" => gettext("Home Videos"),
+        "musicvideos" => gettext("Music Videos"),
+        "mixed" => gettext("Mixed Content"),
+        _ => kind.to_string(),
+    }
+}
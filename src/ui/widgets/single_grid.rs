@@ -85,6 +85,8 @@ pub mod imp {
         BoxSet,
         Tags,
         Genres,
+        AlbumArtists,
+        Artists,
         Liked,
         Folder,
         #[default]
@@ -399,7 +401,7 @@ impl SingleGrid {
                 imp.glbutton.set_visible(false);
                 imp.filter.set_visible(false);
             }
-            ListType::Genres => {
+            ListType::Genres | ListType::AlbumArtists | ListType::Artists => {
                 imp.postmenu.set_visible(false);
                 imp.dropdown.set_visible(false);
                 imp.adbutton.set_visible(false);
@@ -8,7 +8,11 @@ use gtk::{
     CompositeTemplate,
 };
 
+use gettextrs::gettext;
+
+use super::window::Window;
 use crate::{
+    toast,
     ui::provider::{
         actions::HasLikeAction,
         core_song::CoreSong,
@@ -93,6 +97,18 @@ pub(crate) mod imp {
                     insert_editm_dialog!(window, dialog);
                 },
             );
+            klass.install_action("song.play-next", None, |obj, _action, _parameter| {
+                obj.queue(true);
+            });
+            klass.install_action("song.add-to-queue", None, |obj, _action, _parameter| {
+                obj.queue(false);
+            });
+            klass.install_action("song.instant-mix", None, |obj, _action, _parameter| {
+                obj.instant_mix(false);
+            });
+            klass.install_action("song.radio", None, |obj, _action, _parameter| {
+                obj.instant_mix(true);
+            });
             klass.install_action_async(
                 "song.editi",
                 None,
@@ -116,23 +132,7 @@ pub(crate) mod imp {
 
             let obj = self.obj();
             obj.set_up();
-
-            let core_song = self.obj().coresong();
-
-            let item = obj.item();
-            if let Some(album_id) = item.album_id() {
-                core_song.set_album_id(album_id);
-            }
-            core_song.set_artist(item.albumartist_name());
-            core_song.set_name(item.name());
-            core_song.set_id(item.id());
-            if let Some(image_tags) = item.image_tags() {
-                if image_tags.primary().is_some() {
-                    core_song.set_have_single_track_image(true);
-                }
-            }
-            core_song.set_duration(item.run_time_ticks() / 10000000);
-            obj.bind(&core_song);
+            obj.bind(&obj.coresong());
         }
     }
     impl WidgetImpl for SongWidget {}
@@ -172,7 +172,7 @@ glib::wrapper! {
 impl SongWidget {
     pub fn new(item: TuItem) -> Self {
         glib::Object::builder()
-            .property("coresong", CoreSong::new(&item.id()))
+            .property("coresong", CoreSong::from_item(&item))
             .property("item", item)
             .build()
     }
@@ -199,6 +199,25 @@ impl SongWidget {
         ));
     }
 
+    fn queue(&self, next: bool) {
+        let Some(window) = self.root().and_downcast::<Window>() else {
+            return;
+        };
+        window.queue_song(self.coresong(), next);
+        if next {
+            toast!(self, gettext("Playing next"));
+        } else {
+            toast!(self, gettext("Added to queue"));
+        }
+    }
+
+    fn instant_mix(&self, radio: bool) {
+        let Some(window) = self.root().and_downcast::<Window>() else {
+            return;
+        };
+        window.play_instant_mix(self.item().id(), "Audio".to_string(), radio);
+    }
+
     fn bind(&self, core_song: &CoreSong) {
         self.bind_property("state", core_song, "state")
             .sync_create()
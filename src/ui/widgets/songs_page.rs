@@ -0,0 +1,331 @@
+use adw::{
+    prelude::*,
+    subclass::prelude::*,
+};
+use gtk::{
+    gio,
+    glib,
+    template_callbacks,
+    CompositeTemplate,
+};
+
+use super::{
+    song_widget::format_duration,
+    window::Window,
+};
+use crate::{
+    client::{
+        emby_client::EMBY_CLIENT,
+        error::UserFacingError,
+        structs::TICKS_PER_SECOND,
+    },
+    toast,
+    ui::provider::{
+        core_song::CoreSong,
+        tu_item::TuItem,
+    },
+    utils::{
+        spawn,
+        spawn_tokio,
+    },
+};
+
+mod imp {
+    use std::cell::{
+        Cell,
+        OnceCell,
+    };
+
+    use glib::subclass::InitializingObject;
+
+    use super::*;
+
+    #[derive(Debug, Default, CompositeTemplate, glib::Properties)]
+    #[template(resource = "/moe/tsuna/tsukimi/ui/songs_page.ui")]
+    #[properties(wrapper_type = super::SongsPage)]
+    pub struct SongsPage {
+        /// Library the songs are listed from.
+        #[property(get, set, construct_only)]
+        pub id: OnceCell<String>,
+        #[template_child]
+        pub search_entry: TemplateChild<gtk::SearchEntry>,
+        #[template_child]
+        pub favorites_button: TemplateChild<gtk::ToggleButton>,
+        #[template_child]
+        pub stack: TemplateChild<gtk::Stack>,
+        #[template_child]
+        pub column_view: TemplateChild<gtk::ColumnView>,
+        #[template_child]
+        pub title_column: TemplateChild<gtk::ColumnViewColumn>,
+        #[template_child]
+        pub artist_column: TemplateChild<gtk::ColumnViewColumn>,
+        #[template_child]
+        pub album_column: TemplateChild<gtk::ColumnViewColumn>,
+        #[template_child]
+        pub duration_column: TemplateChild<gtk::ColumnViewColumn>,
+        #[template_child]
+        pub plays_column: TemplateChild<gtk::ColumnViewColumn>,
+        #[template_child]
+        pub rating_column: TemplateChild<gtk::ColumnViewColumn>,
+
+        pub selection: gtk::SingleSelection,
+        pub total: Cell<u32>,
+        pub loading: Cell<bool>,
+        /// Bumped on every reload so pages of a stale query are dropped.
+        pub generation: Cell<u32>,
+    }
+
+    #[glib::object_subclass]
+    impl ObjectSubclass for SongsPage {
+        const NAME: &'static str = "SongsPage";
+        type Type = super::SongsPage;
+        type ParentType = adw::Bin;
+
+        fn class_init(klass: &mut Self::Class) {
+            klass.bind_template();
+            klass.bind_template_instance_callbacks();
+        }
+
+        fn instance_init(obj: &InitializingObject<Self>) {
+            obj.init_template();
+        }
+    }
+
+    #[glib::derived_properties]
+    impl ObjectImpl for SongsPage {
+        fn constructed(&self) {
+            self.parent_constructed();
+            let obj = self.obj();
+
+            self.selection.set_autoselect(false);
+            self.selection
+                .set_model(Some(&gio::ListStore::new::<TuItem>()));
+            self.column_view.set_model(Some(&self.selection));
+
+            self.title_column
+                .set_factory(Some(&label_factory(false, |item| item.name())));
+            self.artist_column
+                .set_factory(Some(&label_factory(false, |item| {
+                    item.artists().unwrap_or_default()
+                })));
+            self.album_column
+                .set_factory(Some(&label_factory(false, |item| {
+                    item.album().unwrap_or_default()
+                })));
+            self.duration_column
+                .set_factory(Some(&label_factory(true, |item| {
+                    format_duration((item.run_time_ticks() as f64 / TICKS_PER_SECOND) as i64)
+                })));
+            self.plays_column
+                .set_factory(Some(&label_factory(true, |item| {
+                    item.play_count().to_string()
+                })));
+            self.rating_column
+                .set_factory(Some(&label_factory(true, |item| {
+                    item.rating().unwrap_or_default()
+                })));
+
+            // The server sorts, the columns only need a sorter to be clickable
+            for column in [
+                &self.title_column,
+                &self.artist_column,
+                &self.album_column,
+                &self.duration_column,
+                &self.plays_column,
+                &self.rating_column,
+            ] {
+                column.set_sorter(Some(&gtk::CustomSorter::new(|_, _| gtk::Ordering::Equal)));
+            }
+
+            if let Some(sorter) = self.column_view.sorter() {
+                sorter.connect_changed(glib::clone!(
+                    #[weak]
+                    obj,
+                    move |_, _| {
+                        obj.reload();
+                    }
+                ));
+            }
+            self.column_view
+                .sort_by_column(Some(&self.title_column), gtk::SortType::Ascending);
+        }
+    }
+
+    impl WidgetImpl for SongsPage {}
+
+    impl BinImpl for SongsPage {}
+}
+
+glib::wrapper! {
+    /// A sortable table of every song in a music library.
+    pub struct SongsPage(ObjectSubclass<imp::SongsPage>)
+        @extends gtk::Widget, adw::Bin, @implements gtk::Accessible;
+}
+
+fn label_factory<F>(numeric: bool, text: F) -> gtk::SignalListItemFactory
+where
+    F: Fn(&TuItem) -> String + 'static,
+{
+    let factory = gtk::SignalListItemFactory::new();
+    factory.connect_setup(move |_, item| {
+        let label = gtk::Label::builder()
+            .xalign(if numeric { 1.0 } else { 0.0 })
+            .ellipsize(gtk::pango::EllipsizeMode::End)
+            .build();
+        if numeric {
+            label.add_css_class("numeric");
+        }
+        item.downcast_ref::<gtk::ListItem>()
+            .expect("Needs to be ListItem")
+            .set_child(Some(&label));
+    });
+    factory.connect_bind(move |_, item| {
+        let list_item = item
+            .downcast_ref::<gtk::ListItem>()
+            .expect("Needs to be ListItem");
+        let (Some(label), Some(tu_item)) = (
+            list_item.child().and_downcast::<gtk::Label>(),
+            list_item.item().and_downcast::<TuItem>(),
+        ) else {
+            return;
+        };
+        label.set_text(&text(&tu_item));
+    });
+    factory
+}
+
+#[template_callbacks]
+impl SongsPage {
+    pub fn new(id: &str) -> Self {
+        glib::Object::builder().property("id", id).build()
+    }
+
+    fn store(&self) -> gio::ListStore {
+        self.imp()
+            .selection
+            .model()
+            .and_downcast::<gio::ListStore>()
+            .unwrap()
+    }
+
+    fn sort_params(&self) -> (String, String) {
+        let sorter = self
+            .imp()
+            .column_view
+            .sorter()
+            .and_downcast::<gtk::ColumnViewSorter>();
+        let sort_by = sorter
+            .as_ref()
+            .and_then(|sorter| sorter.primary_sort_column())
+            .and_then(|column| column.id())
+            .map(|id| id.to_string())
+            .unwrap_or_else(|| "SortName".to_string());
+        let sort_order = match sorter.map(|sorter| sorter.primary_sort_order()) {
+            Some(gtk::SortType::Descending) => "Descending",
+            _ => "Ascending",
+        };
+        (sort_by, sort_order.to_string())
+    }
+
+    fn reload(&self) {
+        let imp = self.imp();
+        imp.generation.set(imp.generation.get().wrapping_add(1));
+        imp.total.set(0);
+        imp.loading.set(false);
+        self.store().remove_all();
+        imp.stack.set_visible_child_name("loading");
+        self.load_more();
+    }
+
+    fn load_more(&self) {
+        let imp = self.imp();
+        let start = self.store().n_items();
+        if imp.loading.get() || (start > 0 && start >= imp.total.get()) {
+            return;
+        }
+        imp.loading.set(true);
+
+        let generation = imp.generation.get();
+        let id = self.id();
+        let (sort_by, sort_order) = self.sort_params();
+        let search = imp.search_entry.text().to_string();
+        let favorites = imp.favorites_button.is_active();
+
+        spawn(glib::clone!(
+            #[weak(rename_to = obj)]
+            self,
+            async move {
+                let result = spawn_tokio(async move {
+                    EMBY_CLIENT
+                        .get_library_songs(&id, start, &sort_by, &sort_order, &search, favorites)
+                        .await
+                })
+                .await;
+
+                let imp = obj.imp();
+                if imp.generation.get() != generation {
+                    return;
+                }
+                imp.loading.set(false);
+
+                let store = obj.store();
+                match result {
+                    Ok(list) => {
+                        imp.total.set(list.total_record_count);
+                        let items: Vec<TuItem> = list
+                            .items
+                            .iter()
+                            .map(|item| TuItem::from_simple(item, None))
+                            .collect();
+                        store.extend_from_slice(&items);
+                    }
+                    Err(e) => {
+                        toast!(obj, e.to_user_facing());
+                    }
+                }
+
+                imp.stack.set_visible_child_name(if store.n_items() == 0 {
+                    "empty"
+                } else {
+                    "songs"
+                });
+            }
+        ));
+    }
+
+    #[template_callback]
+    fn on_search_changed(&self) {
+        self.reload();
+    }
+
+    #[template_callback]
+    fn on_favorites_toggled(&self) {
+        self.reload();
+    }
+
+    #[template_callback]
+    fn on_edge_reached(&self, pos: gtk::PositionType) {
+        if pos == gtk::PositionType::Bottom {
+            self.load_more();
+        }
+    }
+
+    #[template_callback]
+    fn on_row_activated(&self, position: u32) {
+        let Some(window) = self.root().and_downcast::<Window>() else {
+            return;
+        };
+        let songs: Vec<CoreSong> = self
+            .store()
+            .iter::<TuItem>()
+            .flatten()
+            .map(|item| CoreSong::from_item(&item))
+            .collect();
+        let Some(active) = songs.get(position as usize).cloned() else {
+            return;
+        };
+        let model = gio::ListStore::new::<CoreSong>();
+        model.extend_from_slice(&songs);
+        window.bind_song_model(model, active);
+    }
+}
@@ -0,0 +1,359 @@
+use adw::{
+    prelude::*,
+    subclass::prelude::*,
+};
+use gettextrs::gettext;
+use gtk::{
+    glib,
+    template_callbacks,
+};
+
+use crate::{
+    client::{
+        emby_client::EMBY_CLIENT,
+        error::UserFacingError,
+        structs::RemoteSubtitleInfo,
+    },
+    toast,
+    ui::models::languages::{
+        language_name,
+        subtitle_language_codes,
+    },
+    utils::{
+        spawn,
+        spawn_tokio,
+    },
+};
+
+/// How many times to look for the downloaded stream, the server adds it
+/// after a refresh it only queues.
+const POLL_ATTEMPTS: u32 = 10;
+const POLL_INTERVAL_SECS: u32 = 2;
+
+mod imp {
+    use std::{
+        cell::{
+            OnceCell,
+            RefCell,
+        },
+        sync::OnceLock,
+    };
+
+    use glib::subclass::{
+        InitializingObject,
+        Signal,
+    };
+    use gtk::CompositeTemplate;
+
+    use super::*;
+
+    #[derive(Debug, Default, CompositeTemplate, glib::Properties)]
+    #[template(resource = "/moe/tsuna/tsukimi/ui/subtitle_search_dialog.ui")]
+    #[properties(wrapper_type = super::SubtitleSearchDialog)]
+    pub struct SubtitleSearchDialog {
+        #[property(get, set, construct_only)]
+        pub id: OnceCell<String>,
+        #[property(get, set, construct_only)]
+        pub media_source_id: OnceCell<String>,
+        #[template_child]
+        pub language_dropdown: TemplateChild<gtk::DropDown>,
+        #[template_child]
+        pub stack: TemplateChild<gtk::Stack>,
+        #[template_child]
+        pub results_listbox: TemplateChild<gtk::ListBox>,
+        pub language_codes: RefCell<Vec<String>>,
+    }
+
+    #[glib::object_subclass]
+    impl ObjectSubclass for SubtitleSearchDialog {
+        const NAME: &'static str = "SubtitleSearchDialog";
+        type Type = super::SubtitleSearchDialog;
+        type ParentType = adw::Dialog;
+
+        fn class_init(klass: &mut Self::Class) {
+            klass.bind_template();
+            klass.bind_template_instance_callbacks();
+        }
+
+        fn instance_init(obj: &InitializingObject<Self>) {
+            obj.init_template();
+        }
+    }
+
+    #[glib::derived_properties]
+    impl ObjectImpl for SubtitleSearchDialog {
+        fn signals() -> &'static [Signal] {
+            static SIGNALS: OnceLock<Vec<Signal>> = OnceLock::new();
+            SIGNALS.get_or_init(|| {
+                vec![Signal::builder("subtitle-downloaded")
+                    .param_types([String::static_type()])
+                    .build()]
+            })
+        }
+
+        fn constructed(&self) {
+            self.parent_constructed();
+            let obj = self.obj();
+            obj.set_languages();
+            spawn(glib::clone!(
+                #[weak]
+                obj,
+                async move {
+                    obj.search().await;
+                }
+            ));
+        }
+    }
+
+    impl WidgetImpl for SubtitleSearchDialog {}
+    impl AdwDialogImpl for SubtitleSearchDialog {}
+}
+
+glib::wrapper! {
+    pub struct SubtitleSearchDialog(ObjectSubclass<imp::SubtitleSearchDialog>)
+        @extends gtk::Widget, adw::Dialog, @implements gtk::Accessible, gtk::Root;
+}
+
+#[template_callbacks]
+impl SubtitleSearchDialog {
+    pub fn new(id: &str, media_source_id: &str) -> Self {
+        glib::Object::builder()
+            .property("id", id)
+            .property("media-source-id", media_source_id)
+            .build()
+    }
+
+    fn set_languages(&self) {
+        let imp = self.imp();
+        let codes = subtitle_language_codes();
+        let names: Vec<String> = codes.iter().map(|code| language_name(code)).collect();
+        let model = gtk::StringList::new(&names.iter().map(String::as_str).collect::<Vec<_>>());
+
+        imp.language_dropdown
+            .set_expression(Some(gtk::PropertyExpression::new(
+                gtk::StringObject::static_type(),
+                None::<gtk::Expression>,
+                "string",
+            )));
+        imp.language_dropdown.set_model(Some(&model));
+        imp.language_codes.replace(codes);
+    }
+
+    fn selected_language(&self) -> Option<String> {
+        let imp = self.imp();
+        let selected = imp.language_dropdown.selected() as usize;
+        imp.language_codes.borrow().get(selected).cloned()
+    }
+
+    #[template_callback]
+    async fn on_search_clicked(&self) {
+        self.search().await;
+    }
+
+    async fn search(&self) {
+        let imp = self.imp();
+        let Some(language) = self.selected_language() else {
+            return;
+        };
+
+        imp.stack.set_visible_child_name("loading");
+
+        let id = self.id();
+        let media_source_id = self.media_source_id();
+        let results = match spawn_tokio(async move {
+            EMBY_CLIENT
+                .search_remote_subtitles(&id, &media_source_id, &language)
+                .await
+        })
+        .await
+        {
+            Ok(results) => results,
+            Err(e) => {
+                toast!(imp.stack, e.to_user_facing());
+                imp.stack.set_visible_child_name("empty");
+                return;
+            }
+        };
+
+        imp.results_listbox.remove_all();
+
+        if results.is_empty() {
+            imp.stack.set_visible_child_name("empty");
+            return;
+        }
+
+        for info in results {
+            imp.results_listbox.append(&self.result_row(info));
+        }
+
+        imp.stack.set_visible_child_name("results");
+    }
+
+    fn result_row(&self, info: RemoteSubtitleInfo) -> adw::ActionRow {
+        let mut details = Vec::new();
+        details.extend(info.provider_name.clone());
+        details.extend(info.format.as_ref().map(|format| format.to_uppercase()));
+        details.extend(info.language.clone());
+        if info.is_forced == Some(true) {
+            details.push(gettext("Forced"));
+        }
+        if info.is_hash_match == Some(true) {
+            details.push(gettext("Hash Match"));
+        }
+        if let Some(count) = info.download_count {
+            details.push(format!("{} {}", count, gettext("Downloads")));
+        }
+        if let Some(rating) = info.community_rating {
+            details.push(format!("★ {:.1}", rating));
+        }
+        details.extend(
+            info.date_created
+                .as_ref()
+                .map(|date| date.chars().take(10).collect::<String>()),
+        );
+        details.extend(info.author.clone());
+
+        let mut subtitle = details.join(" · ");
+        if let Some(comment) = info.comment.as_ref().filter(|c| !c.is_empty()) {
+            subtitle.push('\n');
+            subtitle.push_str(comment);
+        }
+
+        let row = adw::ActionRow::builder()
+            .title(info.name.clone().unwrap_or_else(|| info.id.clone()))
+            .subtitle(subtitle)
+            .use_markup(false)
+            .subtitle_lines(3)
+            .build();
+
+        let download_button = gtk::Button::builder()
+            .icon_name("folder-download-symbolic")
+            .tooltip_text(gettext("Download"))
+            .valign(gtk::Align::Center)
+            .css_classes(["flat"])
+            .build();
+
+        download_button.connect_clicked(glib::clone!(
+            #[weak(rename_to = obj)]
+            self,
+            move |button| {
+                let subtitle_id = info.id.clone();
+                spawn(glib::clone!(
+                    #[weak]
+                    obj,
+                    #[weak]
+                    button,
+                    async move {
+                        button.set_sensitive(false);
+                        if !obj.download(subtitle_id).await {
+                            button.set_sensitive(true);
+                        }
+                    }
+                ));
+            }
+        ));
+
+        row.add_suffix(&download_button);
+        row
+    }
+
+    async fn download(&self, subtitle_id: String) -> bool {
+        let stack = self.imp().stack.get();
+        let known = match self.subtitle_streams().await {
+            Ok(streams) => streams,
+            Err(e) => {
+                toast!(stack, e.to_user_facing());
+                return false;
+            }
+        };
+
+        let id = self.id();
+        let media_source_id = self.media_source_id();
+        if let Err(e) = spawn_tokio(async move {
+            EMBY_CLIENT
+                .download_remote_subtitles(&id, &media_source_id, &subtitle_id)
+                .await
+        })
+        .await
+        {
+            toast!(stack, e.to_user_facing());
+            return false;
+        }
+
+        toast!(
+            stack,
+            gettext("Subtitle downloaded, waiting for the server...")
+        );
+
+        for _ in 0..POLL_ATTEMPTS {
+            glib::timeout_future_seconds(POLL_INTERVAL_SECS).await;
+
+            let Ok(streams) = self.subtitle_streams().await else {
+                continue;
+            };
+
+            if let Some((_, path)) = streams
+                .into_iter()
+                .find(|(index, _)| !known.iter().any(|(known_index, _)| known_index == index))
+            {
+                self.emit_by_name::<()>("subtitle-downloaded", &[&path.unwrap_or_default()]);
+                toast!(stack, gettext("Subtitle added"));
+                return true;
+            }
+        }
+
+        toast!(
+            stack,
+            gettext("Subtitle downloaded, it will appear once the server refreshes the item")
+        );
+        true
+    }
+
+    /// Index and path to stream every subtitle stream of the media source
+    /// from.
+    async fn subtitle_streams(&self) -> anyhow::Result<Vec<(u64, Option<String>)>> {
+        let id = self.id();
+        let media_source_id = self.media_source_id();
+        let media =
+            spawn_tokio(async move { EMBY_CLIENT.get_sub(&id, &media_source_id).await }).await?;
+
+        let Some(source) = media
+            .media_sources
+            .into_iter()
+            .find(|source| source.id == self.media_source_id())
+        else {
+            return Ok(Vec::new());
+        };
+
+        Ok(source
+            .media_streams
+            .into_iter()
+            .filter(|stream| stream.stream_type == "Subtitle")
+            .map(|stream| {
+                let path = stream.delivery_url.or_else(|| {
+                    stream.codec.as_ref().map(|codec| {
+                        EMBY_CLIENT.get_subtitle_delivery_path(
+                            &self.id(),
+                            &source.id,
+                            stream.index,
+                            codec,
+                        )
+                    })
+                });
+                (stream.index, path)
+            })
+            .collect())
+    }
+
+    pub fn connect_subtitle_downloaded<F: Fn(&Self, String) + 'static>(
+        &self, f: F,
+    ) -> glib::SignalHandlerId {
+        self.connect_closure(
+            "subtitle-downloaded",
+            true,
+            glib::closure_local!(move |obj: Self, path: String| {
+                f(&obj, path);
+            }),
+        )
+    }
+}
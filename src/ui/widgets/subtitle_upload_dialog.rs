@@ -0,0 +1,352 @@
+use adw::{
+    prelude::*,
+    subclass::prelude::*,
+};
+use gettextrs::gettext;
+use gtk::{
+    gio,
+    glib,
+    template_callbacks,
+};
+
+use crate::{
+    client::{
+        emby_client::EMBY_CLIENT,
+        error::UserFacingError,
+    },
+    toast,
+    ui::models::languages::{
+        iso639_2,
+        language_matches,
+        language_name,
+        subtitle_language_codes,
+    },
+    utils::spawn_tokio,
+};
+
+/// Text formats the server can store as an external subtitle.
+const UPLOAD_FORMATS: &[&str] = &["srt", "ass", "ssa", "vtt"];
+
+mod imp {
+    use std::cell::{
+        OnceCell,
+        RefCell,
+    };
+
+    use glib::subclass::InitializingObject;
+    use gtk::CompositeTemplate;
+
+    use super::*;
+
+    #[derive(Debug, Default, CompositeTemplate, glib::Properties)]
+    #[template(resource = "/moe/tsuna/tsukimi/ui/subtitle_upload_dialog.ui")]
+    #[properties(wrapper_type = super::SubtitleUploadDialog)]
+    pub struct SubtitleUploadDialog {
+        #[property(get, set, construct_only)]
+        pub id: OnceCell<String>,
+        #[template_child]
+        pub page: TemplateChild<adw::PreferencesPage>,
+        #[template_child]
+        pub file_row: TemplateChild<adw::ActionRow>,
+        #[template_child]
+        pub language_combo: TemplateChild<adw::ComboRow>,
+        #[template_child]
+        pub forced_switchrow: TemplateChild<adw::SwitchRow>,
+        #[template_child]
+        pub sdh_switchrow: TemplateChild<adw::SwitchRow>,
+        #[template_child]
+        pub upload_button: TemplateChild<gtk::Button>,
+        pub language_codes: RefCell<Vec<String>>,
+        /// Format and contents of the validated file.
+        pub subtitle: RefCell<Option<(String, Vec<u8>)>>,
+    }
+
+    #[glib::object_subclass]
+    impl ObjectSubclass for SubtitleUploadDialog {
+        const NAME: &'static str = "SubtitleUploadDialog";
+        type Type = super::SubtitleUploadDialog;
+        type ParentType = adw::Dialog;
+
+        fn class_init(klass: &mut Self::Class) {
+            klass.bind_template();
+            klass.bind_template_instance_callbacks();
+        }
+
+        fn instance_init(obj: &InitializingObject<Self>) {
+            obj.init_template();
+        }
+    }
+
+    #[glib::derived_properties]
+    impl ObjectImpl for SubtitleUploadDialog {
+        fn constructed(&self) {
+            self.parent_constructed();
+            self.obj().set_languages();
+        }
+    }
+
+    impl WidgetImpl for SubtitleUploadDialog {}
+    impl AdwDialogImpl for SubtitleUploadDialog {}
+}
+
+glib::wrapper! {
+    pub struct SubtitleUploadDialog(ObjectSubclass<imp::SubtitleUploadDialog>)
+        @extends gtk::Widget, adw::Dialog, @implements gtk::Accessible, gtk::Root;
+}
+
+#[template_callbacks]
+impl SubtitleUploadDialog {
+    pub fn new(id: &str) -> Self {
+        glib::Object::builder().property("id", id).build()
+    }
+
+    fn set_languages(&self) {
+        let imp = self.imp();
+        let codes = subtitle_language_codes();
+        let names: Vec<String> = codes.iter().map(|code| language_name(code)).collect();
+        let model = gtk::StringList::new(&names.iter().map(String::as_str).collect::<Vec<_>>());
+
+        imp.language_combo
+            .set_expression(Some(gtk::PropertyExpression::new(
+                gtk::StringObject::static_type(),
+                None::<gtk::Expression>,
+                "string",
+            )));
+        imp.language_combo.set_model(Some(&model));
+        imp.language_codes.replace(codes);
+    }
+
+    fn selected_language(&self) -> Option<String> {
+        let imp = self.imp();
+        let selected = imp.language_combo.selected() as usize;
+        imp.language_codes.borrow().get(selected).cloned()
+    }
+
+    #[template_callback]
+    async fn on_choose_file_clicked(&self) {
+        let filter = gtk::FileFilter::new();
+        filter.set_name(Some(&gettext("Subtitles")));
+        for extension in UPLOAD_FORMATS {
+            filter.add_suffix(extension);
+        }
+        let model = gio::ListStore::new::<gtk::FileFilter>();
+        model.append(&filter);
+
+        let window = self.root().and_downcast::<gtk::Window>();
+        let filedialog = gtk::FileDialog::builder()
+            .modal(true)
+            .title(gettext("Select a subtitle file"))
+            .filters(&model)
+            .build();
+
+        let Ok(file) = filedialog.open_future(window.as_ref()).await else {
+            return;
+        };
+
+        self.set_file(&file).await;
+    }
+
+    async fn set_file(&self, file: &gio::File) {
+        let imp = self.imp();
+        imp.subtitle.replace(None);
+        imp.upload_button.set_sensitive(false);
+
+        let file_name = file
+            .basename()
+            .map(|name| name.to_string_lossy().to_string())
+            .unwrap_or_default();
+        imp.file_row.set_subtitle(&file_name);
+
+        let Some(format) = subtitle_format(&file_name) else {
+            toast!(imp.page, gettext("Unsupported subtitle format"));
+            return;
+        };
+
+        let bytes = match file.load_bytes_future().await {
+            Ok((bytes, _)) => bytes.to_vec(),
+            Err(_) => {
+                toast!(imp.page, gettext("Failed to read file"));
+                return;
+            }
+        };
+
+        if !is_valid_subtitle(&format, &bytes) {
+            toast!(
+                imp.page,
+                gettext("The file does not look like a valid subtitle")
+            );
+            return;
+        }
+
+        let (language, forced, sdh) = guess_from_file_name(&file_name);
+        if let Some(language) = language {
+            let position = imp
+                .language_codes
+                .borrow()
+                .iter()
+                .position(|code| language_matches(code, &language));
+            if let Some(position) = position {
+                imp.language_combo.set_selected(position as u32);
+            }
+        }
+        imp.forced_switchrow.set_active(forced);
+        imp.sdh_switchrow.set_active(sdh);
+
+        imp.subtitle.replace(Some((format, bytes)));
+        imp.upload_button.set_sensitive(true);
+    }
+
+    #[template_callback]
+    async fn on_upload_clicked(&self) {
+        let imp = self.imp();
+
+        let Some((format, bytes)) = imp.subtitle.borrow().clone() else {
+            return;
+        };
+        let Some(language) = self.selected_language() else {
+            toast!(imp.page, gettext("Please select a language"));
+            return;
+        };
+        let language = iso639_2(&language).map_or(language, str::to_string);
+
+        use base64::{
+            engine::general_purpose::STANDARD,
+            Engine as _,
+        };
+        let data = STANDARD.encode(bytes);
+
+        let id = self.id();
+        let is_forced = imp.forced_switchrow.is_active();
+        let is_hearing_impaired = imp.sdh_switchrow.is_active();
+
+        imp.upload_button.set_sensitive(false);
+
+        match spawn_tokio(async move {
+            EMBY_CLIENT
+                .upload_subtitle(
+                    &id,
+                    &language,
+                    &format,
+                    is_forced,
+                    is_hearing_impaired,
+                    &data,
+                )
+                .await
+        })
+        .await
+        {
+            Ok(_) => {
+                toast!(self, gettext("Subtitle uploaded"));
+                self.close();
+            }
+            Err(e) => {
+                toast!(imp.page, e.to_user_facing());
+                imp.upload_button.set_sensitive(true);
+            }
+        }
+    }
+}
+
+fn subtitle_format(file_name: &str) -> Option<String> {
+    let (_, extension) = file_name.rsplit_once('.')?;
+    let extension = extension.to_lowercase();
+    UPLOAD_FORMATS
+        .contains(&extension.as_str())
+        .then_some(extension)
+}
+
+/// Cheap sanity check so a wrong file is caught before the server stores it.
+fn is_valid_subtitle(format: &str, bytes: &[u8]) -> bool {
+    let text = String::from_utf8_lossy(bytes);
+    let text = text.trim_start_matches('\u{feff}').trim_start();
+    match format {
+        "srt" => text.contains("-->"),
+        "vtt" => text.starts_with("WEBVTT"),
+        "ass" | "ssa" => text.contains("[Script Info]") || text.contains("[Events]"),
+        _ => false,
+    }
+}
+
+/// Language and flags from names like `Movie.en.forced.srt` or
+/// `Movie.ger.sdh.ass`.
+///
+/// Only the last tag, or the one before a flag, is taken as the language, so
+/// title words like `It` are left alone. `hi` is Hindi unless it follows a
+/// language.
+fn guess_from_file_name(file_name: &str) -> (Option<String>, bool, bool) {
+    let mut forced = false;
+    let mut sdh = false;
+
+    // The first part is the title, never a tag.
+    let mut tags: Vec<String> = file_name
+        .split('.')
+        .skip(1)
+        .map(str::to_lowercase)
+        .collect();
+    tags.pop();
+    let last = tags.pop();
+    let before = tags.pop();
+
+    let flagged = match last.as_deref() {
+        Some("forced" | "foreign") => {
+            forced = true;
+            true
+        }
+        Some("sdh" | "cc") => {
+            sdh = true;
+            true
+        }
+        Some("hi") if before.as_deref().and_then(iso639_2).is_some() => {
+            sdh = true;
+            true
+        }
+        _ => false,
+    };
+    let language = if flagged { before } else { last };
+
+    (language.filter(|tag| iso639_2(tag).is_some()), forced, sdh)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_guess_from_file_name() {
+        assert_eq!(
+            guess_from_file_name("Movie.2019.en.forced.srt"),
+            (Some("en".to_string()), true, false)
+        );
+        assert_eq!(
+            guess_from_file_name("Show.S01E01.ger.SDH.ass"),
+            (Some("ger".to_string()), false, true)
+        );
+        assert_eq!(guess_from_file_name("eng.srt"), (None, false, false));
+        assert_eq!(
+            guess_from_file_name("Movie.en.hi.srt"),
+            (Some("en".to_string()), false, true)
+        );
+        assert_eq!(
+            guess_from_file_name("Movie.hi.srt"),
+            (Some("hi".to_string()), false, false)
+        );
+        assert_eq!(
+            guess_from_file_name("The.It.Crowd.srt"),
+            (None, false, false)
+        );
+    }
+
+    #[test]
+    fn test_is_valid_subtitle() {
+        assert!(is_valid_subtitle(
+            "srt",
+            b"1\n00:00:01,000 --> 00:00:02,000\nHello\n"
+        ));
+        assert!(is_valid_subtitle("vtt", "\u{feff}WEBVTT\n".as_bytes()));
+        assert!(!is_valid_subtitle(
+            "vtt",
+            b"1\n00:00:01,000 --> 00:00:02,000\n"
+        ));
+        assert!(!is_valid_subtitle("srt", b"\x89PNG"));
+    }
+}
@@ -0,0 +1,145 @@
+use adw::{
+    prelude::*,
+    subclass::prelude::*,
+};
+use gettextrs::gettext;
+use gtk::{
+    glib,
+    template_callbacks,
+};
+
+use crate::{
+    toast,
+    ui::models::track_memory::{
+        TrackChoice,
+        TrackMemory,
+    },
+};
+
+mod imp {
+    use std::cell::OnceCell;
+
+    use glib::subclass::InitializingObject;
+    use gtk::{
+        glib,
+        CompositeTemplate,
+    };
+
+    use super::*;
+
+    #[derive(Debug, Default, CompositeTemplate, glib::Properties)]
+    #[template(resource = "/moe/tsuna/tsukimi/ui/track_memory_dialog.ui")]
+    #[properties(wrapper_type = super::TrackMemoryDialog)]
+    pub struct TrackMemoryDialog {
+        #[property(get, set, construct_only)]
+        pub id: OnceCell<String>,
+        #[template_child]
+        pub audio_lang_entry: TemplateChild<adw::EntryRow>,
+        #[template_child]
+        pub speed_adj: TemplateChild<gtk::Adjustment>,
+        #[template_child]
+        pub sub_off_switch: TemplateChild<adw::SwitchRow>,
+        #[template_child]
+        pub sub_lang_entry: TemplateChild<adw::EntryRow>,
+        #[template_child]
+        pub sub_title_entry: TemplateChild<adw::EntryRow>,
+        #[template_child]
+        pub sub_delay_adj: TemplateChild<gtk::Adjustment>,
+    }
+
+    #[glib::object_subclass]
+    impl ObjectSubclass for TrackMemoryDialog {
+        const NAME: &'static str = "TrackMemoryDialog";
+        type Type = super::TrackMemoryDialog;
+        type ParentType = adw::Dialog;
+
+        fn class_init(klass: &mut Self::Class) {
+            klass.bind_template();
+            klass.bind_template_instance_callbacks();
+        }
+
+        fn instance_init(obj: &InitializingObject<Self>) {
+            obj.init_template();
+        }
+    }
+
+    #[glib::derived_properties]
+    impl ObjectImpl for TrackMemoryDialog {
+        fn constructed(&self) {
+            self.parent_constructed();
+            self.obj().load_choice();
+        }
+    }
+
+    impl WidgetImpl for TrackMemoryDialog {}
+    impl AdwDialogImpl for TrackMemoryDialog {}
+}
+
+glib::wrapper! {
+
+    pub struct TrackMemoryDialog(ObjectSubclass<imp::TrackMemoryDialog>)
+        @extends gtk::Widget, adw::Dialog, @implements gtk::Accessible, gtk::Root;
+}
+
+#[template_callbacks]
+impl TrackMemoryDialog {
+    pub fn new(id: &str) -> Self {
+        glib::Object::builder().property("id", id).build()
+    }
+
+    fn load_choice(&self) {
+        let imp = self.imp();
+        let choice = TrackMemory::get(&self.id(), None).unwrap_or_default();
+
+        imp.audio_lang_entry
+            .set_text(choice.audio_lang.as_deref().unwrap_or_default());
+        imp.speed_adj.set_value(choice.speed.unwrap_or(1.0));
+        imp.sub_off_switch.set_active(choice.sub_off);
+        imp.sub_lang_entry
+            .set_text(choice.sub_lang.as_deref().unwrap_or_default());
+        imp.sub_title_entry
+            .set_text(choice.sub_title.as_deref().unwrap_or_default());
+        imp.sub_delay_adj.set_value(choice.sub_delay.unwrap_or(0.0));
+    }
+
+    #[template_callback]
+    fn on_save(&self) {
+        let imp = self.imp();
+        let text = |entry: &adw::EntryRow| {
+            let text = entry.text().trim().to_string();
+            (!text.is_empty()).then_some(text)
+        };
+
+        let speed = imp.speed_adj.value();
+        let sub_delay = imp.sub_delay_adj.value();
+        let sub_off = imp.sub_off_switch.is_active();
+
+        let choice = TrackChoice {
+            audio_lang: text(&imp.audio_lang_entry),
+            sub_lang: if sub_off {
+                None
+            } else {
+                text(&imp.sub_lang_entry)
+            },
+            sub_title: if sub_off {
+                None
+            } else {
+                text(&imp.sub_title_entry)
+            },
+            sub_off,
+            sub_delay: (sub_delay != 0.0).then_some(sub_delay),
+            speed: (speed != 1.0).then_some(speed),
+            ..TrackMemory::get(&self.id(), None).unwrap_or_default()
+        };
+
+        TrackMemory::replace(&self.id(), choice);
+        self.close();
+    }
+
+    #[template_callback]
+    fn on_clear(&self) {
+        TrackMemory::clear(&self.id());
+        self.load_choice();
+        toast!(self, gettext("Remembered tracks cleared"));
+    }
+}
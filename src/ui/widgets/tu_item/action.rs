@@ -9,6 +9,7 @@ use crate::{
         provider::IS_ADMIN,
         widgets::{
             missing_episodes_dialog::MissingEpisodesDialog,
+            subtitle_upload_dialog::SubtitleUploadDialog,
             window::Window,
         },
     },
@@ -179,10 +180,9 @@ where
             "Movie" | "Series" | "Episode" | "MusicVideo" | "AdultVideo" | "Audio" => {
                 self.set_item_action(true, true, true)
             }
-            "MusicAlbum" | "BoxSet" | "Tag" | "Genre" | "Views" | "Person" | "Actor"
-            | "Director" | "Writer" | "Producer" | "TvChannel" | "Folder" | "Season" => {
-                self.set_item_action(false, true, true)
-            }
+            "MusicAlbum" | "MusicArtist" | "MusicGenre" | "BoxSet" | "Tag" | "Genre" | "Views"
+            | "Person" | "Actor" | "Director" | "Writer" | "Producer" | "TvChannel" | "Folder"
+            | "Season" => self.set_item_action(false, true, true),
             "CollectionFolder" | "UserView" => self.set_item_action(false, false, false),
             _ => None,
         }
@@ -233,6 +233,44 @@ where
                 .build()]);
         }
 
+        if matches!(
+            self.item().item_type().as_str(),
+            "Audio" | "MusicAlbum" | "MusicArtist" | "MusicGenre"
+        ) {
+            for (name, radio) in [("instant-mix", false), ("radio", true)] {
+                action_group.add_action_entries([gio::ActionEntry::builder(name)
+                    .activate(glib::clone!(
+                        #[weak(rename_to = obj)]
+                        self,
+                        move |_, _, _| {
+                            let Some(window) = obj.root().and_downcast::<Window>() else {
+                                return;
+                            };
+                            let item = obj.item();
+                            window.play_instant_mix(item.id(), item.item_type(), radio);
+                        }
+                    ))
+                    .build()]);
+            }
+        }
+
+        // The gallery tells when there are none, listing them here would
+        // read the disk on every popover.
+        if is_playable {
+            action_group.add_action_entries([gio::ActionEntry::builder("screenshots")
+                .activate(glib::clone!(
+                    #[weak(rename_to = obj)]
+                    self,
+                    move |_, _, _| {
+                        let id = obj.item().id();
+                        let dialog =
+                            crate::ui::widgets::screenshot_gallery::ScreenshotGallery::new(&id);
+                        crate::insert_editm_dialog!(obj, dialog);
+                    }
+                ))
+                .build()]);
+        }
+
         if IS_ADMIN.load(std::sync::atomic::Ordering::Relaxed) {
             action_group.add_action_entries([gio::ActionEntry::builder("scan")
                 .activate(glib::clone!(
@@ -335,6 +373,29 @@ where
                 .build()]);
             }
 
+            if matches!(
+                self.item().item_type().as_str(),
+                "Movie" | "Episode" | "MusicVideo" | "AdultVideo" | "Video"
+            ) {
+                action_group.add_action_entries([gio::ActionEntry::builder("upload-subtitle")
+                    .activate(glib::clone!(
+                        #[weak(rename_to = obj)]
+                        self,
+                        move |_, _, _| {
+                            spawn(glib::clone!(
+                                #[weak]
+                                obj,
+                                async move {
+                                    let id = obj.item().id();
+                                    let dialog = SubtitleUploadDialog::new(&id);
+                                    crate::insert_editm_dialog!(obj, dialog);
+                                }
+                            ))
+                        }
+                    ))
+                    .build()]);
+            }
+
             if self.item().item_type() == "Series" {
                 action_group.add_action_entries([gio::ActionEntry::builder("view-missing")
                     .activate(glib::clone!(
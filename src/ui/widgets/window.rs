@@ -28,6 +28,7 @@ mod imp {
         mpv::{
             control_sidebar::MPVControlSidebar,
             page::MPVPage,
+            syncplay_panel::MPVSyncPlayPanel,
         },
         provider::tu_object::TuObject,
         widgets::{
@@ -102,6 +103,8 @@ mod imp {
         pub mpv_playlist: TemplateChild<gtk::ListView>,
         #[template_child]
         pub mpv_control_sidebar: TemplateChild<MPVControlSidebar>,
+        #[template_child]
+        pub mpv_syncplay_panel: TemplateChild<MPVSyncPlayPanel>,
 
         #[template_child]
         pub mpv_view: TemplateChild<adw::OverlaySplitView>,
@@ -139,6 +142,7 @@ mod imp {
             MPVPage::ensure_type();
             ListExpandRow::ensure_type();
             MPVControlSidebar::ensure_type();
+            MPVSyncPlayPanel::ensure_type();
             ThemeSwitcher::ensure_type();
             klass.bind_template();
             klass.bind_template_instance_callbacks();
@@ -189,6 +193,8 @@ mod imp {
             ));
             self.mpv_control_sidebar
                 .set_player(Some(&self.mpvnav.imp().video.get()));
+            self.mpv_syncplay_panel
+                .set_syncplay(Some(&self.mpvnav.imp().syncplay));
 
             let obj = self.obj();
             obj.set_fonts();
@@ -223,6 +229,7 @@ mod imp {
             self.obj()
                 .save_window_size()
                 .expect("Failed to save window state");
+            self.player_toolbar_box.save_queue();
             // Allow to invoke other event handlers
             glib::Propagation::Proceed
         }
@@ -248,6 +255,7 @@ use super::{
     home::HomePage,
     item::ItemPage,
     liked::LikedPage,
+    resume_dialog::choose_resume,
     search::SearchPage,
     server_action_row,
     server_panel::ServerPanel,
@@ -257,12 +265,27 @@ use super::{
 use crate::{
     client::{
         emby_client::EMBY_CLIENT,
-        structs::Back,
+        error::UserFacingError,
+        structs::{
+            Back,
+            TICKS_PER_SECOND,
+        },
         Account,
     },
     toast,
     ui::{
-        models::SETTINGS,
+        models::{
+            keybindings::{
+                self,
+                KeyScope,
+                KEY_ACTIONS,
+            },
+            SETTINGS,
+        },
+        mpv::multipart::{
+            load_parts,
+            MultiPart,
+        },
         provider::{
             core_song::CoreSong,
             tu_item::TuItem,
@@ -463,6 +486,7 @@ impl Window {
         self.account_setup();
         self.remove_all();
         self.homepage();
+        self.imp().player_toolbar_box.restore_queue();
 
         spawn(glib::clone!(
             #[weak(rename_to = obj)]
@@ -529,6 +553,16 @@ impl Window {
         let settings = Settings::new(APP_ID);
         let is_overlay = settings.boolean("is-overlay");
         self.overlay_sidebar(is_overlay);
+        settings.connect_changed(
+            Some("key-bindings"),
+            glib::clone!(
+                #[weak(rename_to = obj)]
+                self,
+                move |_, _| {
+                    obj.set_shortcuts();
+                }
+            ),
+        );
         self.imp()
             .settings
             .set(settings)
@@ -759,12 +793,76 @@ impl Window {
             .bind_song_model(active_model, active_core_song);
     }
 
+    pub fn queue_song(&self, core_song: CoreSong, next: bool) {
+        self.imp().player_toolbar_box.queue_song(core_song, next);
+    }
+
+    pub fn play_instant_mix(&self, id: String, item_type: String, radio: bool) {
+        self.imp()
+            .player_toolbar_box
+            .play_instant_mix(id, item_type, radio);
+    }
+
+    pub fn play_audiobook(&self, item: TuItem) {
+        self.imp().player_toolbar_box.play_audiobook(item);
+    }
+
     #[allow(clippy::too_many_arguments)]
     pub fn play_media(
         &self, url: String, suburl: Option<String>, item: TuItem, episode_list: Vec<TuItem>,
         back: Option<Back>, _selected: Option<String>, percentage: f64, matcher: Option<String>,
+    ) {
+        spawn(glib::clone!(
+            #[weak(rename_to = obj)]
+            self,
+            async move {
+                let multipart = match load_parts(&item, &url, back.clone(), matcher.clone()).await {
+                    Ok(multipart) => multipart,
+                    Err(e) => {
+                        toast!(obj, e.to_user_facing());
+                        None
+                    }
+                };
+
+                // Finished parts are reset on the server, a multi-part movie
+                // resumes in the part that was left unfinished
+                let (percentage, position) = match &multipart {
+                    Some(multipart) => (multipart.resume_percentage(), multipart.resume_position()),
+                    None => (
+                        percentage,
+                        item.playback_position_ticks() as f64 / TICKS_PER_SECOND,
+                    ),
+                };
+                let percentage = if percentage > 0.0 {
+                    match choose_resume(&obj, &item, position).await {
+                        Some(true) => percentage,
+                        Some(false) => 0.0,
+                        None => return,
+                    }
+                } else {
+                    0.0
+                };
+                obj.start_media(
+                    url,
+                    suburl,
+                    item,
+                    episode_list,
+                    back,
+                    multipart,
+                    percentage,
+                    matcher,
+                );
+            }
+        ));
+    }
+
+    #[allow(clippy::too_many_arguments)]
+    fn start_media(
+        &self, url: String, suburl: Option<String>, item: TuItem, episode_list: Vec<TuItem>,
+        back: Option<Back>, multipart: Option<MultiPart>, percentage: f64, matcher: Option<String>,
     ) {
         let imp = self.imp();
+        imp.mpvnav.leave_pip();
         imp.stack.set_visible_child_name("mpv");
         self.prevent_suspend();
         self.set_mpv_playlist(&episode_list);
@@ -774,6 +872,7 @@ impl Window {
             item,
             episode_list,
             back,
+            multipart,
             percentage,
             matcher,
         );
@@ -894,14 +993,37 @@ impl Window {
         }
     }
 
+    /// Apply the key bindings and build the shortcuts window from them.
     pub fn set_shortcuts(&self) {
-        let Some(window) =
-            gtk::Builder::from_resource("/moe/tsuna/tsukimi/ui/mpv_shortcuts_window.ui")
-                .object::<gtk::ShortcutsWindow>("mpv_shortcuts")
-        else {
-            eprintln!("Failed to load shortcuts window");
-            return;
-        };
+        keybindings::apply_app_accels();
+
+        let section = gtk::ShortcutsSection::builder()
+            .section_name("shortcuts")
+            .max_height(12)
+            .build();
+
+        for (title, player) in [(gettext("Player"), true), (gettext("Application"), false)] {
+            let group = gtk::ShortcutsGroup::builder().title(title).build();
+            for action in KEY_ACTIONS
+                .iter()
+                .filter(|action| (action.scope == KeyScope::Player) == player)
+            {
+                let accels = action.bindings();
+                if accels.is_empty() {
+                    continue;
+                }
+                group.add_shortcut(
+                    &gtk::ShortcutsShortcut::builder()
+                        .title(action.title())
+                        .accelerator(accels.join(" "))
+                        .build(),
+                );
+            }
+            section.add_group(&group);
+        }
+
+        let window = gtk::ShortcutsWindow::builder().modal(true).build();
+        window.add_section(&section);
         self.set_help_overlay(Some(&window));
     }
 